@@ -0,0 +1,497 @@
+//! Algorithm expressions: move sequences built up from commutators,
+//! conjugates, repetition and inversion, so a sequence like
+//! `IBUR' LB IBUR LB'` can be written as `[IBUR', LB]`.
+//!
+//! The syntax extends `moves::parse_moves`'s: any whitespace-separated
+//! sequence of moves is already an expression. On top of that:
+//!
+//! - `[A, B]` is the commutator `A B A' B'`
+//! - `[A: B]` is the conjugate `A B A'`
+//! - `(A)` groups, and a group or bracket can take a repeat count and/or a
+//!   `'` to invert it, the same suffixes a single move takes: `(A)3`,
+//!   `[A, B]'`, `(A B)2'`
+//!
+//! `Alg::expand` flattens an expression to the moves it stands for. Repeat
+//! counts are capped at `MAX_REPEAT` and whole expressions at `MAX_MOVES`
+//! expanded moves, so a typo can't ask for billions of them.
+
+use std::fmt;
+use std::ops::Range;
+
+use crate::moves::{Move, ParseMoveError, ParseMoveErrorKind, invert_moves, parse_move};
+
+/// Largest repeat count a group or bracket takes.
+const MAX_REPEAT: u32 = 1000;
+/// Most moves an expression may expand to.
+const MAX_MOVES: usize = 10_000;
+
+/// A parsed algorithm expression.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Alg {
+    Move(Move),
+    /// Each item in turn.
+    Sequence(Vec<Alg>),
+    /// `[A, B]`: `A B A' B'`.
+    Commutator(Box<Alg>, Box<Alg>),
+    /// `[A: B]`: `A B A'`.
+    Conjugate(Box<Alg>, Box<Alg>),
+    /// `(A)n`: `A`, `n` times over.
+    Repeat(Box<Alg>, u32),
+    /// `(A)'`: the moves undoing `A`.
+    Inverse(Box<Alg>),
+}
+
+impl Alg {
+    /// The flat move sequence this expression stands for.
+    pub(crate) fn expand(&self) -> Vec<Move> {
+        match self {
+            Alg::Move(mv) => vec![*mv],
+            Alg::Sequence(items) => items.iter().flat_map(Alg::expand).collect(),
+            Alg::Commutator(a, b) => {
+                let (a, b) = (a.expand(), b.expand());
+                [&a[..], &b, &invert_moves(&a), &invert_moves(&b)].concat()
+            }
+            Alg::Conjugate(a, b) => {
+                let a = a.expand();
+                [&a[..], &b.expand(), &invert_moves(&a)].concat()
+            }
+            Alg::Repeat(alg, count) => alg.expand().repeat(*count as usize),
+            Alg::Inverse(alg) => invert_moves(&alg.expand()),
+        }
+    }
+
+    /// How many moves `expand` gives, worked out without expanding;
+    /// saturates rather than overflowing.
+    fn expanded_len(&self) -> usize {
+        match self {
+            Alg::Move(_) => 1,
+            Alg::Sequence(items) => items
+                .iter()
+                .fold(0, |len, item| len.saturating_add(item.expanded_len())),
+            Alg::Commutator(a, b) => a
+                .expanded_len()
+                .saturating_add(b.expanded_len())
+                .saturating_mul(2),
+            Alg::Conjugate(a, b) => a
+                .expanded_len()
+                .saturating_mul(2)
+                .saturating_add(b.expanded_len()),
+            Alg::Repeat(alg, count) => alg.expanded_len().saturating_mul(*count as usize),
+            Alg::Inverse(alg) => alg.expanded_len(),
+        }
+    }
+
+    /// Writes `self` so a repeat count or `'` can follow it: brackets
+    /// already delimit themselves, anything else is wrapped in parentheses.
+    fn fmt_operand(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Alg::Commutator(..) | Alg::Conjugate(..) => write!(f, "{self}"),
+            _ => write!(f, "({self})"),
+        }
+    }
+}
+
+impl fmt::Display for Alg {
+    /// Writes the expression such that `parse_alg` reads it back as the same
+    /// `Alg`: a nested sequence keeps its parentheses, brackets separate
+    /// their halves with `, ` or `: `.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Alg::Move(mv) => write!(f, "{mv}"),
+            Alg::Sequence(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    match item {
+                        Alg::Sequence(_) => write!(f, "({item})")?,
+                        _ => write!(f, "{item}")?,
+                    }
+                }
+                Ok(())
+            }
+            Alg::Commutator(a, b) => write!(f, "[{a}, {b}]"),
+            Alg::Conjugate(a, b) => write!(f, "[{a}: {b}]"),
+            Alg::Repeat(alg, count) => {
+                alg.fmt_operand(f)?;
+                write!(f, "{count}")
+            }
+            Alg::Inverse(alg) => {
+                match &**alg {
+                    Alg::Repeat(..) => write!(f, "{alg}")?,
+                    _ => alg.fmt_operand(f)?,
+                }
+                write!(f, "'")
+            }
+        }
+    }
+}
+
+/// What went wrong parsing an expression; see `ParseAlgError`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ParseAlgErrorKind {
+    /// A move token that doesn't parse (see `ParseMoveErrorKind`).
+    Move(ParseMoveErrorKind),
+    /// A `[` or `(` with no matching close.
+    Unclosed(char),
+    /// A bracket, separator or parenthesis where it can't go.
+    Unexpected(char),
+    /// A `[...]` with no `,` or `:` in it.
+    MissingSeparator,
+    /// A repeat count over `MAX_REPEAT`.
+    BadCount,
+    /// An expression expanding to more than `MAX_MOVES` moves.
+    TooLong,
+}
+
+impl fmt::Display for ParseAlgErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseAlgErrorKind::Move(kind) => write!(f, "{kind}"),
+            ParseAlgErrorKind::Unclosed(c) => write!(f, "'{c}' is never closed"),
+            ParseAlgErrorKind::Unexpected(c) => write!(f, "unexpected '{c}'"),
+            ParseAlgErrorKind::MissingSeparator => {
+                write!(f, "expected ',' (commutator) or ':' (conjugate)")
+            }
+            ParseAlgErrorKind::BadCount => write!(f, "repeat count is over {MAX_REPEAT}"),
+            ParseAlgErrorKind::TooLong => write!(f, "expands to more than {MAX_MOVES} moves"),
+        }
+    }
+}
+
+/// An expression parse failure, with the byte range of the offending input
+/// so a UI can point at it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ParseAlgError {
+    pub(crate) span: Range<usize>,
+    pub(crate) kind: ParseAlgErrorKind,
+}
+
+impl fmt::Display for ParseAlgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (at {}..{})",
+            self.kind, self.span.start, self.span.end
+        )
+    }
+}
+
+impl std::error::Error for ParseAlgError {}
+
+impl From<ParseMoveError> for ParseAlgError {
+    fn from(err: ParseMoveError) -> Self {
+        Self {
+            span: err.span,
+            kind: ParseAlgErrorKind::Move(err.kind),
+        }
+    }
+}
+
+/// Characters that end a move token, besides whitespace.
+const DELIMITERS: [char; 6] = ['[', ']', '(', ')', ',', ':'];
+
+/// Recursive-descent parser state: the whole input and how far into it
+/// parsing has got, in bytes.
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn error_at_peek(&self, kind: ParseAlgErrorKind) -> ParseAlgError {
+        let len = self.peek().map_or(0, char::len_utf8);
+        ParseAlgError {
+            span: self.pos..self.pos + len,
+            kind,
+        }
+    }
+
+    /// Items up to the next separator, closing bracket or the end of the
+    /// input. A single item stands for itself rather than a one-item
+    /// sequence, so `(RU)'` is the inverse of one move.
+    fn sequence(&mut self) -> Result<Alg, ParseAlgError> {
+        let mut items = Vec::new();
+        loop {
+            while self.peek().is_some_and(char::is_whitespace) {
+                self.pos += 1;
+            }
+            match self.peek() {
+                None | Some(',' | ':' | ']' | ')') => break,
+                Some(_) => items.push(self.item()?),
+            }
+        }
+        Ok(if items.len() == 1 {
+            items.pop().expect("one item")
+        } else {
+            Alg::Sequence(items)
+        })
+    }
+
+    /// One move, bracket or group, with a group's or bracket's suffix.
+    fn item(&mut self) -> Result<Alg, ParseAlgError> {
+        let start = self.pos;
+        let alg = match self.peek() {
+            Some('[') => self.bracket()?,
+            Some('(') => {
+                self.pos += 1;
+                let inner = self.sequence()?;
+                match self.peek() {
+                    Some(')') => self.pos += 1,
+                    None => {
+                        return Err(ParseAlgError {
+                            span: start..start + 1,
+                            kind: ParseAlgErrorKind::Unclosed('('),
+                        });
+                    }
+                    Some(c) => return Err(self.error_at_peek(ParseAlgErrorKind::Unexpected(c))),
+                }
+                inner
+            }
+            // A move takes its own count and `'`, in `parse_move`.
+            _ => return self.move_token(),
+        };
+        self.suffix(alg)
+    }
+
+    /// `[A, B]` or `[A: B]`, from its opening `[`.
+    fn bracket(&mut self) -> Result<Alg, ParseAlgError> {
+        let start = self.pos;
+        let unclosed = ParseAlgError {
+            span: start..start + 1,
+            kind: ParseAlgErrorKind::Unclosed('['),
+        };
+        self.pos += 1;
+        let a = self.sequence()?;
+        let separator = match self.peek() {
+            Some(c @ (',' | ':')) => c,
+            Some(']') => {
+                return Err(ParseAlgError {
+                    span: start..self.pos + 1,
+                    kind: ParseAlgErrorKind::MissingSeparator,
+                });
+            }
+            Some(c) => return Err(self.error_at_peek(ParseAlgErrorKind::Unexpected(c))),
+            None => return Err(unclosed),
+        };
+        self.pos += 1;
+        let b = self.sequence()?;
+        match self.peek() {
+            Some(']') => self.pos += 1,
+            Some(c) => return Err(self.error_at_peek(ParseAlgErrorKind::Unexpected(c))),
+            None => return Err(unclosed),
+        }
+        let (a, b) = (Box::new(a), Box::new(b));
+        Ok(if separator == ',' {
+            Alg::Commutator(a, b)
+        } else {
+            Alg::Conjugate(a, b)
+        })
+    }
+
+    /// An optional repeat count, then an optional `'`.
+    fn suffix(&mut self, mut alg: Alg) -> Result<Alg, ParseAlgError> {
+        let count_start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        if self.pos > count_start {
+            let span = count_start..self.pos;
+            let count = self.input[span.clone()]
+                .parse()
+                .ok()
+                .filter(|&count| count <= MAX_REPEAT)
+                .ok_or(ParseAlgError {
+                    span: span.clone(),
+                    kind: ParseAlgErrorKind::BadCount,
+                })?;
+            alg = Alg::Repeat(Box::new(alg), count);
+            if alg.expanded_len() > MAX_MOVES {
+                return Err(ParseAlgError {
+                    span,
+                    kind: ParseAlgErrorKind::TooLong,
+                });
+            }
+        }
+        if self.peek() == Some('\'') {
+            self.pos += 1;
+            alg = Alg::Inverse(Box::new(alg));
+        }
+        Ok(alg)
+    }
+
+    fn move_token(&mut self) -> Result<Alg, ParseAlgError> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| !c.is_whitespace() && !DELIMITERS.contains(&c))
+        {
+            self.pos += self.peek().map_or(0, char::len_utf8);
+        }
+        Ok(Alg::Move(parse_move(&self.input[start..self.pos], start)?))
+    }
+}
+
+/// Parses an algorithm expression (see the module docs). An empty or
+/// all-whitespace input is an empty sequence.
+pub(crate) fn parse_alg(input: &str) -> Result<Alg, ParseAlgError> {
+    let mut parser = Parser { input, pos: 0 };
+    let alg = parser.sequence()?;
+    if let Some(c) = parser.peek() {
+        return Err(parser.error_at_peek(ParseAlgErrorKind::Unexpected(c)));
+    }
+    // Nested brackets double with each level, so a short input can still
+    // expand too far without any one count doing it.
+    if alg.expanded_len() > MAX_MOVES {
+        return Err(ParseAlgError {
+            span: 0..input.len(),
+            kind: ParseAlgErrorKind::TooLong,
+        });
+    }
+    Ok(alg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::moves::parse_moves;
+    use crate::piece::Hypercube;
+
+    fn moves(text: &str) -> Vec<Move> {
+        parse_moves(text).unwrap()
+    }
+
+    #[test]
+    fn plain_move_sequences_expand_to_themselves() {
+        let text = "RU OBR' {2}IUFR2 {1-3}RU";
+        assert_eq!(parse_alg(text).unwrap().expand(), moves(text));
+        assert_eq!(parse_alg("  ").unwrap(), Alg::Sequence(Vec::new()));
+    }
+
+    #[test]
+    fn brackets_groups_and_suffixes_expand_as_written() {
+        for (alg, expanded) in [
+            ("[IBUR', LB]", "IBUR' LB IBUR LB'"),
+            ("[RU: LB]", "RU LB RU'"),
+            ("(RU LB)3", "RU LB RU LB RU LB"),
+            ("(RU LB)'", "LB' RU'"),
+            ("(RU LB)2'", "LB' RU' LB' RU'"),
+            ("[RU, LB]'", "LB RU LB' RU'"),
+            ("[IB': LB2] FUR", "IB' LB2 IB FUR"),
+            (
+                "RU [FU: [RU, LB]]2",
+                "RU FU RU LB RU' LB' FU' FU RU LB RU' LB' FU'",
+            ),
+        ] {
+            assert_eq!(parse_alg(alg).unwrap().expand(), moves(expanded), "{alg}");
+        }
+    }
+
+    #[test]
+    fn commutator_then_its_inverse_is_the_identity() {
+        let alg = parse_alg("[RU LB, {2}FUR'] ([RU LB, {2}FUR'])'").unwrap();
+        let mut cube = Hypercube::solved();
+        for mv in alg.expand() {
+            cube.apply_move(mv);
+        }
+        assert_eq!(cube, Hypercube::solved());
+    }
+
+    #[test]
+    fn display_round_trips_through_the_parser() {
+        for text in [
+            "RU OBR'",
+            "[IBUR', LB]",
+            "[RU LB: (FU IB)2]",
+            "RU (LB IB) OR",
+            "(RU)'",
+            "(RU LB)2'",
+            "[RU, LB]3",
+            "([RU, LB] FU)2 [{2}OBDL: [RU: LB]']",
+        ] {
+            let alg = parse_alg(text).unwrap();
+            assert_eq!(alg.to_string(), text);
+            assert_eq!(parse_alg(&alg.to_string()), Ok(alg));
+        }
+    }
+
+    #[test]
+    fn errors_point_at_the_offending_input() {
+        let error = |text| parse_alg(text).unwrap_err();
+        assert_eq!(
+            error("RU [LB, FU"),
+            ParseAlgError {
+                span: 3..4,
+                kind: ParseAlgErrorKind::Unclosed('['),
+            }
+        );
+        assert_eq!(
+            error("(RU LB"),
+            ParseAlgError {
+                span: 0..1,
+                kind: ParseAlgErrorKind::Unclosed('('),
+            }
+        );
+        assert_eq!(
+            error("[RU LB]"),
+            ParseAlgError {
+                span: 0..7,
+                kind: ParseAlgErrorKind::MissingSeparator,
+            }
+        );
+        assert_eq!(
+            error("RU LB)"),
+            ParseAlgError {
+                span: 5..6,
+                kind: ParseAlgErrorKind::Unexpected(')'),
+            }
+        );
+        assert_eq!(
+            error("[RU, LB, FU]"),
+            ParseAlgError {
+                span: 7..8,
+                kind: ParseAlgErrorKind::Unexpected(','),
+            }
+        );
+        assert_eq!(
+            error("(RU)99999999999"),
+            ParseAlgError {
+                span: 4..15,
+                kind: ParseAlgErrorKind::BadCount,
+            }
+        );
+        assert_eq!(
+            error("(RU)1001"),
+            ParseAlgError {
+                span: 4..8,
+                kind: ParseAlgErrorKind::BadCount,
+            }
+        );
+        assert_eq!(
+            error("RU ((RU LB)1000)1000"),
+            ParseAlgError {
+                span: 16..20,
+                kind: ParseAlgErrorKind::TooLong,
+            }
+        );
+        let nested = (0..14).fold("RU".to_string(), |alg, _| format!("[{alg}, LB]"));
+        assert_eq!(
+            error(&nested),
+            ParseAlgError {
+                span: 0..nested.len(),
+                kind: ParseAlgErrorKind::TooLong,
+            }
+        );
+        assert_eq!(
+            error("[RU, LX]"),
+            ParseAlgError {
+                span: 6..7,
+                kind: ParseAlgErrorKind::Move(ParseMoveErrorKind::UnknownCell('X')),
+            }
+        );
+    }
+}
@@ -1,12 +1,36 @@
 //! Gui elements and messaging for the application
 
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime};
 
-use iced::widget::{Button, Checkbox, Column, PickList, Row, Shader, Slider};
-use iced::{Element, Length, Task};
+use iced::keyboard::{self, Key};
+use iced::widget::{Button, Checkbox, Column, PickList, Row, Shader, Slider, TextInput, button};
+use iced::{Element, Length, Subscription, Task};
 
-use crate::settings::{self, ANIMATION_DURATION_MS_RANGE, AppSettings, RotateButton};
+use crate::algorithm::{ParseAlgError, parse_alg};
+use crate::cycles::CycleAnalysis;
+use crate::hint::{Click, Hint};
+use crate::history::{MoveHistory, MoveLog, Replay};
+use crate::mc4d;
+use crate::metrics::Metrics;
+use crate::moves::{
+    CELL_LETTERS, Move, ParseMoveError, format_moves, parse_moves, random_move, simplify_moves,
+};
+use crate::painting::{Brush, InvalidState};
+use crate::piece::Hypercube;
+use crate::playback::{self, Playback, PlaybackKind};
+use crate::search::{Search, SearchEvent, TrackedPieces, twists_of_cells};
+use crate::session::{self, PuzzleSnapshot, Session, ViewPose};
+use crate::settings::{
+    self, ANIMATION_DURATION_MS_RANGE, AppSettings, RotateButton, SavedAlgorithm,
+};
 use crate::shader_widget::{HypercubeShaderProgram, PRIMARY_FACE_GAP, PRIMARY_STICKER_SCALE};
+use crate::solve_viewer::{SolveRecording, SolveViewer};
+use crate::solver;
+use crate::stats::{self, SolveDb, SolveRecord};
+use crate::timer::{SolveTimer, format_solve_time};
 
 /// Rendering modes for visualization
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -38,11 +62,51 @@ impl RenderMode {
     const ALL: [RenderMode; 3] = [RenderMode::Standard, RenderMode::Normals, RenderMode::Depth];
 }
 
-/// Move count for the "Scramble" button. 25 mixes a 27-piece side several
-/// times over (180-degree edge and 120-degree corner turns disturb most of
-/// a side per move), enough that the puzzle reads as thoroughly shuffled
-/// without an excessive click-to-solved feel for manual play.
-const SCRAMBLE_MOVE_COUNT: u32 = 25;
+/// The scramble for `seed`: the uniformly random state drawn from it, so
+/// everyone given the same seed gets the same puzzle.
+fn state_for_seed(seed: u64) -> Hypercube {
+    Hypercube::random_state(&mut fastrand::Rng::with_seed(seed))
+}
+
+/// `log` from solved: its start state (if any) replaced by moves reaching
+/// it, ahead of the scramble. `None` if moves can't reach the start. Runs
+/// the solver, so it's kept off the UI thread.
+fn from_solved(log: MoveLog) -> Option<MoveLog> {
+    let Some(start) = &log.start else {
+        return Some(log);
+    };
+    let mut scramble = solver::moves_reaching(start)?;
+    scramble.extend(&log.scramble);
+    Some(MoveLog {
+        start: None,
+        scramble,
+        moves: log.moves,
+    })
+}
+
+/// Writes `log` to `path` as an MC4D log, creating its parent directory if
+/// needed.
+fn export_log(path: &Path, log: MoveLog) -> Result<(), String> {
+    let log = from_solved(log).ok_or("moves can't reach the scramble's start state")?;
+    path.parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| std::fs::write(path, mc4d::format_log(&log)))
+        .map_err(|err| err.to_string())
+}
+
+/// Runs `work` on its own thread, handing what it returns (`None` if the
+/// thread died) to `message` once it's done.
+fn off_thread<T: Send + 'static>(
+    work: impl FnOnce() -> T + Send + 'static,
+    message: impl FnOnce(Option<T>) -> Message + Send + 'static,
+) -> Task<Message> {
+    let (sender, receiver) = iced::futures::channel::oneshot::channel();
+    std::thread::spawn(move || {
+        // The app dropping the receiver just means nobody's listening.
+        let _ = sender.send(work());
+    });
+    Task::perform(receiver, |result| message(result.ok()))
+}
 
 impl std::fmt::Display for AABBMode {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -74,6 +138,27 @@ fn format_animation_duration(duration_ms: u32) -> String {
     format!("{duration_ms}ms")
 }
 
+/// Formats the playback speed multiplier, e.g. "1.5x".
+fn format_playback_speed(speed: f32) -> String {
+    format!("{speed}x")
+}
+
+/// The scramble as shown under the seed: a start state isn't spelled out
+/// (Copy Scramble works its moves out), only the moves after it are.
+fn scramble_summary(has_start: bool, scramble: &[Move]) -> String {
+    match (has_start, scramble.is_empty()) {
+        (false, _) => format_moves(scramble),
+        (true, true) => "A given state".to_string(),
+        (true, false) => format!("A given state, then {}", format_moves(scramble)),
+    }
+}
+
+/// Reads and parses the MC4D log at `path`.
+fn read_log(path: &str) -> Result<MoveLog, String> {
+    let text = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    mc4d::parse_log(&text).map_err(|err| err.to_string())
+}
+
 /// Delay before the floating value tooltip appears: instant while the
 /// slider is actively being adjusted, otherwise a short hover delay.
 fn tooltip_delay(is_adjusting: bool) -> Duration {
@@ -104,6 +189,65 @@ fn sliders_visible(revealed: bool, reveal_animating: bool) -> bool {
     revealed && !reveal_animating
 }
 
+/// Maps an otherwise-unhandled key press to its undo/redo shortcut, if any:
+/// Ctrl+Z (Cmd+Z on macOS) undoes, Ctrl+Shift+Z or Ctrl+Y redoes.
+fn undo_redo_shortcut(event: keyboard::Event) -> Option<Message> {
+    let keyboard::Event::KeyPressed { key, modifiers, .. } = event else {
+        return None;
+    };
+    if !modifiers.command() {
+        return None;
+    }
+    match key.as_ref() {
+        Key::Character("z") if modifiers.shift() => Some(Message::Redo),
+        Key::Character("z") => Some(Message::Undo),
+        Key::Character("y") => Some(Message::Redo),
+        _ => None,
+    }
+}
+
+/// A key binding from what's typed into its box: the last character,
+/// lowercase, so typing over a binding replaces it; empty unbinds.
+fn binding_key(typed: &str) -> String {
+    typed
+        .chars()
+        .last()
+        .map_or(String::new(), |key| key.to_lowercase().to_string())
+}
+
+/// What the scramble box was asked to reproduce.
+#[derive(Debug, Clone, PartialEq)]
+enum ScrambleInput {
+    /// A scramble seed, as shown next to a generated scramble.
+    Seed(u64),
+    /// Scramble moves in the usual notation.
+    Moves(Vec<Move>),
+}
+
+/// Reads the scramble box: a bare number is a seed, anything else has to
+/// parse as move notation.
+fn parse_scramble_input(input: &str) -> Result<ScrambleInput, ParseMoveError> {
+    match input.trim().parse() {
+        Ok(seed) => Ok(ScrambleInput::Seed(seed)),
+        Err(_) => parse_moves(input).map(ScrambleInput::Moves),
+    }
+}
+
+/// Checks `expression` parses before it's saved, naming it after itself if
+/// no name was given.
+fn saved_algorithm(name: &str, expression: &str) -> Result<SavedAlgorithm, ParseAlgError> {
+    let expression = expression.trim();
+    parse_alg(expression)?;
+    let name = match name.trim() {
+        "" => expression,
+        name => name,
+    };
+    Ok(SavedAlgorithm {
+        name: name.to_string(),
+        expression: expression.to_string(),
+    })
+}
+
 /// Main application state - handles UI controls only
 #[derive(Debug)]
 pub(crate) struct HypercubeApp {
@@ -123,6 +267,134 @@ pub(crate) struct HypercubeApp {
     /// program to pick up, since a bare generation bump carries no payload
     /// (mirrors how `revealed` is threaded alongside `reveal_generation`).
     pending_random_move_count: u32,
+    /// Seed for the next random moves, carried the same way.
+    pending_random_seed: u64,
+    /// Seed `scramble_start` (or `scramble`, for random moves from solved)
+    /// was generated from, if it came from Random Move(s)/Scramble (or a
+    /// solve record that did); kept with each recorded solve.
+    scramble_seed: Option<u64>,
+    /// Contents of the box a seed or scramble text is pasted into.
+    scramble_input: String,
+    /// Why `scramble_input` last failed to parse, shown under the box until
+    /// it's edited again.
+    scramble_error: Option<String>,
+    undo_generation: u64,
+    redo_generation: u64,
+    /// The shader program's move history as last reported via
+    /// `Message::HistoryChanged`; gates the Undo/Redo buttons and is what
+    /// "Copy Moves" copies.
+    history: MoveHistory,
+    /// Whether the last move animation finished on a solved puzzle, as
+    /// reported by `Message::Solved`; cleared by the next history change.
+    solved: bool,
+    /// Speedsolving timer: inspection after a Scramble, then running from
+    /// the first twist until `Message::Solved`.
+    timer: SolveTimer,
+    /// Time of the latest frame tick, which the timer readout is drawn at.
+    now: Instant,
+    /// Contents of the move notation text box.
+    notation_input: String,
+    /// Why `notation_input` last failed to parse, shown under the text box
+    /// until it's edited again.
+    notation_error: Option<String>,
+    apply_moves_generation: u64,
+    /// Moves parsed from `notation_input`, carried alongside
+    /// `apply_moves_generation` the same way `pending_random_move_count` is.
+    pending_moves: Arc<[Move]>,
+    /// Contents of the name and expression boxes a new algorithm is saved
+    /// from.
+    algorithm_name: String,
+    algorithm_input: String,
+    /// Why the last algorithm failed to parse (when saved or played), shown
+    /// under the boxes until they're edited again.
+    algorithm_error: Option<String>,
+    play_generation: u64,
+    /// A played algorithm's moves, carried alongside `play_generation`.
+    pending_play: Arc<[Move]>,
+    /// The last analyzed saved algorithm's name and cycles, shown under the
+    /// algorithms with the pieces it moves highlighted until cleared.
+    analysis: Option<(String, CycleAnalysis)>,
+    /// Whether the solver is working out a Solve on its own thread, so only
+    /// one is at a time.
+    solving: bool,
+    /// Whether hint mode is on: the next move of a way back to solved is
+    /// worked out in the background and marked on the puzzle.
+    hints: bool,
+    /// The last hint worked out, kept until a newer one arrives; only
+    /// shown while its position is still the current one.
+    hint: Option<Hint>,
+    /// Whether a hint is being worked out, so only one is at a time.
+    hint_pending: bool,
+    /// Whether painting mode is on: clicks paint stickers with
+    /// `paint_brush` on a draft the shader program keeps, until Apply
+    /// validates it and it replaces the puzzle.
+    painting: bool,
+    paint_brush: Brush,
+    apply_paint_generation: u64,
+    /// Why the draft was last rejected, shown until it's applied or
+    /// painting is turned off.
+    paint_error: Option<String>,
+    /// Algorithm search (`search.rs`) inputs: the expression whose effect
+    /// on the tracked pieces to look for, which pieces, which cells'
+    /// twists to build it from (blank for all), and how deep to go.
+    search_target: String,
+    search_pieces: TrackedPieces,
+    search_cells: String,
+    search_depth: u8,
+    /// Bumped per search started, tagging its events so a cancelled one's
+    /// stragglers are ignored.
+    search_generation: u64,
+    /// Set to stop the running search, if any.
+    search_cancel: Option<Arc<AtomicBool>>,
+    /// The running or last search's progress line, or why it couldn't start.
+    search_status: String,
+    /// The running or last search's solutions, as reported.
+    search_solutions: Vec<Vec<Move>>,
+    /// State the puzzle was scrambled into before `scramble`, if it wasn't
+    /// solved: a Scramble's sampled random state, or a painted one. Only spelled out as
+    /// moves, off the UI thread, when exported or copied.
+    scramble_start: Option<Hypercube>,
+    /// Moves applied by the last Random Move(s) (or recorded by an opened
+    /// session/imported log) before `history` began; empty after Reset.
+    /// Exported ahead of `history` so a log replays from solved.
+    scramble: Vec<Move>,
+    /// Contents of the session file path box that Save/Open act on.
+    session_path: String,
+    /// Outcome of the last Save/Open, shown under the path box.
+    session_status: Option<String>,
+    /// Bumped to ask the shader program for a `PuzzleSnapshot`, which comes
+    /// back as `Message::SessionCaptured` and is written out from there.
+    save_session_generation: u64,
+    load_session_generation: u64,
+    /// Opened session's puzzle state, carried alongside
+    /// `load_session_generation`.
+    pending_session: Option<Arc<PuzzleSnapshot>>,
+    /// Contents of the MC4D log path box that Import/Export act on.
+    log_path: String,
+    /// Outcome of the last Import/Export, shown under the path box.
+    log_status: Option<String>,
+    replay_generation: u64,
+    /// Imported log or reopened solve, carried alongside
+    /// `replay_generation`.
+    pending_replay: Option<Arc<Replay>>,
+    /// The move list being played (`playback.rs`), if any: loaded into the
+    /// history through `pending_replay` and stepped through by redo.
+    playback: Option<Playback>,
+    /// Multiple of the normal animation speed playback runs at.
+    playback_speed: f32,
+    /// Whether Scramble and Random Move(s) play their moves through the
+    /// move animation instead of applying them instantly.
+    animate_scrambles: bool,
+    /// Every timed solve so far, saved after each new one.
+    solves: SolveDb,
+    /// The view the shader program last reported (`Message::ViewChanged`,
+    /// sent while the timer is live).
+    view: Option<ViewPose>,
+    /// The running solve as it happens, started along with the clock and
+    /// saved with its `SolveRecord`.
+    recording: Option<SolveRecording>,
+    /// The recorded solve being watched (`solve_viewer.rs`), if any.
+    viewer: Option<SolveViewer>,
     sticker_scale_adjusting: bool,
     face_gap_adjusting: bool,
     animation_duration_adjusting: bool,
@@ -140,6 +412,9 @@ pub(crate) struct HypercubeApp {
     reveal_loop_remaining: u32,
 }
 
+/// How many of a search's solutions the panel lists.
+const SHOWN_SOLUTIONS: usize = 20;
+
 /// Number of scripted flourishes still to run, after the one the boot task
 /// already kicked off, so `--features gpu-capture-hooks` totals 5 runs.
 #[cfg(feature = "gpu-capture-hooks")]
@@ -178,10 +453,118 @@ pub(crate) enum Message {
     AnimationDuration(u32),
     AnimationDurationReleased,
     Reset,
+    /// Plays the solver's solution through the move animation.
+    Solve,
+    /// The solver's way back to solved from `from`, worked out in the
+    /// background (`None` if no moves reach it).
+    Solution {
+        from: MoveLog,
+        moves: Option<Vec<Move>>,
+    },
     RandomMoves(u32),
+    /// Scrambles into a uniformly random state from a fresh seed.
+    Scramble,
+    /// Moves reaching the state drawn from `seed`, worked out in the
+    /// background to play an animated Scramble, if the puzzle is still at
+    /// `from`.
+    ScrambleMoves {
+        seed: u64,
+        from: MoveLog,
+        moves: Option<Vec<Move>>,
+    },
     ToggleReveal,
-    RevealAnimationComplete { final_scale: f32, final_gap: f32 },
+    RevealAnimationComplete {
+        final_scale: f32,
+        final_gap: f32,
+    },
     ResetAnimationComplete,
+    Undo,
+    Redo,
+    HistoryChanged(MoveHistory),
+    CopyMoves,
+    NotationInput(String),
+    ApplyNotation,
+    AlgorithmName(String),
+    AlgorithmInput(String),
+    SaveAlgorithm,
+    /// Plays a saved algorithm (by index) through the move animation.
+    PlayAlgorithm(usize),
+    /// Shows which pieces a saved algorithm (by index) moves, and how.
+    AnalyzeAlgorithm(usize),
+    ClearAnalysis,
+    Hints(bool),
+    /// A hint worked out in the background (`None` if its thread died).
+    HintReady(Option<Hint>),
+    PlayHint,
+    Painting(bool),
+    PaintBrush(Brush),
+    ApplyPaint,
+    /// The shader program's verdict on the painted draft: the draft, if
+    /// moves can reach it, or what's wrong with it.
+    Painted(Result<Hypercube, InvalidState>),
+    /// A cell's grip key (by face id) was rebound to what's typed.
+    GripKey(usize, String),
+    /// A cell's twist key (by face id) was rebound to what's typed.
+    TwistKey(usize, String),
+    SearchTarget(String),
+    SearchPieces(TrackedPieces),
+    SearchCells(String),
+    SearchDepth(u8),
+    StartSearch,
+    CancelSearch,
+    /// An event from the search started as `search_generation` (the first
+    /// field).
+    Search(u64, SearchEvent),
+    /// Saves a search solution (by index) as an algorithm.
+    SaveSolution(usize),
+    DeleteAlgorithm(usize),
+    SessionPath(String),
+    SaveSession,
+    SessionCaptured(Box<PuzzleSnapshot>),
+    OpenSession,
+    Scrambled(Vec<Move>),
+    Solved {
+        committed_at: Instant,
+    },
+    TimerTick(Instant),
+    LogPath(String),
+    ImportLog,
+    ExportLog,
+    /// Loads a recorded solve (by index into the solve record) at its
+    /// finish, with every move undoable.
+    OpenSolve(usize),
+    /// The outcome of an export, written in the background.
+    LogExported(Result<(), String>),
+    CopyScramble,
+    /// The scramble spelled out as moves in the background for Copy
+    /// Scramble (`None` if it couldn't be).
+    ScrambleText(Option<String>),
+    ScrambleInput(String),
+    /// Reproduces the scramble from the seed or moves in the scramble box.
+    LoadScramble,
+    /// Loads a recorded solve at its scramble, paused, to play back.
+    ReplaySolve(usize),
+    /// Plays the log at the log path back from its scramble.
+    WatchLog,
+    AnimateScrambles(bool),
+    /// Plays or pauses the playback.
+    PlaybackPlaying(bool),
+    /// Pauses the playback and plays its next move.
+    PlaybackStep,
+    /// Pauses the playback and takes back its last played move.
+    PlaybackStepBack,
+    /// Ends the playback, leaving the puzzle as it is.
+    StopPlayback,
+    PlaybackSpeed(f32),
+    /// The 4D orientation or camera moved (reported while the timer is
+    /// live, to record solves).
+    ViewChanged(ViewPose),
+    /// Opens the solve viewer on a recorded solve.
+    WatchSolve(usize),
+    ViewerPlaying(bool),
+    /// Moves the solve viewer to this many milliseconds into the solve.
+    ViewerSeek(u32),
+    CloseViewer,
 }
 
 impl HypercubeApp {
@@ -197,6 +580,64 @@ impl HypercubeApp {
             reset_animating: false,
             random_moves_generation: 0,
             pending_random_move_count: 0,
+            pending_random_seed: 0,
+            scramble_seed: None,
+            scramble_input: String::new(),
+            scramble_error: None,
+            undo_generation: 0,
+            redo_generation: 0,
+            history: MoveHistory::default(),
+            solved: false,
+            timer: SolveTimer::default(),
+            now: Instant::now(),
+            notation_input: String::new(),
+            notation_error: None,
+            apply_moves_generation: 0,
+            pending_moves: Arc::from([]),
+            algorithm_name: String::new(),
+            algorithm_input: String::new(),
+            algorithm_error: None,
+            play_generation: 0,
+            pending_play: Arc::from([]),
+            analysis: None,
+            solving: false,
+            hints: false,
+            hint: None,
+            hint_pending: false,
+            painting: false,
+            paint_brush: Brush::default(),
+            apply_paint_generation: 0,
+            paint_error: None,
+            search_target: String::new(),
+            search_pieces: TrackedPieces::default(),
+            search_cells: String::new(),
+            search_depth: 6,
+            search_generation: 0,
+            search_cancel: None,
+            search_status: String::new(),
+            search_solutions: Vec::new(),
+            session_path: session::default_path()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default(),
+            session_status: None,
+            scramble_start: None,
+            scramble: Vec::new(),
+            log_path: mc4d::default_path()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default(),
+            log_status: None,
+            replay_generation: 0,
+            pending_replay: None,
+            playback: None,
+            playback_speed: 1.0,
+            animate_scrambles: false,
+            solves: stats::load(),
+            view: None,
+            recording: None,
+            viewer: None,
+            save_session_generation: 0,
+            load_session_generation: 0,
+            pending_session: None,
             sticker_scale_adjusting: false,
             face_gap_adjusting: false,
             animation_duration_adjusting: false,
@@ -229,6 +670,16 @@ impl HypercubeApp {
 
     /// Update the application state
     pub(crate) fn update(&mut self, message: Message) -> Task<Message> {
+        let task = self.handle_message(message);
+        self.drop_abandoned_playback();
+        // A recording outlives its solve only until `record_solve` takes it.
+        if self.timer.running_for(Instant::now()).is_none() {
+            self.recording = None;
+        }
+        Task::batch([task, self.refresh_hint()])
+    }
+
+    fn handle_message(&mut self, message: Message) -> Task<Message> {
         match message {
             Message::StickerScale(value) => {
                 self.sticker_scale = value;
@@ -268,14 +719,496 @@ impl HypercubeApp {
             Message::Reset => {
                 self.reset_generation = self.reset_generation.wrapping_add(1);
                 self.reset_animating = true;
+                self.history.clear();
+                self.scramble_start = None;
+                self.scramble.clear();
+                self.scramble_seed = None;
+                self.solved = false;
+                self.timer = SolveTimer::Idle;
+            }
+            Message::Solve => {
+                self.solving = true;
+                // Watching the solver isn't a solve of one's own.
+                self.timer = SolveTimer::Idle;
+                let from = self.log();
+                let puzzle = from.puzzle();
+                return off_thread(
+                    move || solver::solve(&puzzle),
+                    move |moves| Message::Solution {
+                        from,
+                        moves: moves.flatten(),
+                    },
+                );
+            }
+            Message::Solution { from, moves } => {
+                self.solving = false;
+                // A twist made meanwhile leaves the solution for a puzzle
+                // that's gone.
+                if from == self.log() {
+                    match moves {
+                        Some(moves) => {
+                            self.pending_play = moves.into();
+                            self.play_generation = self.play_generation.wrapping_add(1);
+                        }
+                        None => log::warn!("Puzzle is in a state no moves reach; not solving"),
+                    }
+                }
             }
             Message::ResetAnimationComplete => {
                 self.reset_animating = false;
             }
+            Message::RandomMoves(count) if self.animate_scrambles => {
+                let seed = fastrand::u64(..);
+                let mut rng = fastrand::Rng::with_seed(seed);
+                let moves: Vec<Move> = (0..count).map(|_| random_move(&mut rng)).collect();
+                let from = MoveLog {
+                    start: self.scramble_start.clone(),
+                    scramble: self.position().collect(),
+                    moves: Vec::new(),
+                };
+                let kind = PlaybackKind::RandomMoves {
+                    seed: (from == MoveLog::default()).then_some(seed),
+                };
+                self.start_playback(kind, from, moves, true);
+                self.timer = SolveTimer::Idle;
+            }
             Message::RandomMoves(count) => {
-                self.pending_random_move_count = count;
-                self.random_moves_generation = self.random_moves_generation.wrapping_add(1);
+                self.random_moves(count, fastrand::u64(..));
+            }
+            Message::Scramble => {
+                let seed = fastrand::u64(..);
+                if self.animate_scrambles {
+                    let from = self.log();
+                    return off_thread(
+                        move || solver::moves_reaching(&state_for_seed(seed)),
+                        move |moves| Message::ScrambleMoves {
+                            seed,
+                            from,
+                            moves: moves.flatten(),
+                        },
+                    );
+                }
+                self.load_scramble(Some(state_for_seed(seed)), Vec::new(), Some(seed));
+            }
+            Message::ScrambleMoves { seed, from, moves } => {
+                if let Some(moves) = moves
+                    && from == self.log()
+                {
+                    let kind = PlaybackKind::Scramble { seed };
+                    self.start_playback(kind, MoveLog::default(), moves, true);
+                    self.timer = SolveTimer::Idle;
+                }
+            }
+            Message::Undo => {
+                self.undo_generation = self.undo_generation.wrapping_add(1);
+            }
+            Message::Redo => {
+                self.redo_generation = self.redo_generation.wrapping_add(1);
+            }
+            Message::HistoryChanged(history) => {
+                // A replayed scramble reports an empty history; only a
+                // history with a twist in it ends inspection (reorienting
+                // the whole puzzle while inspecting is allowed).
+                let now = Instant::now();
+                if history.applied().iter().any(|mv| !mv.is_rotation()) {
+                    self.timer.twisted(now);
+                }
+                if let Some(at_ms) = self.recording_ms(now)
+                    && let Some(recording) = &mut self.recording
+                {
+                    recording.record_history(at_ms, self.history.applied(), history.applied());
+                }
+                self.history = history;
+                self.solved = false;
+            }
+            Message::Solved { committed_at } => {
+                self.solved = true;
+                if let Some(elapsed) = self.timer.solved(committed_at) {
+                    self.record_solve(elapsed);
+                }
+            }
+            Message::TimerTick(now) => {
+                self.now = now;
+                self.timer.tick(now);
+                let move_duration = Duration::from_millis(self.move_duration_ms().into());
+                if let Some(playback) = &mut self.playback
+                    && playback.step_due(now, move_duration)
+                {
+                    self.step_playback();
+                }
+                if let Some(viewer) = &mut self.viewer
+                    && viewer.advance(now, self.playback_speed)
+                {
+                    self.show_viewer_frame();
+                }
+            }
+            Message::CopyMoves => {
+                return iced::clipboard::write(format_moves(self.history.applied()));
+            }
+            Message::NotationInput(input) => {
+                self.notation_input = input;
+                self.notation_error = None;
+            }
+            Message::ApplyNotation => match parse_alg(&self.notation_input) {
+                Ok(alg) => {
+                    self.pending_moves = alg.expand().into();
+                    self.apply_moves_generation = self.apply_moves_generation.wrapping_add(1);
+                    self.notation_input.clear();
+                    self.timer = SolveTimer::Idle;
+                }
+                Err(err) => {
+                    self.notation_error = Some(err.to_string());
+                }
+            },
+            Message::AlgorithmName(name) => {
+                self.algorithm_name = name;
+                self.algorithm_error = None;
+            }
+            Message::AlgorithmInput(input) => {
+                self.algorithm_input = input;
+                self.algorithm_error = None;
+            }
+            Message::SaveAlgorithm => {
+                match saved_algorithm(&self.algorithm_name, &self.algorithm_input) {
+                    Ok(algorithm) => {
+                        self.settings.algorithms.push(algorithm);
+                        settings::save(&self.settings);
+                        self.algorithm_name.clear();
+                        self.algorithm_input.clear();
+                    }
+                    Err(err) => {
+                        self.algorithm_error = Some(err.to_string());
+                    }
+                }
+            }
+            Message::PlayAlgorithm(index) => {
+                let algorithm = &self.settings.algorithms[index];
+                match parse_alg(&algorithm.expression) {
+                    Ok(alg) => {
+                        let kind = PlaybackKind::Algorithm(algorithm.name.clone());
+                        self.start_playback(kind, self.log(), alg.expand(), true);
+                    }
+                    Err(err) => {
+                        self.algorithm_error = Some(err.to_string());
+                    }
+                }
+            }
+            Message::AnalyzeAlgorithm(index) => {
+                let algorithm = &self.settings.algorithms[index];
+                match parse_alg(&algorithm.expression) {
+                    Ok(alg) => {
+                        self.analysis =
+                            Some((algorithm.name.clone(), CycleAnalysis::of(&alg.expand())));
+                    }
+                    Err(err) => {
+                        self.algorithm_error = Some(err.to_string());
+                    }
+                }
+            }
+            Message::ClearAnalysis => {
+                self.analysis = None;
+            }
+            Message::Hints(enabled) => {
+                self.hints = enabled;
+                // With hints on, a solve isn't one's own.
+                if enabled && self.timer.is_live() {
+                    self.timer = SolveTimer::Idle;
+                }
+            }
+            Message::HintReady(hint) => {
+                self.hint_pending = false;
+                self.hint = hint;
+            }
+            Message::PlayHint => {
+                if let Some(&mv) = self.current_hint().and_then(|hint| hint.path.first()) {
+                    self.pending_play = Arc::from([mv]);
+                    self.play_generation = self.play_generation.wrapping_add(1);
+                    // Playing the hint isn't solving it oneself.
+                    self.timer = SolveTimer::Idle;
+                }
+            }
+            Message::Painting(enabled) => {
+                self.painting = enabled;
+                self.paint_error = None;
+            }
+            Message::PaintBrush(brush) => {
+                self.paint_brush = brush;
+            }
+            Message::ApplyPaint => {
+                self.apply_paint_generation = self.apply_paint_generation.wrapping_add(1);
+            }
+            Message::Painted(Ok(painted)) => {
+                self.painting = false;
+                self.paint_error = None;
+                self.load_scramble(Some(painted), Vec::new(), None);
+                // A painted puzzle is for exploring, not a timed scramble.
+                self.timer = SolveTimer::Idle;
+            }
+            Message::Painted(Err(err)) => {
+                self.paint_error = Some(err.to_string());
+            }
+            Message::GripKey(cell, typed) => {
+                self.settings.key_bindings.grips[cell] = binding_key(&typed);
+                settings::save(&self.settings);
+            }
+            Message::TwistKey(cell, typed) => {
+                self.settings.key_bindings.twists[cell] = binding_key(&typed);
+                settings::save(&self.settings);
+            }
+            Message::SearchTarget(target) => {
+                self.search_target = target;
+            }
+            Message::SearchPieces(pieces) => {
+                self.search_pieces = pieces;
+            }
+            Message::SearchCells(cells) => {
+                self.search_cells = cells;
+            }
+            Message::SearchDepth(depth) => {
+                self.search_depth = depth;
+            }
+            Message::StartSearch => return self.start_search(),
+            Message::CancelSearch => {
+                if let Some(cancel) = self.search_cancel.take() {
+                    cancel.store(true, Ordering::Relaxed);
+                }
+            }
+            Message::Search(generation, event) => {
+                if generation == self.search_generation {
+                    self.search_event(event);
+                }
+            }
+            Message::SaveSolution(index) => {
+                let expression = format_moves(&self.search_solutions[index]);
+                if let Ok(algorithm) = saved_algorithm("", &expression) {
+                    self.settings.algorithms.push(algorithm);
+                    settings::save(&self.settings);
+                }
+            }
+            Message::DeleteAlgorithm(index) => {
+                self.settings.algorithms.remove(index);
+                settings::save(&self.settings);
+            }
+            Message::SessionPath(path) => {
+                self.session_path = path;
+                self.session_status = None;
+            }
+            Message::SaveSession => {
+                self.save_session_generation = self.save_session_generation.wrapping_add(1);
+            }
+            Message::SessionCaptured(puzzle) => {
+                let session = Session {
+                    puzzle: *puzzle,
+                    scramble_start: self.scramble_start.clone(),
+                    scramble: self.scramble.clone(),
+                    sticker_scale: 1.0 - self.sticker_scale,
+                    face_gap: self.face_gap,
+                };
+                self.session_status = Some(
+                    match session::save(Path::new(&self.session_path), &session) {
+                        Ok(()) => "Session saved".to_string(),
+                        Err(err) => format!("Save failed: {err}"),
+                    },
+                );
             }
+            Message::OpenSession => match session::load(Path::new(&self.session_path)) {
+                Ok(session) => {
+                    self.sticker_scale = 1.0 - session.sticker_scale;
+                    self.face_gap = session.face_gap;
+                    self.history = session.puzzle.history.clone();
+                    self.scramble_start = session.scramble_start;
+                    self.scramble = session.scramble;
+                    self.scramble_seed = None;
+                    self.pending_session = Some(Arc::new(session.puzzle));
+                    self.load_session_generation = self.load_session_generation.wrapping_add(1);
+                    self.session_status = Some("Session opened".to_string());
+                    self.timer = SolveTimer::Idle;
+                }
+                Err(err) => {
+                    self.session_status = Some(format!("Open failed: {err}"));
+                }
+            },
+            Message::Scrambled(scramble) => {
+                self.scramble.extend(scramble);
+                self.solved = false;
+            }
+            Message::LogPath(path) => {
+                self.log_path = path;
+                self.log_status = None;
+            }
+            Message::ImportLog => match read_log(&self.log_path) {
+                Ok(log) => {
+                    self.scramble_start = None;
+                    self.scramble = log.scramble.clone();
+                    self.scramble_seed = None;
+                    self.pending_replay = Some(Arc::new(Replay::applied(log)));
+                    self.replay_generation = self.replay_generation.wrapping_add(1);
+                    self.log_status = Some("Log imported".to_string());
+                    self.timer = SolveTimer::Idle;
+                }
+                Err(err) => {
+                    self.log_status = Some(format!("Import failed: {err}"));
+                }
+            },
+            Message::ExportLog => {
+                let path = PathBuf::from(&self.log_path);
+                let log = self.log();
+                self.log_status = Some("Exporting...".to_string());
+                return off_thread(
+                    move || export_log(&path, log),
+                    |result| {
+                        Message::LogExported(
+                            result.unwrap_or_else(|| Err("export stopped".to_string())),
+                        )
+                    },
+                );
+            }
+            Message::LogExported(result) => {
+                self.log_status = Some(match result {
+                    Ok(()) => "Log exported".to_string(),
+                    Err(err) => format!("Export failed: {err}"),
+                });
+            }
+            Message::OpenSolve(index) => {
+                if let Some(record) = self.solves.solves.get(index) {
+                    let log = record.log.clone();
+                    self.scramble_start = log.start.clone();
+                    self.scramble = log.scramble.clone();
+                    self.scramble_seed = record.seed;
+                    self.pending_replay = Some(Arc::new(Replay::applied(log)));
+                    self.replay_generation = self.replay_generation.wrapping_add(1);
+                    self.timer = SolveTimer::Idle;
+                }
+            }
+            Message::ReplaySolve(index) => {
+                if let Some(record) = self.solves.solves.get(index) {
+                    let moves = record.log.moves.clone();
+                    let from = MoveLog {
+                        moves: Vec::new(),
+                        ..record.log.clone()
+                    };
+                    let seed = record.seed;
+                    self.start_playback(PlaybackKind::Log, from, moves, false);
+                    self.scramble_seed = seed;
+                    self.timer = SolveTimer::Idle;
+                }
+            }
+            Message::WatchLog => match read_log(&self.log_path) {
+                Ok(mut log) => {
+                    let moves = std::mem::take(&mut log.moves);
+                    self.start_playback(PlaybackKind::Log, log, moves, true);
+                    self.log_status = Some("Playing log".to_string());
+                    self.timer = SolveTimer::Idle;
+                }
+                Err(err) => {
+                    self.log_status = Some(format!("Import failed: {err}"));
+                }
+            },
+            Message::AnimateScrambles(animate) => {
+                self.animate_scrambles = animate;
+            }
+            Message::PlaybackPlaying(playing) => {
+                if let Some(playback) = &mut self.playback {
+                    playback.set_playing(playing);
+                }
+            }
+            Message::PlaybackStep => {
+                if let Some(playback) = &mut self.playback {
+                    playback.set_playing(false);
+                    self.step_playback();
+                }
+            }
+            Message::PlaybackStepBack => {
+                if let Some(playback) = &mut self.playback {
+                    playback.set_playing(false);
+                    if playback
+                        .progress(&self.history)
+                        .is_some_and(|played| played > 0)
+                    {
+                        self.undo_generation = self.undo_generation.wrapping_add(1);
+                    }
+                }
+            }
+            Message::StopPlayback => {
+                self.playback = None;
+            }
+            Message::PlaybackSpeed(speed) => {
+                self.playback_speed = speed;
+            }
+            Message::ViewChanged(view) => {
+                self.view = Some(view);
+                if let Some(at_ms) = self.recording_ms(Instant::now())
+                    && let Some(recording) = &mut self.recording
+                {
+                    recording.record_view(at_ms, view);
+                }
+            }
+            Message::WatchSolve(index) => {
+                if let Some(record) = self.solves.solves.get(index)
+                    && let Some(viewer) = SolveViewer::new(index, record)
+                {
+                    self.scramble_start = record.log.start.clone();
+                    self.scramble = record.log.scramble.clone();
+                    self.scramble_seed = record.seed;
+                    self.playback = None;
+                    self.viewer = Some(viewer);
+                    self.timer = SolveTimer::Idle;
+                    self.show_viewer_frame();
+                }
+            }
+            Message::ViewerPlaying(playing) => {
+                if let Some(viewer) = &mut self.viewer
+                    && viewer.set_playing(playing)
+                {
+                    self.show_viewer_frame();
+                }
+            }
+            Message::ViewerSeek(at_ms) => {
+                if let Some(viewer) = &mut self.viewer
+                    && viewer.seek(at_ms.into())
+                {
+                    self.show_viewer_frame();
+                }
+            }
+            Message::CloseViewer => {
+                self.viewer = None;
+            }
+            Message::CopyScramble => {
+                let scramble = MoveLog {
+                    moves: Vec::new(),
+                    ..self.log()
+                };
+                if scramble.start.is_none() {
+                    return iced::clipboard::write(format_moves(&scramble.scramble));
+                }
+                return off_thread(
+                    move || from_solved(scramble).map(|log| format_moves(&log.scramble)),
+                    |text| Message::ScrambleText(text.flatten()),
+                );
+            }
+            Message::ScrambleText(Some(text)) => {
+                return iced::clipboard::write(text);
+            }
+            Message::ScrambleText(None) => {
+                self.scramble_error = Some("Moves can't reach this scramble".to_string());
+            }
+            Message::ScrambleInput(input) => {
+                self.scramble_input = input;
+                self.scramble_error = None;
+            }
+            Message::LoadScramble => match parse_scramble_input(&self.scramble_input) {
+                Ok(ScrambleInput::Seed(seed)) => {
+                    self.load_scramble(Some(state_for_seed(seed)), Vec::new(), Some(seed));
+                    self.scramble_input.clear();
+                }
+                Ok(ScrambleInput::Moves(moves)) => {
+                    self.load_scramble(None, moves, None);
+                    self.scramble_input.clear();
+                }
+                Err(err) => {
+                    self.scramble_error = Some(err.to_string());
+                }
+            },
             Message::ToggleReveal => {
                 self.revealed = !self.revealed;
                 self.reveal_generation = self.reveal_generation.wrapping_add(1);
@@ -305,53 +1238,964 @@ impl HypercubeApp {
         Task::none()
     }
 
-    /// Create the view for the application
-    pub(crate) fn view(&self) -> Element<'_, Message> {
-        // Left pane with controls
-        let mut controls =
-            Column::new()
-                .spacing(20)
+    /// The scramble then the applied moves: what the puzzle is, replayed
+    /// from `scramble_start`.
+    fn position(&self) -> impl Iterator<Item = Move> + '_ {
+        self.scramble.iter().chain(self.history.applied()).copied()
+    }
+
+    /// The scramble and the applied moves, as a log.
+    fn log(&self) -> MoveLog {
+        MoveLog {
+            start: self.scramble_start.clone(),
+            scramble: self.scramble.clone(),
+            moves: self.history.applied().to_vec(),
+        }
+    }
+
+    /// The last hint, if it's for the current position.
+    fn current_hint(&self) -> Option<&Hint> {
+        self.hint.as_ref().filter(|hint| {
+            hint.position.start == self.scramble_start
+                && hint.position.position().eq(self.position())
+        })
+    }
+
+    /// In hint mode, starts working out a hint on its own thread if the
+    /// current position doesn't have one and none is underway. Runs after
+    /// every message, so a position that changed while one was being
+    /// worked out gets its own as soon as that one arrives.
+    fn refresh_hint(&mut self) -> Task<Message> {
+        if !self.hints || self.hint_pending || self.current_hint().is_some() {
+            return Task::none();
+        }
+        self.hint_pending = true;
+        let position = self.log();
+        off_thread(move || Hint::for_position(position), Message::HintReady)
+    }
+
+    /// Starts an algorithm search on its own thread (cancelling any still
+    /// running), streaming its events back as `Message::Search`.
+    fn start_search(&mut self) -> Task<Message> {
+        let target = match parse_alg(&self.search_target) {
+            Ok(alg) => alg.expand(),
+            Err(err) => {
+                self.search_status = err.to_string();
+                return Task::none();
+            }
+        };
+        let generators = match twists_of_cells(&self.search_cells) {
+            Ok(generators) => generators,
+            Err(letter) => {
+                self.search_status = format!("'{letter}' is not a cell name");
+                return Task::none();
+            }
+        };
+        if let Some(cancel) = self.search_cancel.take() {
+            cancel.store(true, Ordering::Relaxed);
+        }
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.search_cancel = Some(cancel.clone());
+        self.search_generation = self.search_generation.wrapping_add(1);
+        self.search_status = "Building pattern databases...".to_string();
+        self.search_solutions.clear();
+
+        let tracked = self.search_pieces.slots();
+        let max_depth = usize::from(self.search_depth);
+        let threads = std::thread::available_parallelism().map_or(1, usize::from);
+        let (sender, receiver) = iced::futures::channel::mpsc::unbounded();
+        std::thread::spawn(move || {
+            let report = |event| {
+                // The app dropping the receiver just means nobody's listening.
+                let _ = sender.unbounded_send(event);
+            };
+            if let Some(search) = Search::new(&generators, &tracked, &target, &cancel, report) {
+                search.run(max_depth, threads, &cancel, report);
+            }
+        });
+        let generation = self.search_generation;
+        Task::run(receiver, move |event| Message::Search(generation, event))
+    }
+
+    fn search_event(&mut self, event: SearchEvent) {
+        match event {
+            SearchEvent::Tables { built, tables } => {
+                self.search_status = format!("Building pattern databases: {built}/{tables}");
+            }
+            SearchEvent::Depth(depth) => {
+                self.search_status = format!("Depth {depth}...");
+            }
+            SearchEvent::Progress {
+                depth,
+                branches_done,
+                branches,
+                nodes,
+            } => {
+                self.search_status = format!(
+                    "Depth {depth}: {branches_done}/{branches} first moves, {nodes} positions"
+                );
+            }
+            SearchEvent::Solution(moves) => self.search_solutions.push(moves),
+            SearchEvent::DepthDone { depth, solutions } => {
+                self.search_status = format!("Depth {depth} done: {solutions} found");
+            }
+            SearchEvent::Finished { cancelled } => {
+                self.search_cancel = None;
+                self.search_status = format!(
+                    "{}: {} solutions",
+                    if cancelled { "Cancelled" } else { "Done" },
+                    self.search_solutions.len()
+                );
+            }
+        }
+    }
+
+    /// Asks the shader program for `count` random moves drawn from `seed`.
+    /// A few random moves make a practice position, not a timed scramble.
+    /// They land on the puzzle as it is, so the moves made so far join
+    /// `scramble` (which `Message::Scrambled` then extends) and it still
+    /// replays to the puzzle from solved; the seed alone only reproduces
+    /// them from solved.
+    fn random_moves(&mut self, count: u32, seed: u64) {
+        self.pending_random_move_count = count;
+        self.pending_random_seed = seed;
+        let position: Vec<Move> = self.position().collect();
+        self.scramble_seed = (position.is_empty() && self.scramble_start.is_none()).then_some(seed);
+        self.scramble = position;
+        self.random_moves_generation = self.random_moves_generation.wrapping_add(1);
+        self.history.clear();
+        self.timer = SolveTimer::Idle;
+    }
+
+    /// Resets the puzzle to `start` (solved if `None`) plus `scramble`
+    /// (generated from `seed`, if it was) and starts inspection.
+    fn load_scramble(&mut self, start: Option<Hypercube>, scramble: Vec<Move>, seed: Option<u64>) {
+        self.scramble_start = start.clone();
+        self.scramble = scramble.clone();
+        self.scramble_seed = seed;
+        self.pending_replay = Some(Arc::new(Replay::applied(MoveLog {
+            start,
+            scramble,
+            moves: Vec::new(),
+        })));
+        self.replay_generation = self.replay_generation.wrapping_add(1);
+        self.history.clear();
+        self.solved = false;
+        self.timer.scrambled(Instant::now());
+    }
+
+    /// Loads `moves` to play back where `from` ends (its moves stay
+    /// undoable), starting `playing` or paused.
+    fn start_playback(
+        &mut self,
+        kind: PlaybackKind,
+        from: MoveLog,
+        moves: Vec<Move>,
+        playing: bool,
+    ) {
+        self.playback = Some(Playback::new(
+            kind,
+            moves.clone(),
+            from.moves.len(),
+            playing,
+        ));
+        self.viewer = None;
+        self.scramble_start = from.start.clone();
+        self.scramble = from.scramble.clone();
+        let replay = Replay::continuing(from, &moves);
+        self.scramble_seed = None;
+        self.history = replay.history.clone();
+        self.pending_replay = Some(Arc::new(replay));
+        self.replay_generation = self.replay_generation.wrapping_add(1);
+        self.solved = false;
+    }
+
+    /// Plays the playback's next move, or if they've all been played (a
+    /// step after the last, so it's finished turning) finishes it: played
+    /// scrambles and random moves become the scramble the way instant ones
+    /// do, anything else just pauses at its end.
+    fn step_playback(&mut self) {
+        let Some(playback) = &mut self.playback else {
+            return;
+        };
+        let Some(played) = playback.progress(&self.history) else {
+            return;
+        };
+        if played < playback.moves().len() {
+            self.redo_generation = self.redo_generation.wrapping_add(1);
+            return;
+        }
+        match playback.kind {
+            PlaybackKind::Scramble { seed } => {
+                self.playback = None;
+                self.load_scramble(Some(state_for_seed(seed)), Vec::new(), Some(seed));
+            }
+            PlaybackKind::RandomMoves { seed } => {
+                self.playback = None;
+                self.scramble = self.position().collect();
+                self.scramble_seed = seed;
+                self.pending_replay = Some(Arc::new(Replay::applied(MoveLog {
+                    start: self.scramble_start.clone(),
+                    scramble: self.scramble.clone(),
+                    moves: Vec::new(),
+                })));
+                self.replay_generation = self.replay_generation.wrapping_add(1);
+                self.history.clear();
+            }
+            PlaybackKind::Log | PlaybackKind::Algorithm(_) => playback.set_playing(false),
+        }
+    }
+
+    /// Ends the playback once the history no longer holds its moves, or
+    /// closes the solve viewer once it isn't what the viewer put there, e.g.
+    /// after a twist, Reset or another load. Runs after every message.
+    fn drop_abandoned_playback(&mut self) {
+        if let Some(playback) = &self.playback
+            && playback.progress(&self.history).is_none()
+        {
+            self.playback = None;
+        }
+        if let Some(viewer) = &self.viewer
+            && !viewer.applied().eq(self.history.applied().iter().copied())
+        {
+            self.viewer = None;
+        }
+    }
+
+    /// Hands the solve viewer's puzzle and view at its current point to the
+    /// shader program, the way an opened session is.
+    fn show_viewer_frame(&mut self) {
+        let Some(viewer) = &self.viewer else {
+            return;
+        };
+        let snapshot = viewer.snapshot();
+        self.history = snapshot.history.clone();
+        self.pending_session = Some(Arc::new(snapshot));
+        self.load_session_generation = self.load_session_generation.wrapping_add(1);
+        self.solved = false;
+    }
+
+    /// How far into the running solve `now` is, in milliseconds, starting
+    /// its recording (from the moves so far and the last reported view) if
+    /// the clock has just started. `None` while it isn't running, or if the
+    /// shader program hasn't reported a view to start from.
+    fn recording_ms(&mut self, now: Instant) -> Option<u64> {
+        let elapsed = self.timer.running_for(now)?;
+        if self.recording.is_none() {
+            self.recording = Some(SolveRecording::new(self.view?, self.history.applied()));
+        }
+        Some(elapsed.as_millis() as u64)
+    }
+
+    /// How long a move's animation takes: the setting, scaled by the
+    /// playback speed while one is loaded.
+    fn move_duration_ms(&self) -> u32 {
+        match &self.playback {
+            Some(_) => playback::scaled_duration_ms(
+                self.settings.animation_duration_ms,
+                self.playback_speed,
+            ),
+            None => self.settings.animation_duration_ms,
+        }
+    }
+
+    /// Appends the solve that just finished in `elapsed` to the solve
+    /// record and saves it.
+    fn record_solve(&mut self, elapsed: Duration) {
+        let date = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |since_epoch| since_epoch.as_secs());
+        self.solves.solves.push(SolveRecord {
+            seed: self.scramble_seed,
+            time_ms: elapsed.as_millis() as u64,
+            date,
+            metrics: Metrics::of(self.history.applied()),
+            log: self.log(),
+            recording: self.recording.take(),
+        });
+        stats::save(&self.solves);
+    }
+
+    /// Keyboard shortcuts that aren't tied to any focused widget, plus frame
+    /// ticks while the timer readout is counting or playback or the solve
+    /// viewer is playing.
+    pub(crate) fn subscription(&self) -> Subscription<Message> {
+        let shortcuts = keyboard::listen().filter_map(undo_redo_shortcut);
+        let playing = self.playback.as_ref().is_some_and(Playback::is_playing)
+            || self.viewer.as_ref().is_some_and(SolveViewer::is_playing);
+        if self.timer.is_live() || playing {
+            Subscription::batch([shortcuts, iced::window::frames().map(Message::TimerTick)])
+        } else {
+            shortcuts
+        }
+    }
+
+    /// The moves made so far, simplified so a twist and its undoing (or a
+    /// quarter turn done twice) don't read as separate moves.
+    fn history_summary(&self) -> Element<'_, Message> {
+        iced::widget::text(format_moves(&simplify_moves(self.history.applied())))
+            .size(12)
+            .width(250)
+            .into()
+    }
+
+    /// Text box for pasting move notation (e.g. `RU OBR' IUFR2`, or any
+    /// algorithm expression) plus the button that applies it, with the
+    /// parse error (if any) underneath.
+    fn notation_controls(&self) -> Column<'_, Message> {
+        let can_apply = !self.reset_animating && !self.notation_input.trim().is_empty();
+        let mut column = Column::new().spacing(5).push(
+            Row::new()
+                .spacing(5)
                 .push(
-                    Checkbox::new(self.debug_mode)
-                        .label("Debug Mode")
-                        .on_toggle(Message::DebugMode),
+                    TextInput::new("Moves, e.g. RU OBR'", &self.notation_input)
+                        .on_input(Message::NotationInput)
+                        .on_submit_maybe(can_apply.then_some(Message::ApplyNotation))
+                        .width(170),
                 )
                 .push(
-                    Column::new()
-                        .spacing(5)
-                        .push(iced::widget::text("Rotate Button"))
-                        .push(
-                            PickList::new(
-                                &RotateButton::ALL[..],
-                                Some(self.settings.rotate_button),
-                                Message::RotateButton,
-                            )
-                            .width(250),
+                    Button::new("Apply")
+                        .on_press_maybe(can_apply.then_some(Message::ApplyNotation)),
+                ),
+        );
+        if let Some(error) = &self.notation_error {
+            column = column.push(iced::widget::text(error).size(12));
+        }
+        column
+    }
+
+    /// A button per saved algorithm that plays it through the move
+    /// animation (with ones to show its cycles and to delete it), then boxes
+    /// to name and write a new one and save it, then the cycles of the last
+    /// one analyzed.
+    fn algorithm_controls(&self) -> Column<'_, Message> {
+        let mut column = Column::new()
+            .spacing(5)
+            .push(iced::widget::text("Algorithms"));
+        for (index, algorithm) in self.settings.algorithms.iter().enumerate() {
+            column = column.push(
+                Row::new()
+                    .spacing(5)
+                    .align_y(iced::Alignment::Center)
+                    .push(
+                        Button::new(iced::widget::text(&algorithm.name)).on_press_maybe(
+                            (!self.reset_animating).then_some(Message::PlayAlgorithm(index)),
                         ),
+                    )
+                    .push(iced::widget::text(&algorithm.expression).size(12).width(70))
+                    .push(Button::new("Cycles").on_press(Message::AnalyzeAlgorithm(index)))
+                    .push(Button::new("Delete").on_press(Message::DeleteAlgorithm(index))),
+            );
+        }
+        let can_save = !self.algorithm_input.trim().is_empty();
+        column = column.push(
+            Row::new()
+                .spacing(5)
+                .push(
+                    TextInput::new("Name", &self.algorithm_name)
+                        .on_input(Message::AlgorithmName)
+                        .width(60),
                 )
                 .push(
-                    Button::new("Reset")
-                        .on_press_maybe((!self.reset_animating).then_some(Message::Reset)),
+                    TextInput::new("[RU, LB]", &self.algorithm_input)
+                        .on_input(Message::AlgorithmInput)
+                        .on_submit_maybe(can_save.then_some(Message::SaveAlgorithm))
+                        .width(105),
                 )
                 .push(
-                    Column::new()
+                    Button::new("Save").on_press_maybe(can_save.then_some(Message::SaveAlgorithm)),
+                ),
+        );
+        if let Some(error) = &self.algorithm_error {
+            column = column.push(iced::widget::text(error).size(12));
+        }
+        if let Some((name, analysis)) = &self.analysis {
+            column = column
+                .push(
+                    Row::new()
+                        .spacing(10)
+                        .align_y(iced::Alignment::Center)
+                        .push(iced::widget::text(format!("{name} moves:")))
+                        .push(Button::new("Clear").on_press(Message::ClearAnalysis)),
+                )
+                .push(iced::widget::text(analysis.to_string()).size(12).width(250));
+        }
+        column
+    }
+
+    /// The hint mode toggle, Play hint, and the next move with how to click
+    /// it.
+    fn hint_controls(&self) -> Column<'_, Message> {
+        let next = self.current_hint().and_then(|hint| hint.path.first());
+        let mut column = Column::new().spacing(5).push(
+            Row::new()
+                .spacing(10)
+                .align_y(iced::Alignment::Center)
+                .push(
+                    Checkbox::new(self.hints)
+                        .label("Hints")
+                        .on_toggle(Message::Hints),
+                )
+                .push(
+                    Button::new("Play hint").on_press_maybe(
+                        (self.hints && next.is_some() && !self.reset_animating)
+                            .then_some(Message::PlayHint),
+                    ),
+                ),
+        );
+        if self.hints {
+            let status = match (self.current_hint(), next) {
+                (None, _) => "Working out a hint...".to_string(),
+                (Some(_), None) => "Solved".to_string(),
+                (Some(hint), Some(&mv)) => {
+                    let how =
+                        Click::for_move(mv).map_or(String::new(), |click| format!(": {click}"));
+                    format!("Next: {mv}{how} ({} to go)", hint.path.len())
+                }
+            };
+            column = column.push(iced::widget::text(status).size(12));
+        }
+        column
+    }
+
+    /// The painting mode toggle, the brush, and Apply with why the last
+    /// draft was rejected.
+    fn paint_controls(&self) -> Column<'_, Message> {
+        let mut column = Column::new().spacing(5).push(
+            Checkbox::new(self.painting)
+                .label("Paint Stickers")
+                .on_toggle(Message::Painting),
+        );
+        if self.painting {
+            column = column.push(
+                Row::new()
+                    .spacing(5)
+                    .push(
+                        PickList::new(&Brush::ALL[..], Some(self.paint_brush), Message::PaintBrush)
+                            .width(150),
+                    )
+                    .push(Button::new("Apply").on_press(Message::ApplyPaint)),
+            );
+            if let Some(error) = &self.paint_error {
+                column = column.push(iced::widget::text(error).size(12));
+            }
+        }
+        column
+    }
+
+    /// A grip key and a twist key box per cell, rebinding as they're typed
+    /// into.
+    fn key_binding_controls(&self) -> Column<'_, Message> {
+        let bindings = &self.settings.key_bindings;
+        let mut column = Column::new()
+            .spacing(5)
+            .push(iced::widget::text("Keyboard Twisting"))
+            .push(
+                iced::widget::text(
+                    "With the cursor over the puzzle, hold a cell's grip key and \
+                     press the twist keys of the cells naming the grip",
+                )
+                .size(12)
+                .width(250),
+            )
+            .push(
+                Row::new()
+                    .spacing(5)
+                    .push(iced::widget::text("Cell").size(12).width(40))
+                    .push(iced::widget::text("Grip").size(12).width(60))
+                    .push(iced::widget::text("Twist").size(12).width(60)),
+            );
+        for (cell, letter) in CELL_LETTERS.iter().enumerate() {
+            column = column.push(
+                Row::new()
+                    .spacing(5)
+                    .align_y(iced::Alignment::Center)
+                    .push(iced::widget::text(letter.to_string()).width(40))
+                    .push(
+                        TextInput::new("", &bindings.grips[cell])
+                            .on_input(move |typed| Message::GripKey(cell, typed))
+                            .width(60),
+                    )
+                    .push(
+                        TextInput::new("", &bindings.twists[cell])
+                            .on_input(move |typed| Message::TwistKey(cell, typed))
+                            .width(60),
+                    ),
+            );
+        }
+        column
+    }
+
+    /// Inputs for an algorithm search, Start/Cancel, its progress, and the
+    /// first solutions found, each with a button to save it.
+    fn search_controls(&self) -> Column<'_, Message> {
+        let running = self.search_cancel.is_some();
+        let mut column = Column::new()
+            .spacing(5)
+            .push(iced::widget::text("Algorithm Search"))
+            .push(
+                TextInput::new("Effect to find, e.g. [IBUR', LB]", &self.search_target)
+                    .on_input(Message::SearchTarget)
+                    .width(250),
+            )
+            .push(
+                Row::new()
+                    .spacing(5)
+                    .push(
+                        PickList::new(
+                            &TrackedPieces::ALL[..],
+                            Some(self.search_pieces),
+                            Message::SearchPieces,
+                        )
+                        .width(120),
+                    )
+                    .push(
+                        TextInput::new("Cells, e.g. IL", &self.search_cells)
+                            .on_input(Message::SearchCells)
+                            .width(125),
+                    ),
+            )
+            .push(iced::widget::text(format!(
+                "Max depth: {}",
+                self.search_depth
+            )))
+            .push(Slider::new(1..=12, self.search_depth, Message::SearchDepth).width(250))
+            .push(
+                Row::new()
+                    .spacing(5)
+                    .push(Button::new("Start").on_press_maybe(
+                        (!self.search_target.trim().is_empty()).then_some(Message::StartSearch),
+                    ))
+                    .push(
+                        Button::new("Cancel")
+                            .on_press_maybe(running.then_some(Message::CancelSearch)),
+                    ),
+            )
+            .push(iced::widget::text(&self.search_status).size(12));
+        for (index, solution) in self
+            .search_solutions
+            .iter()
+            .enumerate()
+            .take(SHOWN_SOLUTIONS)
+        {
+            column = column.push(
+                Row::new()
+                    .spacing(5)
+                    .align_y(iced::Alignment::Center)
+                    .push(
+                        iced::widget::text(format_moves(solution))
+                            .size(12)
+                            .width(190),
+                    )
+                    .push(Button::new("Save").on_press(Message::SaveSolution(index))),
+            );
+        }
+        if self.search_solutions.len() > SHOWN_SOLUTIONS {
+            column = column.push(
+                iced::widget::text(format!(
+                    "...and {} more",
+                    self.search_solutions.len() - SHOWN_SOLUTIONS
+                ))
+                .size(12),
+            );
+        }
+        column
+    }
+
+    /// The current scramble's seed and moves (copyable, for racing someone
+    /// on the same scramble), plus a box to paste a seed or scramble text
+    /// into to reproduce one.
+    fn scramble_controls(&self) -> Column<'_, Message> {
+        let can_load = !self.reset_animating && !self.scramble_input.trim().is_empty();
+        let seed = self
+            .scramble_seed
+            .map_or("Seed: -".to_string(), |seed| format!("Seed: {seed}"));
+        let mut column = Column::new()
+            .spacing(5)
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_y(iced::Alignment::Center)
+                    .push(iced::widget::text(seed))
+                    .push(
+                        Button::new("Copy Scramble").on_press_maybe(
+                            (self.scramble_start.is_some() || !self.scramble.is_empty())
+                                .then_some(Message::CopyScramble),
+                        ),
+                    ),
+            )
+            .push(
+                iced::widget::text(scramble_summary(
+                    self.scramble_start.is_some(),
+                    &self.scramble,
+                ))
+                .size(12)
+                .width(250),
+            )
+            .push(
+                Row::new()
+                    .spacing(5)
+                    .push(
+                        TextInput::new("Seed or scramble moves", &self.scramble_input)
+                            .on_input(Message::ScrambleInput)
+                            .on_submit_maybe(can_load.then_some(Message::LoadScramble))
+                            .width(170),
+                    )
+                    .push(
+                        Button::new("Load")
+                            .on_press_maybe(can_load.then_some(Message::LoadScramble)),
+                    ),
+            );
+        if let Some(error) = &self.scramble_error {
+            column = column.push(iced::widget::text(error).size(12));
+        }
+        column
+    }
+
+    /// Session file path box plus Save/Open, with the last outcome
+    /// underneath.
+    fn session_controls(&self) -> Column<'_, Message> {
+        let has_path = !self.session_path.trim().is_empty() && !self.reset_animating;
+        let mut column = Column::new()
+            .spacing(5)
+            .push(iced::widget::text("Session File"))
+            .push(
+                TextInput::new("Path to a session file", &self.session_path)
+                    .on_input(Message::SessionPath)
+                    .width(250),
+            )
+            .push(
+                Row::new()
+                    .spacing(5)
+                    .push(
+                        Button::new("Save")
+                            .on_press_maybe(has_path.then_some(Message::SaveSession)),
+                    )
+                    .push(
+                        Button::new("Open")
+                            .on_press_maybe(has_path.then_some(Message::OpenSession)),
+                    ),
+            );
+        if let Some(status) = &self.session_status {
+            column = column.push(iced::widget::text(status).size(12));
+        }
+        column
+    }
+
+    /// Whether Scramble/Random Move(s) animate, the loaded playback's
+    /// progress and next move with its play/pause/step/stop buttons, and the
+    /// playback speed.
+    fn playback_controls(&self) -> Column<'_, Message> {
+        let mut column = Column::new()
+            .spacing(5)
+            .push(iced::widget::text("Playback"))
+            .push(
+                Checkbox::new(self.animate_scrambles)
+                    .label("Animate scrambles and random moves")
+                    .on_toggle(Message::AnimateScrambles),
+            );
+        if let Some(playback) = &self.playback {
+            let played = playback.progress(&self.history).unwrap_or(0);
+            let total = playback.moves().len();
+            let next = playback
+                .moves()
+                .get(played)
+                .map_or("Done".to_string(), |mv| format!("Next: {mv}"));
+            let idle = !self.reset_animating;
+            // Played scrambles and random moves still have a step left:
+            // becoming the scramble.
+            let more = played < total
+                || matches!(
+                    playback.kind,
+                    PlaybackKind::Scramble { .. } | PlaybackKind::RandomMoves { .. }
+                );
+            column = column
+                .push(
+                    iced::widget::text(format!("{}: {played}/{total} ({next})", playback.kind))
+                        .size(12),
+                )
+                .push(
+                    Row::new()
                         .spacing(5)
-                        .push(Button::new("1 Random Move").on_press_maybe(
-                            (!self.reset_animating).then_some(Message::RandomMoves(1)),
+                        .push(Button::new("Back").on_press_maybe(
+                            (idle && played > 0).then_some(Message::PlaybackStepBack),
                         ))
-                        .push(Button::new("2 Random Moves").on_press_maybe(
-                            (!self.reset_animating).then_some(Message::RandomMoves(2)),
+                        .push(if playback.is_playing() {
+                            Button::new("Pause").on_press(Message::PlaybackPlaying(false))
+                        } else {
+                            Button::new("Play").on_press_maybe(
+                                (idle && more).then_some(Message::PlaybackPlaying(true)),
+                            )
+                        })
+                        .push(
+                            Button::new("Step")
+                                .on_press_maybe((idle && more).then_some(Message::PlaybackStep)),
+                        )
+                        .push(Button::new("Stop").on_press(Message::StopPlayback)),
+                );
+        }
+        if let Some(viewer) = &self.viewer {
+            column = column.push(self.viewer_controls(viewer));
+        }
+        column.push(
+            Row::new()
+                .spacing(10)
+                .align_y(iced::Alignment::Center)
+                .push(
+                    Slider::new(
+                        playback::SPEED_RANGE,
+                        self.playback_speed,
+                        Message::PlaybackSpeed,
+                    )
+                    .step(0.25)
+                    .width(200),
+                )
+                .push(iced::widget::text(format_playback_speed(
+                    self.playback_speed,
+                ))),
+        )
+    }
+
+    /// The solve viewer's timeline (a slider over the solve, with where it
+    /// is of how long it took), Play/Pause and Close, then the moves with
+    /// the last one made highlighted; clicking one jumps to it.
+    fn viewer_controls(&self, viewer: &SolveViewer) -> Column<'_, Message> {
+        let at = Duration::from_millis(viewer.at_ms());
+        let duration = Duration::from_millis(viewer.duration_ms());
+        let current = viewer.played().checked_sub(1);
+        let moves = viewer.moves().iter().enumerate().map(|(index, timed)| {
+            let style: fn(&iced::Theme, button::Status) -> button::Style = if Some(index) == current
+            {
+                button::primary
+            } else {
+                button::text
+            };
+            Button::new(iced::widget::text(timed.mv.to_string()).size(12))
+                .padding([2, 4])
+                .style(style)
+                .on_press(Message::ViewerSeek(timed.at_ms as u32))
+                .into()
+        });
+        Column::new()
+            .spacing(5)
+            .push(
+                iced::widget::text(format!(
+                    "Solve {}: {} / {}",
+                    viewer.solve + 1,
+                    format_solve_time(at),
+                    format_solve_time(duration),
+                ))
+                .size(12),
+            )
+            .push(
+                Slider::new(
+                    0..=viewer.duration_ms() as u32,
+                    viewer.at_ms() as u32,
+                    Message::ViewerSeek,
+                )
+                .width(250),
+            )
+            .push(
+                Row::new()
+                    .spacing(5)
+                    .push(if viewer.is_playing() {
+                        Button::new("Pause").on_press(Message::ViewerPlaying(false))
+                    } else {
+                        Button::new("Play").on_press(Message::ViewerPlaying(true))
+                    })
+                    .push(Button::new("Close").on_press(Message::CloseViewer)),
+            )
+            .push(Row::with_children(moves).spacing(2).width(250).wrap())
+    }
+
+    /// MC4D log path box plus Import/Export, with the last outcome
+    /// underneath.
+    fn log_controls(&self) -> Column<'_, Message> {
+        let has_path = !self.log_path.trim().is_empty() && !self.reset_animating;
+        let mut column = Column::new()
+            .spacing(5)
+            .push(iced::widget::text("MC4D Log"))
+            .push(
+                TextInput::new("Path to a .log file", &self.log_path)
+                    .on_input(Message::LogPath)
+                    .width(250),
+            )
+            .push(
+                Row::new()
+                    .spacing(5)
+                    .push(
+                        Button::new("Import")
+                            .on_press_maybe(has_path.then_some(Message::ImportLog)),
+                    )
+                    .push(
+                        Button::new("Export")
+                            .on_press_maybe(has_path.then_some(Message::ExportLog)),
+                    )
+                    .push(
+                        Button::new("Watch").on_press_maybe(has_path.then_some(Message::WatchLog)),
+                    ),
+            );
+        if let Some(status) = &self.log_status {
+            column = column.push(iced::widget::text(status).size(12));
+        }
+        column
+    }
+
+    /// Current and best single/averages, then every recorded solve (most
+    /// recent first) with buttons to reopen it at its finish or replay it
+    /// from its scramble.
+    fn stats_panel(&self) -> Column<'_, Message> {
+        let time_or_dash = |time: Option<Duration>| time.map_or("-".to_string(), format_solve_time);
+        let mut table = Column::new().spacing(2).push(
+            Row::new()
+                .push(iced::widget::text("").width(60))
+                .push(iced::widget::text("current").width(80))
+                .push(iced::widget::text("best").width(80)),
+        );
+        for statistic in stats::statistics(&self.solves.solves) {
+            table = table.push(
+                Row::new()
+                    .push(iced::widget::text(statistic.label).width(60))
+                    .push(iced::widget::text(time_or_dash(statistic.current)).width(80))
+                    .push(iced::widget::text(time_or_dash(statistic.best)).width(80)),
+            );
+        }
+
+        let mut solves = Column::new().spacing(5);
+        for (index, record) in self.solves.solves.iter().enumerate().rev() {
+            solves = solves.push(
+                Row::new()
+                    .spacing(5)
+                    .align_y(iced::Alignment::Center)
+                    .push(
+                        iced::widget::text(format!(
+                            "{}. {} ({}, {})",
+                            index + 1,
+                            format_solve_time(record.time()),
+                            record.metrics,
+                            stats::format_date(record.date),
                         ))
-                        .push(Button::new("3 Random Moves").on_press_maybe(
+                        .size(12),
+                    )
+                    .push(Button::new("Open").on_press_maybe(
+                        (!self.reset_animating).then_some(Message::OpenSolve(index)),
+                    ))
+                    .push(Button::new("Replay").on_press_maybe(
+                        (!self.reset_animating).then_some(Message::ReplaySolve(index)),
+                    ))
+                    .push(
+                        Button::new("Watch").on_press_maybe(
+                            (record.recording.is_some() && !self.reset_animating)
+                                .then_some(Message::WatchSolve(index)),
+                        ),
+                    ),
+            );
+        }
+
+        Column::new()
+            .spacing(5)
+            .push(iced::widget::text("Statistics"))
+            .push(table)
+            .push(solves)
+    }
+
+    /// Create the view for the application
+    pub(crate) fn view(&self) -> Element<'_, Message> {
+        // Left pane with controls
+        let mut controls = Column::new()
+            .spacing(20)
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_y(iced::Alignment::Center)
+                    .push(iced::widget::text(self.timer.display(self.now)).size(36))
+                    .push(iced::widget::text(
+                        Metrics::of(self.history.applied()).to_string(),
+                    )),
+            )
+            .push(
+                Checkbox::new(self.debug_mode)
+                    .label("Debug Mode")
+                    .on_toggle(Message::DebugMode),
+            )
+            .push(
+                Column::new()
+                    .spacing(5)
+                    .push(iced::widget::text("Rotate Button"))
+                    .push(
+                        PickList::new(
+                            &RotateButton::ALL[..],
+                            Some(self.settings.rotate_button),
+                            Message::RotateButton,
+                        )
+                        .width(250),
+                    ),
+            )
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_y(iced::Alignment::Center)
+                    .push(
+                        Button::new("Reset")
+                            .on_press_maybe((!self.reset_animating).then_some(Message::Reset)),
+                    )
+                    .push(
+                        Button::new("Solve").on_press_maybe(
+                            (!self.reset_animating && !self.solved && !self.solving)
+                                .then_some(Message::Solve),
+                        ),
+                    )
+                    .push(iced::widget::text(if self.solved { "Solved!" } else { "" })),
+            )
+            .push(
+                Row::new()
+                    .spacing(5)
+                    .push(Button::new("Undo").on_press_maybe(
+                        (self.history.can_undo() && !self.reset_animating).then_some(Message::Undo),
+                    ))
+                    .push(Button::new("Redo").on_press_maybe(
+                        (self.history.can_redo() && !self.reset_animating).then_some(Message::Redo),
+                    ))
+                    .push(
+                        Button::new("Copy Moves")
+                            .on_press_maybe(self.history.can_undo().then_some(Message::CopyMoves)),
+                    ),
+            )
+            .push(self.hint_controls())
+            .push(self.paint_controls())
+            .push(self.history_summary())
+            .push(self.notation_controls())
+            .push(self.algorithm_controls())
+            .push(self.search_controls())
+            .push(self.key_binding_controls())
+            .push(
+                Column::new()
+                    .spacing(5)
+                    .push(
+                        Button::new("1 Random Move").on_press_maybe(
+                            (!self.reset_animating).then_some(Message::RandomMoves(1)),
+                        ),
+                    )
+                    .push(
+                        Button::new("2 Random Moves").on_press_maybe(
+                            (!self.reset_animating).then_some(Message::RandomMoves(2)),
+                        ),
+                    )
+                    .push(
+                        Button::new("3 Random Moves").on_press_maybe(
                             (!self.reset_animating).then_some(Message::RandomMoves(3)),
-                        ))
-                        .push(
-                            Button::new("Scramble").on_press_maybe(
-                                (!self.reset_animating)
-                                    .then_some(Message::RandomMoves(SCRAMBLE_MOVE_COUNT)),
-                            ),
                         ),
-                );
+                    )
+                    .push(
+                        Button::new("Scramble")
+                            .on_press_maybe((!self.reset_animating).then_some(Message::Scramble)),
+                    ),
+            )
+            .push(self.playback_controls())
+            .push(self.scramble_controls())
+            .push(self.session_controls())
+            .push(self.log_controls())
+            .push(self.stats_panel());
 
         if self.debug_mode {
             controls = controls
@@ -451,20 +2295,44 @@ impl HypercubeApp {
         );
 
         // Right pane with 3D viewport
-        let viewport = Shader::new(HypercubeShaderProgram::new(
+        let viewport = Shader::new(HypercubeShaderProgram {
             // Invert value since the slider can't work in reverse.
-            1.0 - self.sticker_scale,
-            self.face_gap,
-            self.render_mode,
-            self.aabb_mode,
-            self.settings.rotate_button,
-            self.settings.animation_duration_ms,
-            self.reset_generation,
-            self.random_moves_generation,
-            self.pending_random_move_count,
-            self.reveal_generation,
-            self.revealed,
-        ))
+            sticker_scale: 1.0 - self.sticker_scale,
+            face_gap: self.face_gap,
+            render_mode: self.render_mode,
+            aabb_mode: self.aabb_mode,
+            rotate_button: self.settings.rotate_button,
+            animation_duration_ms: self.move_duration_ms(),
+            reset_generation: self.reset_generation,
+            random_moves_generation: self.random_moves_generation,
+            random_move_count: self.pending_random_move_count,
+            random_seed: self.pending_random_seed,
+            reveal_generation: self.reveal_generation,
+            revealed_target: self.revealed,
+            undo_generation: self.undo_generation,
+            redo_generation: self.redo_generation,
+            apply_moves_generation: self.apply_moves_generation,
+            pending_moves: self.pending_moves.clone(),
+            save_session_generation: self.save_session_generation,
+            load_session_generation: self.load_session_generation,
+            pending_session: self.pending_session.clone(),
+            replay_generation: self.replay_generation,
+            pending_replay: self.pending_replay.clone(),
+            play_generation: self.play_generation,
+            pending_play: self.pending_play.clone(),
+            highlighted_slots: self
+                .analysis
+                .as_ref()
+                .map_or([0; 4], |(_, analysis)| analysis.slot_mask()),
+            hint: (self.hints && !self.painting)
+                .then(|| self.current_hint()?.path.first().copied())
+                .flatten()
+                .and_then(Click::for_move),
+            paint_brush: self.painting.then_some(self.paint_brush),
+            apply_paint_generation: self.apply_paint_generation,
+            key_bindings: Arc::new(self.settings.key_bindings.clone()),
+            record_view: self.timer.is_live(),
+        })
         .width(Length::Fill)
         .height(Length::Fill);
 
@@ -473,7 +2341,7 @@ impl HypercubeApp {
             .spacing(10)
             .padding(10)
             .push(
-                iced::widget::container(controls)
+                iced::widget::container(iced::widget::scrollable(controls))
                     .width(Length::Shrink)
                     .height(Length::Fill),
             )
@@ -508,6 +2376,13 @@ mod tests {
         assert_eq!(format_animation_duration(600), "600ms");
     }
 
+    #[test]
+    fn format_playback_speed_appends_x_suffix() {
+        assert_eq!(format_playback_speed(0.25), "0.25x");
+        assert_eq!(format_playback_speed(1.0), "1x");
+        assert_eq!(format_playback_speed(4.0), "4x");
+    }
+
     #[test]
     fn tooltip_delay_is_zero_while_adjusting() {
         assert_eq!(tooltip_delay(true), Duration::ZERO);
@@ -540,6 +2415,112 @@ mod tests {
         assert!(sliders_visible(true, false));
     }
 
+    #[test]
+    fn hinted_solves_are_not_recorded() {
+        let mut app = HypercubeApp::new_inner();
+        let recorded = app.solves.solves.len();
+        let running = SolveTimer::Running {
+            started: Instant::now(),
+        };
+        app.hint = Some(Hint {
+            position: app.log(),
+            path: parse_moves("RU").unwrap(),
+        });
+
+        app.timer = running;
+        let _ = app.update(Message::Hints(true));
+        assert_eq!(app.timer, SolveTimer::Idle);
+
+        app.timer = running;
+        let _ = app.update(Message::PlayHint);
+        assert_eq!(app.timer, SolveTimer::Idle);
+        assert_eq!(app.play_generation, 1);
+
+        let _ = app.update(Message::Solved {
+            committed_at: Instant::now(),
+        });
+        assert_eq!(app.solves.solves.len(), recorded);
+    }
+
+    fn key_press(key: Key, modifiers: keyboard::Modifiers) -> keyboard::Event {
+        keyboard::Event::KeyPressed {
+            key: key.clone(),
+            modified_key: key,
+            physical_key: keyboard::key::Physical::Unidentified(
+                keyboard::key::NativeCode::Unidentified,
+            ),
+            location: keyboard::Location::Standard,
+            modifiers,
+            text: None,
+            repeat: false,
+        }
+    }
+
+    #[test]
+    fn undo_redo_shortcut_maps_command_z_and_y() {
+        let z = Key::Character("z".into());
+        let y = Key::Character("y".into());
+        let command = keyboard::Modifiers::COMMAND;
+        let command_shift = command | keyboard::Modifiers::SHIFT;
+
+        assert!(matches!(
+            undo_redo_shortcut(key_press(z.clone(), command)),
+            Some(Message::Undo)
+        ));
+        assert!(matches!(
+            undo_redo_shortcut(key_press(z.clone(), command_shift)),
+            Some(Message::Redo)
+        ));
+        assert!(matches!(
+            undo_redo_shortcut(key_press(y, command)),
+            Some(Message::Redo)
+        ));
+        assert!(undo_redo_shortcut(key_press(z, keyboard::Modifiers::empty())).is_none());
+    }
+
+    #[test]
+    fn parse_scramble_input_reads_a_number_as_a_seed_and_anything_else_as_moves() {
+        assert_eq!(
+            parse_scramble_input(" 12345 "),
+            Ok(ScrambleInput::Seed(12345))
+        );
+        assert_eq!(
+            parse_scramble_input("UF DB2"),
+            Ok(ScrambleInput::Moves(parse_moves("UF DB2").unwrap()))
+        );
+        assert!(parse_scramble_input("-1").is_err());
+    }
+
+    #[test]
+    fn from_solved_spells_a_start_state_out_ahead_of_the_scramble() {
+        let log = MoveLog {
+            start: Some(state_for_seed(7)),
+            scramble: parse_moves("UF DB2").unwrap(),
+            moves: parse_moves("RU").unwrap(),
+        };
+        let spelled = from_solved(log.clone()).unwrap();
+        assert_eq!(spelled.start, None);
+        assert!(spelled.scramble.ends_with(&log.scramble));
+        assert_eq!(spelled.moves, log.moves);
+        assert_eq!(spelled.puzzle(), log.puzzle());
+    }
+
+    #[test]
+    fn saved_algorithm_must_parse_and_is_named_after_itself_by_default() {
+        assert_eq!(
+            saved_algorithm(" Sune ", " [RU, LB]2 "),
+            Ok(SavedAlgorithm {
+                name: "Sune".to_string(),
+                expression: "[RU, LB]2".to_string(),
+            })
+        );
+        assert_eq!(
+            saved_algorithm("", "[RU: LB]").map(|algorithm| algorithm.name),
+            Ok("[RU: LB]".to_string())
+        );
+        assert!(saved_algorithm("Broken", "[RU LB]").is_err());
+    }
+
     #[cfg(feature = "gpu-capture-hooks")]
     #[test]
     fn next_reveal_loop_action_repeats_until_remaining_is_exhausted() {
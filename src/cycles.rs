@@ -0,0 +1,243 @@
+//! Which pieces a move sequence moves, and how: the permutation and
+//! orientation change it applies to the 80 movable pieces, as disjoint
+//! cycles grouped by piece type.
+//!
+//! Read off a solved puzzle with the sequence applied: the piece now in
+//! slot `s` came from `index_of(piece.home())`, turned by
+//! `piece.orientation()`. A cycle `(BU RU FU)` means the piece in `BU` goes
+//! to `RU`, the one there to `FU`, and that one back to `BU`.
+
+use std::fmt;
+
+use crate::moves::{Move, piece_name};
+use crate::piece::{
+    Hypercube, IDENTITY_ORIENTATION, Orientation, compose_orientations, index_of, position_of,
+    slots_with,
+};
+
+/// Labels of the piece types, by `Piece::facet_count`.
+const PIECE_TYPES: [(usize, &str); 4] = [
+    (1, "Cell centers"),
+    (2, "Faces"),
+    (3, "Edges"),
+    (4, "Corners"),
+];
+
+/// One cycle of slots, in the order pieces move along it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Cycle {
+    pub(crate) slots: Vec<usize>,
+    /// How many times round the cycle its pieces take to come home turned
+    /// the way they started: 1 unless going round once leaves them twisted
+    /// (the only way a cycle of one slot is listed at all).
+    pub(crate) twist_order: u8,
+}
+
+impl fmt::Display for Cycle {
+    /// Writes `(BU RU FU)`, then `+n` if it's twisted with order `n`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(")?;
+        for (index, &slot) in self.slots.iter().enumerate() {
+            if index > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", piece_name(position_of(slot)))?;
+        }
+        write!(f, ")")?;
+        if self.twist_order > 1 {
+            write!(f, "+{}", self.twist_order)?;
+        }
+        Ok(())
+    }
+}
+
+/// The cycles of a move sequence's effect, per piece type.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct CycleAnalysis {
+    /// Indexed like `PIECE_TYPES`, each in order of the cycle's first slot.
+    cycles: [Vec<Cycle>; 4],
+}
+
+impl CycleAnalysis {
+    pub(crate) fn of(moves: &[Move]) -> Self {
+        let mut cube = Hypercube::solved();
+        for &mv in moves {
+            cube.apply_move(mv);
+        }
+        // Where the piece from each home slot went, and how it's turned.
+        let mut target = [0; 81];
+        let mut turned = [IDENTITY_ORIENTATION; 81];
+        for (slot, piece) in cube.pieces.iter().enumerate() {
+            let home = index_of(piece.home());
+            target[home] = slot;
+            turned[home] = piece.orientation();
+        }
+
+        let mut seen = [false; 81];
+        let mut analysis = Self::default();
+        for ((facet_count, _), cycles) in PIECE_TYPES.iter().zip(&mut analysis.cycles) {
+            for start in slots_with(*facet_count) {
+                if seen[start] {
+                    continue;
+                }
+                let mut slots = Vec::new();
+                let mut net = IDENTITY_ORIENTATION;
+                let mut at = start;
+                loop {
+                    seen[at] = true;
+                    slots.push(at);
+                    net = compose_orientations(net, turned[at]);
+                    at = target[at];
+                    if at == start {
+                        break;
+                    }
+                }
+                let twist_order = order(net);
+                if slots.len() > 1 || twist_order > 1 {
+                    cycles.push(Cycle { slots, twist_order });
+                }
+            }
+        }
+        analysis
+    }
+
+    /// Cycles among the pieces with `facet_count` stickers (1-4).
+    #[cfg(test)]
+    pub(crate) fn cycles(&self, facet_count: usize) -> &[Cycle] {
+        &self.cycles[facet_count - 1]
+    }
+
+    pub(crate) fn is_identity(&self) -> bool {
+        self.cycles.iter().all(Vec::is_empty)
+    }
+
+    /// Every slot the sequence moves or twists a piece in, as a bitmask
+    /// over `Hypercube::pieces` slots (bit `s % 32` of word `s / 32`), the
+    /// form the renderer's highlighting takes.
+    pub(crate) fn slot_mask(&self) -> [u32; 4] {
+        let mut mask = [0; 4];
+        for slot in self.cycles.iter().flatten().flat_map(|cycle| &cycle.slots) {
+            mask[slot / 32] |= 1 << (slot % 32);
+        }
+        mask
+    }
+}
+
+impl fmt::Display for CycleAnalysis {
+    /// One line per piece type that moves, e.g. `Faces: (BU RU FU)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_identity() {
+            return write!(f, "No pieces move");
+        }
+        let mut first = true;
+        for ((_, label), cycles) in PIECE_TYPES.iter().zip(&self.cycles) {
+            if cycles.is_empty() {
+                continue;
+            }
+            if !first {
+                writeln!(f)?;
+            }
+            first = false;
+            write!(f, "{label}:")?;
+            for cycle in cycles {
+                write!(f, " {cycle}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How many times `orientation` has to be applied to get back to the
+/// identity.
+fn order(orientation: Orientation) -> u8 {
+    let mut power = orientation;
+    let mut order = 1;
+    while power != IDENTITY_ORIENTATION {
+        power = compose_orientations(power, orientation);
+        order += 1;
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::parse_alg;
+    use crate::moves::parse_moves;
+
+    fn analysis(text: &str) -> CycleAnalysis {
+        CycleAnalysis::of(&parse_alg(text).unwrap().expand())
+    }
+
+    #[test]
+    fn sequences_that_cancel_move_nothing() {
+        for text in ["", "RU RU'", "RUFO3", "[RU, RU]"] {
+            let analysis = analysis(text);
+            assert!(analysis.is_identity(), "{text}: {analysis}");
+            assert_eq!(analysis.slot_mask(), [0; 4]);
+            assert_eq!(analysis.to_string(), "No pieces move");
+        }
+    }
+
+    #[test]
+    fn face_turn_four_cycles_its_side() {
+        let analysis = analysis("RU");
+        let lengths = |facet_count| -> Vec<usize> {
+            analysis
+                .cycles(facet_count)
+                .iter()
+                .map(|cycle| {
+                    assert_eq!(cycle.twist_order, 1);
+                    cycle.slots.len()
+                })
+                .collect()
+        };
+        assert!(lengths(1).is_empty());
+        assert_eq!(lengths(2), [4]);
+        assert_eq!(lengths(3), [4, 4, 4]);
+        assert_eq!(lengths(4), [4, 4]);
+
+        // Everything on the R side moves, except its center and the two
+        // face pieces on the grip axis.
+        let mask = analysis.slot_mask();
+        let moved: Vec<usize> = (0..81)
+            .filter(|slot| mask[slot / 32] & (1 << (slot % 32)) != 0)
+            .collect();
+        assert_eq!(moved.len(), 24);
+        assert!(moved.iter().all(|&slot| position_of(slot)[2] == 1));
+    }
+
+    #[test]
+    fn cycles_follow_pieces_and_their_twists() {
+        // The solver's face 3-cycle moves exactly three face pieces.
+        let face_cycles = analysis("[IBUR', LB]").cycles(2).to_vec();
+        assert_eq!(face_cycles.len(), 1);
+        assert_eq!(face_cycles[0].slots.len(), 3);
+
+        // The inverse runs the same cycles backwards.
+        let forward = CycleAnalysis::of(&parse_moves("RU").unwrap());
+        let backward = CycleAnalysis::of(&parse_moves("RU'").unwrap());
+        for facet_count in 1..=4 {
+            for (a, b) in forward
+                .cycles(facet_count)
+                .iter()
+                .zip(backward.cycles(facet_count))
+            {
+                let mut reversed = b.slots.clone();
+                reversed[1..].reverse();
+                assert_eq!(a.slots, reversed);
+            }
+        }
+
+        // An edge twist flips the edge piece on its own axis in place.
+        let twist = analysis("RUF");
+        assert!(twist.cycles(3).contains(&Cycle {
+            slots: vec![index_of([-1, 1, 1, 0])],
+            twist_order: 2,
+        }));
+        assert!(twist.to_string().contains("(FUR)+2"), "{twist}");
+
+        // Slice moves carry cell centers.
+        assert_eq!(analysis("{2}RU").cycles(1).len(), 1);
+    }
+}
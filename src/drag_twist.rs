@@ -0,0 +1,132 @@
+//! Drag-to-twist: pressing on a sticker and dragging across it twists the
+//! sticker's cell the way that carries the grabbed point along the drag.
+//!
+//! The candidates are the cell's twists about its three face grips, each
+//! way. Each one nudges the grabbed point (the sticker's center plus the
+//! press's offset along the facet's `basis`) a little with
+//! `rotate_local_position`, exactly as the move animation would start it
+//! off; whichever nudge's on-screen motion goes furthest along the drag
+//! wins. Edge and corner grips stay with clicks and keys: their motions run
+//! close to the face grips', so a drag couldn't tell them apart reliably.
+
+use nalgebra::{Vector2, Vector3, Vector4};
+
+use crate::moves::{Move, rotate_local_position};
+use crate::piece::FACET_TABLE;
+
+/// Fraction of a candidate's turn the grabbed point is nudged by: small
+/// enough that its on-screen motion is effectively a direction.
+const NUDGE: f32 = 0.01;
+/// Least cosine between a twist's on-screen motion and the drag for the
+/// twist to count as following it.
+const MIN_ALIGNMENT: f32 = 0.5;
+
+/// The twist of sticker `sticker`'s cell that best follows a screen-space
+/// `drag` starting from `offset` (a `ray_casting::StickerHit` offset).
+/// `to_screen` places a 4D sticker-space point on screen, the way the
+/// renderer would draw it. `None` if the sticker isn't actionable, the drag
+/// has no length, or no twist moves the point within 60° of it.
+pub(crate) fn drag_twist(
+    sticker: usize,
+    offset: Vector3<f32>,
+    drag: Vector2<f32>,
+    to_screen: impl Fn(Vector4<f32>) -> Option<Vector2<f32>>,
+) -> Option<Move> {
+    let facet = &FACET_TABLE[sticker];
+    if !facet.is_actionable {
+        return None;
+    }
+    let drag = drag.try_normalize(f32::EPSILON)?;
+
+    // The twisted side is the facet's own cell, so its free axes are the
+    // facet's, in the same order as `basis`.
+    let at = |local: [f32; 3]| {
+        let mut point = Vector4::from(facet.position_4d);
+        for (i, &axis) in facet.free_axes.iter().enumerate() {
+            point[axis] = local[i];
+        }
+        point
+    };
+    let grabbed: [f32; 3] =
+        std::array::from_fn(|i| facet.position_4d[facet.free_axes[i]] + offset[i]);
+    let start = to_screen(at(grabbed))?;
+
+    let mut best = None;
+    let mut best_reach = 0.0;
+    for axis in 0..3 {
+        for turns in [1, -1] {
+            let mut grip = [0; 3];
+            grip[axis] = 1;
+            let mv = Move::new(facet.axis, facet.side_sign, grip, turns);
+            let nudged = rotate_local_position(mv.grip(), mv.angle() * NUDGE, grabbed);
+            let Some(end) = to_screen(at(nudged)) else {
+                continue;
+            };
+            let motion = end - start;
+            let reach = motion.dot(&drag);
+            if reach > motion.norm() * MIN_ALIGNMENT && reach > best_reach {
+                best = Some(mv);
+                best_reach = reach;
+            }
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::piece::{Hypercube, index_of};
+
+    /// Looks at the puzzle's x/y plane straight on, x right and y up.
+    fn flat(point: Vector4<f32>) -> Option<Vector2<f32>> {
+        Some(Vector2::new(point.x, -point.y) * 100.0)
+    }
+
+    /// Where the piece at `from` ends up after dragging its O-cell
+    /// (w = +1) sticker by `drag`, seen with `flat`.
+    fn dragged(from: [i8; 4], offset: Vector3<f32>, drag: [f32; 2]) -> Option<[i8; 4]> {
+        let slot = index_of(from);
+        let sticker = FACET_TABLE
+            .iter()
+            .position(|f| f.piece_slot == slot && f.axis == 3)
+            .unwrap();
+        let mv = drag_twist(sticker, offset, Vector2::from(drag), flat)?;
+        let mut cube = Hypercube::solved();
+        cube.apply_move(mv);
+        cube.pieces
+            .iter()
+            .find(|piece| piece.home() == from)
+            .map(|piece| piece.position)
+    }
+
+    #[test]
+    fn dragging_carries_the_sticker_along() {
+        let right = [1, 0, 0, 1];
+        assert_eq!(
+            dragged(right, Vector3::zeros(), [0.0, -1.0]),
+            Some([0, 1, 0, 1])
+        );
+        assert_eq!(
+            dragged(right, Vector3::zeros(), [0.0, 1.0]),
+            Some([0, -1, 0, 1])
+        );
+        // Slightly off the axis still picks the nearest twist.
+        assert_eq!(
+            dragged(right, Vector3::zeros(), [0.3, 1.0]),
+            Some([0, -1, 0, 1])
+        );
+        let corner = [1, 1, 1, 1];
+        assert_eq!(
+            dragged(corner, Vector3::zeros(), [-1.0, 0.0]),
+            Some([-1, 1, 1, 1])
+        );
+    }
+
+    #[test]
+    fn drags_no_twist_follows_make_no_twist() {
+        // Straight out from the cell's center, which no twist moves it along.
+        assert_eq!(dragged([1, 0, 0, 1], Vector3::zeros(), [1.0, 0.0]), None);
+        assert_eq!(dragged([1, 0, 0, 1], Vector3::zeros(), [0.0, 0.0]), None);
+    }
+}
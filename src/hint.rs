@@ -0,0 +1,170 @@
+//! Hint mode: a way back to solved from the current position, and how to
+//! make its next move by clicking.
+//!
+//! The position is the scramble's start state (solved if it has none), then
+//! the scramble and the history's applied moves. `solution_from` asks the
+//! solver first and falls back to undoing the moves, simplified.
+
+use std::fmt;
+
+use crate::history::MoveLog;
+use crate::moves::{Layers, Move, clockwise_sign, invert_moves, simplify_moves};
+use crate::piece::FACET_TABLE;
+use crate::solver;
+
+/// A way back to solved, and the position it's from.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Hint {
+    /// The scramble then the applied moves.
+    pub(crate) position: MoveLog,
+    pub(crate) path: Vec<Move>,
+}
+
+impl Hint {
+    pub(crate) fn for_position(position: MoveLog) -> Self {
+        let path = solution_from(&position);
+        Self { position, path }
+    }
+}
+
+/// Moves from the position `log` ends on back to solved.
+pub(crate) fn solution_from(log: &MoveLog) -> Vec<Move> {
+    solver::solve(&log.puzzle()).unwrap_or_else(|| {
+        let moves: Vec<Move> = log.position().collect();
+        simplify_moves(&invert_moves(&moves))
+    })
+}
+
+/// How to make one move with the mouse: which sticker to click, in which
+/// direction, with which layer modifiers, and how many times.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Click {
+    /// Index into `FACET_TABLE` (and the rendered sticker instances).
+    pub(crate) sticker: usize,
+    /// Whether it's the Shift-click, against `moves::clockwise_sign`.
+    pub(crate) counterclockwise: bool,
+    pub(crate) layers: Layers,
+    /// 2 for a face half turn, else 1.
+    pub(crate) times: u8,
+}
+
+impl Click {
+    /// The first sticker that makes `mv`, preferring a single plain click,
+    /// then a single Shift-click, then two clicks; `None` if no click does.
+    pub(crate) fn for_move(mv: Move) -> Option<Self> {
+        let actionable = || {
+            FACET_TABLE
+                .iter()
+                .enumerate()
+                .filter(|(_, f)| f.is_actionable)
+        };
+        [(1, false, 1), (-1, true, 1), (2, false, 2)]
+            .into_iter()
+            .find_map(|(multiple, counterclockwise, times)| {
+                actionable().find_map(|(sticker, facet)| {
+                    let turns = clockwise_sign(facet) as i8 * multiple;
+                    let clicked = Move::new(facet.axis, facet.side_sign, facet.local_coords, turns)
+                        .with_layers(mv.layers());
+                    (clicked == mv).then_some(Self {
+                        sticker,
+                        counterclockwise,
+                        layers: mv.layers(),
+                        times,
+                    })
+                })
+            })
+    }
+}
+
+impl fmt::Display for Click {
+    /// E.g. `Shift+Alt-click the highlighted sticker twice`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.counterclockwise {
+            write!(f, "Shift+")?;
+        }
+        match self.layers {
+            Layers::Outer => {}
+            Layers::Middle => write!(f, "Alt+")?,
+            Layers::Wide => write!(f, "Ctrl+")?,
+            Layers::All => write!(f, "Ctrl+Alt+")?,
+        }
+        let click = if self.counterclockwise || self.layers != Layers::Outer {
+            "click"
+        } else {
+            "Click"
+        };
+        write!(f, "{click} the highlighted sticker")?;
+        if self.times > 1 {
+            write!(f, " twice")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::moves::{all_moves, all_rotations, parse_moves};
+    use crate::piece::Hypercube;
+
+    #[test]
+    fn solution_from_returns_to_solved() {
+        for text in ["", "RU", "RU LB2 FUR BURO", "{2}RU {1-3}LB"] {
+            let position = MoveLog {
+                scramble: parse_moves(text).unwrap(),
+                ..MoveLog::default()
+            };
+            let mut cube = position.puzzle();
+            for mv in solution_from(&position) {
+                cube.apply_move(mv);
+            }
+            assert!(cube.is_solved(), "{text}");
+        }
+        let position = MoveLog {
+            start: Some(Hypercube::random_state(&mut fastrand::Rng::with_seed(6))),
+            scramble: parse_moves("RU").unwrap(),
+            moves: parse_moves("LB2").unwrap(),
+        };
+        let mut cube = position.puzzle();
+        for mv in solution_from(&position) {
+            cube.apply_move(mv);
+        }
+        assert!(cube.is_solved(), "from a start state");
+    }
+
+    #[test]
+    fn every_twist_and_rotation_has_a_click() {
+        for mv in all_moves().into_iter().chain(all_rotations()) {
+            let click = Click::for_move(mv).unwrap_or_else(|| panic!("no click for {mv}"));
+            let facet = &FACET_TABLE[click.sticker];
+            let sign = if click.counterclockwise { -1 } else { 1 };
+            let turns = clockwise_sign(facet) as i8 * sign * click.times as i8;
+            assert_eq!(
+                Move::new(facet.axis, facet.side_sign, facet.local_coords, turns)
+                    .with_layers(click.layers),
+                mv
+            );
+        }
+    }
+
+    #[test]
+    fn clicks_describe_their_modifiers() {
+        let outer = Click {
+            sticker: 0,
+            counterclockwise: false,
+            layers: Layers::Outer,
+            times: 2,
+        };
+        assert_eq!(outer.to_string(), "Click the highlighted sticker twice");
+        let whole = Click {
+            counterclockwise: true,
+            layers: Layers::All,
+            times: 1,
+            ..outer
+        };
+        assert_eq!(
+            whole.to_string(),
+            "Shift+Ctrl+Alt+click the highlighted sticker"
+        );
+    }
+}
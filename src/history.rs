@@ -0,0 +1,231 @@
+//! Undo/redo move history for the puzzle state owned by the shader widget.
+//!
+//! A linear history with a cursor: everything before `cursor` has been
+//! applied to the `Hypercube`, everything from `cursor` on has been undone
+//! and can be redone. Recording a fresh move discards the redo tail, the
+//! same way a text editor forgets undone edits once you type something new.
+
+use serde::{Deserialize, Serialize};
+
+use crate::moves::Move;
+use crate::piece::Hypercube;
+
+/// Linear undo/redo history of committed moves.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "UncheckedMoveHistory")]
+pub(crate) struct MoveHistory {
+    moves: Vec<Move>,
+    /// Number of entries in `moves` currently applied to the puzzle.
+    cursor: usize,
+}
+
+/// A scramble plus the moves made after it: everything needed to rebuild a
+/// puzzle state, with the scramble kept apart so a log can say where the
+/// solve itself began.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct MoveLog {
+    /// State the scramble starts from when it isn't solved, e.g. a sampled
+    /// random state. Kept as the state itself: the moves reaching it are
+    /// only worked out (by the solver, off the UI thread) for formats that
+    /// hold nothing but moves.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) start: Option<Hypercube>,
+    pub(crate) scramble: Vec<Move>,
+    pub(crate) moves: Vec<Move>,
+}
+
+impl MoveLog {
+    /// The scramble then the moves, as they're applied to `start`.
+    pub(crate) fn position(&self) -> impl Iterator<Item = Move> + '_ {
+        self.scramble.iter().chain(&self.moves).copied()
+    }
+
+    /// The puzzle the log ends on.
+    pub(crate) fn puzzle(&self) -> Hypercube {
+        rebuild(self.start.as_ref(), self.position())
+    }
+}
+
+/// A puzzle to rebuild: `start` (solved if `None`), `scramble`, then
+/// `history.applied()`, with the rest of `history` left to redo.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Replay {
+    pub(crate) start: Option<Hypercube>,
+    pub(crate) scramble: Vec<Move>,
+    pub(crate) history: MoveHistory,
+}
+
+impl Replay {
+    /// Where `log` ends: every move applied, all of them undoable.
+    pub(crate) fn applied(log: MoveLog) -> Self {
+        let cursor = log.moves.len();
+        Self {
+            start: log.start,
+            scramble: log.scramble,
+            history: MoveHistory {
+                moves: log.moves,
+                cursor,
+            },
+        }
+    }
+
+    /// Where `log` ends (its moves all undoable), with `moves` waiting to be
+    /// stepped through by redo.
+    pub(crate) fn continuing(log: MoveLog, moves: &[Move]) -> Self {
+        let cursor = log.moves.len();
+        Self {
+            start: log.start,
+            scramble: log.scramble,
+            history: MoveHistory {
+                moves: [log.moves, moves.to_vec()].concat(),
+                cursor,
+            },
+        }
+    }
+
+    /// The puzzle with the applied moves made.
+    pub(crate) fn puzzle(&self) -> Hypercube {
+        let moves = self.scramble.iter().chain(self.history.applied()).copied();
+        rebuild(self.start.as_ref(), moves)
+    }
+}
+
+/// `start` (solved if `None`) with `moves` applied.
+fn rebuild(start: Option<&Hypercube>, moves: impl Iterator<Item = Move>) -> Hypercube {
+    let mut hypercube = start.cloned().unwrap_or_else(Hypercube::solved);
+    for mv in moves {
+        hypercube.apply_move(mv);
+    }
+    hypercube
+}
+
+/// `MoveHistory` as read from a session file, before checking that its
+/// cursor actually lands inside its moves.
+#[derive(Deserialize)]
+struct UncheckedMoveHistory {
+    moves: Vec<Move>,
+    cursor: usize,
+}
+
+impl TryFrom<UncheckedMoveHistory> for MoveHistory {
+    type Error = String;
+
+    fn try_from(unchecked: UncheckedMoveHistory) -> Result<Self, Self::Error> {
+        let UncheckedMoveHistory { moves, cursor } = unchecked;
+        if cursor > moves.len() {
+            return Err(format!(
+                "history cursor {cursor} is past its {} moves",
+                moves.len()
+            ));
+        }
+        Ok(Self { moves, cursor })
+    }
+}
+
+impl MoveHistory {
+    /// Records a freshly committed move, discarding anything that had been
+    /// undone but not yet redone.
+    pub(crate) fn push(&mut self, mv: Move) {
+        self.moves.truncate(self.cursor);
+        self.moves.push(mv);
+        self.cursor += 1;
+    }
+
+    /// Steps the cursor back one move, returning the move that was undone
+    /// (the caller applies its `inverse`), or `None` at the start of history.
+    pub(crate) fn undo(&mut self) -> Option<Move> {
+        self.cursor = self.cursor.checked_sub(1)?;
+        Some(self.moves[self.cursor])
+    }
+
+    /// Steps the cursor forward one move, returning the move to re-apply, or
+    /// `None` if nothing has been undone.
+    pub(crate) fn redo(&mut self) -> Option<Move> {
+        let mv = *self.moves.get(self.cursor)?;
+        self.cursor += 1;
+        Some(mv)
+    }
+
+    /// The moves currently applied to the puzzle, oldest first - what the
+    /// puzzle has been through since the last reset or scramble.
+    pub(crate) fn applied(&self) -> &[Move] {
+        &self.moves[..self.cursor]
+    }
+
+    pub(crate) fn can_undo(&self) -> bool {
+        self.cursor > 0
+    }
+
+    pub(crate) fn can_redo(&self) -> bool {
+        self.cursor < self.moves.len()
+    }
+
+    /// Forgets every move, e.g. when the puzzle is reset or scrambled and
+    /// the recorded moves no longer lead anywhere meaningful.
+    pub(crate) fn clear(&mut self) {
+        self.moves.clear();
+        self.cursor = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A distinct quarter-turn multiple of the same face-type grip per `n`
+    /// in `1..=3`.
+    fn recorded(n: i8) -> Move {
+        Move::new(3, 1, [1, 0, 0], n)
+    }
+
+    #[test]
+    fn empty_history_has_nothing_to_undo_or_redo() {
+        let mut history = MoveHistory::default();
+        assert!(!history.can_undo());
+        assert!(!history.can_redo());
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.redo(), None);
+    }
+
+    #[test]
+    fn undo_then_redo_walks_back_and_forth_in_order() {
+        let mut history = MoveHistory::default();
+        history.push(recorded(1));
+        history.push(recorded(2));
+
+        assert_eq!(history.undo(), Some(recorded(2)));
+        assert_eq!(history.undo(), Some(recorded(1)));
+        assert_eq!(history.undo(), None);
+        assert!(history.can_redo());
+
+        assert_eq!(history.redo(), Some(recorded(1)));
+        assert_eq!(history.redo(), Some(recorded(2)));
+        assert_eq!(history.redo(), None);
+        assert!(history.can_undo());
+    }
+
+    #[test]
+    fn push_after_undo_discards_redo_tail() {
+        let mut history = MoveHistory::default();
+        history.push(recorded(1));
+        history.push(recorded(2));
+        history.undo();
+        history.push(recorded(3));
+
+        assert!(!history.can_redo());
+        assert_eq!(history.applied(), &[recorded(1), recorded(3)]);
+        assert_eq!(history.undo(), Some(recorded(3)));
+        assert_eq!(history.undo(), Some(recorded(1)));
+    }
+
+    #[test]
+    fn clear_forgets_everything() {
+        let mut history = MoveHistory::default();
+        history.push(recorded(1));
+        history.push(recorded(2));
+        history.undo();
+        history.clear();
+        assert!(!history.can_undo());
+        assert!(!history.can_redo());
+    }
+}
@@ -0,0 +1,148 @@
+//! Twisting from the keyboard, Hyperspeedcube-style: hold a cell's grip
+//! key, then press the twist keys of the neighboring cells naming the grip
+//! (one for a face, two for an edge, three for a corner, as in notation:
+//! gripping `R` and pressing `U` and `F` together is `RUF`). The twist is
+//! made when the last twist key is released, turning the way a plain click
+//! on that grip's sticker does; Shift reverses it and Alt/Ctrl pick layers,
+//! as for clicks.
+
+use crate::moves::{CELL_LETTERS, Layers, Move, parse_move};
+use crate::settings::KeyBindings;
+
+/// The keys currently held towards a keyboard twist.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct TwistKeys {
+    /// Face id of the gripped cell, while its grip key is held.
+    gripped: Option<usize>,
+    /// Face ids of the twist keys held right now.
+    held: Vec<usize>,
+    /// Face ids of every twist key pressed since none were held, in order.
+    chord: Vec<usize>,
+}
+
+impl TwistKeys {
+    /// Handles a key press; returns whether `key` is bound to anything.
+    pub(crate) fn press(&mut self, bindings: &KeyBindings, key: &str) -> bool {
+        if let Some(cell) = bound(&bindings.grips, key) {
+            self.gripped = Some(cell);
+            return true;
+        }
+        let Some(cell) = bound(&bindings.twists, key) else {
+            return false;
+        };
+        if self.gripped.is_some() && !self.held.contains(&cell) {
+            self.held.push(cell);
+            if !self.chord.contains(&cell) {
+                self.chord.push(cell);
+            }
+        }
+        true
+    }
+
+    /// Handles a key release, returning the twist it completes, if any:
+    /// the gripped cell's twist about the chord's grip, on `layers`,
+    /// reversed if `reversed`. A chord that doesn't name a grip of the
+    /// gripped cell (e.g. its own axis) makes no twist.
+    pub(crate) fn release(
+        &mut self,
+        bindings: &KeyBindings,
+        key: &str,
+        layers: Layers,
+        reversed: bool,
+    ) -> Option<Move> {
+        if let Some(cell) = bound(&bindings.grips, key) {
+            if self.gripped == Some(cell) {
+                self.gripped = None;
+            }
+            return None;
+        }
+        let cell = bound(&bindings.twists, key)?;
+        self.held.retain(|&held| held != cell);
+        if !self.held.is_empty() || self.chord.is_empty() {
+            return None;
+        }
+        let grip: String = self
+            .chord
+            .drain(..)
+            .map(|cell| CELL_LETTERS[cell])
+            .collect();
+        let token = format!("{}{grip}", CELL_LETTERS[self.gripped?]);
+        let mv = parse_move(&token, 0).ok()?.with_layers(layers);
+        Some(if reversed { mv.inverse() } else { mv })
+    }
+}
+
+/// The cell `key` is bound to in `keys`, if any.
+fn bound(keys: &[String; 8], key: &str) -> Option<usize> {
+    keys.iter()
+        .position(|bound| bound.eq_ignore_ascii_case(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::moves::parse_moves;
+
+    /// Presses then releases `keys` (grip key first), returning the twist.
+    fn twist(keys: &[&str], layers: Layers, reversed: bool) -> Option<Move> {
+        let bindings = KeyBindings::default();
+        let mut twist_keys = TwistKeys::default();
+        for key in keys {
+            assert!(twist_keys.press(&bindings, key), "{key} is bound");
+        }
+        let mut twist = None;
+        for key in keys[1..].iter() {
+            twist = twist.or(twist_keys.release(&bindings, key, layers, reversed));
+        }
+        twist
+    }
+
+    #[test]
+    fn grip_and_twist_keys_name_a_move() {
+        // `d` grips R; `i`, `u` and `;` are U, F and O.
+        let expected = |text: &str| Some(parse_moves(text).unwrap()[0]);
+        assert_eq!(twist(&["d", "i"], Layers::Outer, false), expected("RU"));
+        assert_eq!(twist(&["d", "i"], Layers::Outer, true), expected("RU'"));
+        assert_eq!(
+            twist(&["d", "i", "u"], Layers::Outer, false),
+            expected("RUF")
+        );
+        assert_eq!(
+            twist(&["d", "i", "u", ";"], Layers::Outer, false),
+            expected("RUFO")
+        );
+        assert_eq!(twist(&["d", "i"], Layers::Middle, false), expected("{2}RU"));
+        assert_eq!(twist(&["D", "I"], Layers::All, false), expected("{1-3}RU"));
+    }
+
+    #[test]
+    fn twists_need_a_grip_and_a_valid_chord() {
+        let bindings = KeyBindings::default();
+        let mut twist_keys = TwistKeys::default();
+        // No cell gripped.
+        assert!(twist_keys.press(&bindings, "i"));
+        assert_eq!(
+            twist_keys.release(&bindings, "i", Layers::Outer, false),
+            None
+        );
+        // R's own axis isn't a grip of R.
+        assert_eq!(twist(&["d", "l"], Layers::Outer, false), None);
+        assert_eq!(twist(&["d", "j"], Layers::Outer, false), None);
+        // Unbound keys are left alone.
+        assert!(!twist_keys.press(&bindings, "x"));
+
+        // The twist waits for the whole chord to be released.
+        assert!(twist_keys.press(&bindings, "d"));
+        assert!(twist_keys.press(&bindings, "i"));
+        assert!(twist_keys.press(&bindings, "u"));
+        assert_eq!(
+            twist_keys.release(&bindings, "i", Layers::Outer, false),
+            None
+        );
+        assert!(
+            twist_keys
+                .release(&bindings, "u", Layers::Outer, false)
+                .is_some()
+        );
+    }
+}
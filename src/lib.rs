@@ -5,16 +5,32 @@
 
 use iced::Settings;
 
+mod algorithm;
 mod app;
 mod camera;
+mod cycles;
+mod drag_twist;
 mod geometry;
+mod hint;
+mod history;
+mod keyboard_twist;
 mod math;
+mod mc4d;
+mod metrics;
 mod moves;
+mod painting;
 pub mod piece;
+mod playback;
 mod ray_casting;
 mod renderer;
+mod search;
+mod session;
 mod settings;
 pub mod shader_widget;
+mod solve_viewer;
+mod solver;
+mod stats;
+mod timer;
 
 /// Entry point for the hypercube visualization application
 pub fn run() -> iced::Result {
@@ -26,6 +42,7 @@ pub fn run() -> iced::Result {
         app::HypercubeApp::view,
     )
     .title(app::HypercubeApp::title)
+    .subscription(app::HypercubeApp::subscription)
     .settings(Settings {
         antialiasing: true,
         ..Settings::default()
@@ -0,0 +1,528 @@
+//! Magic Cube 4D `.log` file import and export.
+//!
+//! MC4D's default puzzle is this same 3^4, and its logs are how that
+//! community stores solves. A log looks like:
+//!
+//! ```text
+//! MagicCube4D 3 <scramble state> <twist count> {4,3,3} 3
+//! <view matrix: 4 lines of 4 numbers>
+//! *
+//! <scramble twists> m| <solve twists>.
+//! ```
+//!
+//! with the twists wrapped ten to a line, as MC4D writes them. Each twist is
+//! `grip,direction,slicemask`; other `m` tokens are MC4D's own history
+//! marks, which don't affect the puzzle and are skipped. A grip is one of the 216
+//! stickers, numbered `mc4d_face * 27 + sticker`, and twists that cell the
+//! way a click on that sticker would; direction is `1` or `-1`; slicemask
+//! bit 0 is the outer layer, bit 1 the middle slice and bit 2 the far
+//! layer, which this puzzle's moves only turn together with the other two,
+//! as a whole-puzzle rotation (MC4D's own reorientations are logged the
+//! same way).
+//!
+//! A cell's center sticker has no twist axis - it sits on every one of the
+//! cell's - so a twist on it can't turn the cell. MC4D logs it when a cell
+//! is brought to the center of the view, so it's read as the whole-puzzle
+//! rotation carrying that cell onto the In cell (`moves::reorientation`),
+//! whatever its direction and slicemask, and as nothing for In's own
+//! center. Export never writes one: rotations go out as all-layer twists
+//! of an ordinary grip.
+//!
+//! The grip numbering (`MC4D_FACE_ORDER`, `sticker_in_face`) and which
+//! direction value is a plain click (`MC4D_CLOCKWISE`) have not been
+//! checked against a log exported by MC4D itself: the tests only cover
+//! logs this converter lays out. Those three are the only things to change
+//! if real logs replay mirrored or on the wrong cells.
+
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::history::MoveLog;
+use crate::moves::{Layers, Move, cell_for_letter, clockwise_sign, reorientation};
+use crate::piece::FACET_TABLE;
+
+const MAGIC: &str = "MagicCube4D";
+const FORMAT_VERSION: u32 = 3;
+/// The puzzle's Schläfli symbol, as MC4D names it in the header.
+const SCHLAFLI: &str = "{4,3,3}";
+const EDGE_LENGTH: u32 = 3;
+/// Separates the scramble twists from the solve twists.
+const SCRAMBLE_MARKER: &str = "m|";
+/// Twists per line of the twist section.
+const TWISTS_PER_LINE: usize = 10;
+
+/// `face_id_for` value of each MC4D face, in MC4D face order: R L U D F B O
+/// I, i.e. +/- along x, y, z, w of MC4D's frame.
+const MC4D_FACE_ORDER: [usize; 8] = [6, 1, 5, 2, 3, 4, 7, 0];
+
+/// The MC4D direction value that turns a grip the way a plain click on its
+/// sticker does here (`clockwise_sign`).
+const MC4D_CLOCKWISE: i8 = -1;
+
+/// MC4D's header scramble states this converter writes.
+const SCRAMBLE_STATE_NONE: u32 = 0;
+const SCRAMBLE_STATE_FULL: u32 = 2;
+
+/// Where the Import/Export path box starts out: `solve.log` in the
+/// `ProjectDirs` data dir, next to saved sessions.
+pub(crate) fn default_path() -> Option<PathBuf> {
+    directories::ProjectDirs::from("", "", "hypercube")
+        .map(|dirs| dirs.data_dir().join("solve.log"))
+}
+
+/// Why a log couldn't be imported.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Mc4dLogError {
+    /// The first line isn't a `MagicCube4D` header for a `{4,3,3}` 3^4
+    /// puzzle.
+    BadHeader,
+    /// The view matrix isn't 16 numbers.
+    BadViewMatrix,
+    /// No `*` line before the twists.
+    MissingTwistSection,
+    /// No `.` ending the twists.
+    MissingTerminator,
+    /// A twist token that isn't `grip,direction,slicemask`.
+    BadTwist(String),
+    /// A grip outside `0..216`.
+    UnsupportedGrip(u32),
+    /// A twist of anything but the outer layer, the middle slice, both, or
+    /// all three layers.
+    UnsupportedSlices(u32),
+}
+
+impl fmt::Display for Mc4dLogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mc4dLogError::BadHeader => write!(f, "not a MagicCube4D 3^4 log"),
+            Mc4dLogError::BadViewMatrix => write!(f, "view matrix isn't 16 numbers"),
+            Mc4dLogError::MissingTwistSection => write!(f, "missing '*' before the twists"),
+            Mc4dLogError::MissingTerminator => write!(f, "missing '.' after the twists"),
+            Mc4dLogError::BadTwist(token) => write!(f, "malformed twist {token:?}"),
+            Mc4dLogError::UnsupportedGrip(grip) => write!(f, "grip {grip} isn't a sticker"),
+            Mc4dLogError::UnsupportedSlices(mask) => {
+                write!(f, "slice mask {mask} isn't supported (1, 2, 3 or 7 only)")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Mc4dLogError {}
+
+/// Index of a sticker within its face: its local coordinates read as a
+/// base-3 number, first free axis most significant.
+fn sticker_in_face(local_coords: [i8; 3]) -> u32 {
+    local_coords
+        .iter()
+        .fold(0, |acc, &c| acc * 3 + (c + 1) as u32)
+}
+
+/// MC4D grip number of a `FACET_TABLE` entry.
+fn grip_of(facet_index: usize) -> u32 {
+    let facet = &FACET_TABLE[facet_index];
+    let mc4d_face = MC4D_FACE_ORDER
+        .iter()
+        .position(|&face_id| face_id == facet.face_id)
+        .expect("MC4D_FACE_ORDER covers every face") as u32;
+    mc4d_face * 27 + sticker_in_face(facet.local_coords)
+}
+
+/// `FACET_TABLE` entry for an MC4D grip number, if it is one.
+fn facet_of(grip: u32) -> Option<usize> {
+    let face_id = *MC4D_FACE_ORDER.get((grip / 27) as usize)?;
+    let sticker = grip % 27;
+    FACET_TABLE
+        .iter()
+        .position(|f| f.face_id == face_id && sticker_in_face(f.local_coords) == sticker)
+}
+
+/// MC4D slicemask for `layers`.
+fn slicemask_of(layers: Layers) -> u32 {
+    match layers {
+        Layers::Outer => 1,
+        Layers::Middle => 2,
+        Layers::Wide => 3,
+        Layers::All => 7,
+    }
+}
+
+/// Converts one MC4D twist to a `Move`: `None` for a twist on the In
+/// cell's center, which is already where a center twist brings its cell.
+fn move_from_twist(grip: u32, direction: i8, slicemask: u32) -> Result<Option<Move>, Mc4dLogError> {
+    let layers = [Layers::Outer, Layers::Middle, Layers::Wide, Layers::All]
+        .into_iter()
+        .find(|&layers| slicemask_of(layers) == slicemask)
+        .ok_or(Mc4dLogError::UnsupportedSlices(slicemask))?;
+    let facet = &FACET_TABLE[facet_of(grip).ok_or(Mc4dLogError::UnsupportedGrip(grip))?];
+    if !facet.is_actionable {
+        let center = cell_for_letter('I').expect("I names a cell");
+        return Ok(reorientation((facet.axis, facet.side_sign), center));
+    }
+    let clicks = if direction == MC4D_CLOCKWISE { 1 } else { -1 };
+    Ok(Some(
+        Move::new(
+            facet.axis,
+            facet.side_sign,
+            facet.local_coords,
+            clicks * clockwise_sign(facet) as i8,
+        )
+        .with_layers(layers),
+    ))
+}
+
+/// Converts a `Move` to MC4D twists (grip, direction, slicemask): one per
+/// click on its grip sticker, so a half turn of a face-type grip becomes
+/// two twists.
+fn twists_for_move(mv: Move) -> Vec<(u32, i8, u32)> {
+    let facet_index = FACET_TABLE
+        .iter()
+        .position(|f| {
+            f.axis == mv.side_axis() && f.side_sign == mv.side_sign() && f.local_coords == mv.grip()
+        })
+        .expect("every move's grip has a facet on its side");
+    let clockwise = clockwise_sign(&FACET_TABLE[facet_index]) as i8;
+    let clicks = mv.turns() * clockwise;
+    let direction = if clicks > 0 {
+        MC4D_CLOCKWISE
+    } else {
+        -MC4D_CLOCKWISE
+    };
+    vec![
+        (grip_of(facet_index), direction, slicemask_of(mv.layers()));
+        clicks.unsigned_abs() as usize
+    ]
+}
+
+fn parse_twist(token: &str) -> Result<Option<Move>, Mc4dLogError> {
+    let bad = || Mc4dLogError::BadTwist(token.to_string());
+    let mut fields = token.split(',');
+    let (Some(grip), Some(direction), Some(slicemask), None) =
+        (fields.next(), fields.next(), fields.next(), fields.next())
+    else {
+        return Err(bad());
+    };
+    let grip = grip.parse().map_err(|_| bad())?;
+    let direction = match direction {
+        "1" => 1,
+        "-1" => -1,
+        _ => return Err(bad()),
+    };
+    let slicemask = slicemask.parse().map_err(|_| bad())?;
+    move_from_twist(grip, direction, slicemask)
+}
+
+/// Reads an MC4D log into the scramble and solve moves it records. The view
+/// matrix is checked for shape but otherwise ignored.
+pub(crate) fn parse_log(text: &str) -> Result<MoveLog, Mc4dLogError> {
+    let mut lines = text.lines();
+
+    let header: Vec<&str> = lines
+        .next()
+        .ok_or(Mc4dLogError::BadHeader)?
+        .split_whitespace()
+        .collect();
+    let [
+        magic,
+        version,
+        _scramble_state,
+        _twist_count,
+        SCHLAFLI,
+        edge_length,
+    ] = header[..]
+    else {
+        return Err(Mc4dLogError::BadHeader);
+    };
+    // MC4D writes the edge length as a decimal for some puzzles, e.g. `3.0`.
+    if magic != MAGIC
+        || version.parse() != Ok(FORMAT_VERSION)
+        || edge_length.parse() != Ok(EDGE_LENGTH as f32)
+    {
+        return Err(Mc4dLogError::BadHeader);
+    }
+
+    let mut matrix_values = 0;
+    let mut found_twists = false;
+    for line in lines.by_ref() {
+        if line.trim() == "*" {
+            found_twists = true;
+            break;
+        }
+        for value in line.split_whitespace() {
+            value
+                .parse::<f32>()
+                .map_err(|_| Mc4dLogError::BadViewMatrix)?;
+            matrix_values += 1;
+        }
+    }
+    if !found_twists {
+        return Err(Mc4dLogError::MissingTwistSection);
+    }
+    if matrix_values != 16 {
+        return Err(Mc4dLogError::BadViewMatrix);
+    }
+
+    let body: String = lines.collect::<Vec<_>>().join(" ");
+    let body = body
+        .trim_end()
+        .strip_suffix('.')
+        .ok_or(Mc4dLogError::MissingTerminator)?;
+    let (scramble, solve) = match body.split_once(SCRAMBLE_MARKER) {
+        Some((scramble, solve)) => (scramble, solve),
+        None => ("", body),
+    };
+    let parse_twists = |section: &str| {
+        section
+            .split_whitespace()
+            .filter(|token| !token.starts_with('m'))
+            .filter_map(|token| parse_twist(token).transpose())
+            .collect::<Result<Vec<_>, _>>()
+    };
+    Ok(MoveLog {
+        start: None,
+        scramble: parse_twists(scramble)?,
+        moves: parse_twists(solve)?,
+    })
+}
+
+/// Writes `log` as an MC4D log, with an identity view matrix. MC4D logs
+/// replay from solved, so a `log.start` has to be turned into scramble
+/// moves first.
+pub(crate) fn format_log(log: &MoveLog) -> String {
+    debug_assert!(log.start.is_none(), "MC4D logs start from solved");
+    let format_twists = |moves: &[Move]| -> Vec<String> {
+        moves
+            .iter()
+            .flat_map(|&mv| twists_for_move(mv))
+            .map(|(grip, direction, slicemask)| format!("{grip},{direction},{slicemask}"))
+            .collect()
+    };
+    let scramble = format_twists(&log.scramble);
+    let solve = format_twists(&log.moves);
+    let scramble_state = if scramble.is_empty() {
+        SCRAMBLE_STATE_NONE
+    } else {
+        SCRAMBLE_STATE_FULL
+    };
+
+    let mut text = format!(
+        "{MAGIC} {FORMAT_VERSION} {scramble_state} {} {SCHLAFLI} {EDGE_LENGTH}\n",
+        solve.len()
+    );
+    for row in 0..4 {
+        let values: Vec<&str> = (0..4)
+            .map(|col| if row == col { "1.0" } else { "0.0" })
+            .collect();
+        text.push_str(&values.join(" "));
+        text.push('\n');
+    }
+    text.push_str("*\n");
+    let mut twists = scramble;
+    if !twists.is_empty() {
+        twists.push(SCRAMBLE_MARKER.to_string());
+    }
+    twists.extend(solve);
+    let lines: Vec<String> = twists
+        .chunks(TWISTS_PER_LINE)
+        .map(|line| line.join(" "))
+        .collect();
+    text.push_str(&lines.join("\n"));
+    text.push_str(".\n");
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::moves::parse_moves;
+    use crate::piece::Hypercube;
+
+    #[test]
+    fn grip_numbering_is_a_bijection_onto_facet_table() {
+        let mut grips: Vec<u32> = (0..FACET_TABLE.len()).map(grip_of).collect();
+        for (index, &grip) in grips.iter().enumerate() {
+            assert_eq!(facet_of(grip), Some(index));
+        }
+        grips.sort_unstable();
+        assert_eq!(grips, (0..216).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn every_twist_round_trips_through_a_move() {
+        for grip in 0..216 {
+            for (direction, slicemask) in [1, -1]
+                .into_iter()
+                .flat_map(|d| [1, 2, 3, 7].map(|mask| (d, mask)))
+            {
+                let mv = move_from_twist(grip, direction, slicemask).unwrap();
+                let facet = &FACET_TABLE[facet_of(grip).unwrap()];
+                if !facet.is_actionable {
+                    // A center twist only reorients, so it carries its cell
+                    // to In whatever the direction and slices.
+                    let center = cell_for_letter('I').unwrap();
+                    assert_eq!(mv, reorientation((facet.axis, facet.side_sign), center));
+                    assert_eq!(mv.is_none(), (facet.axis, facet.side_sign) == center);
+                }
+                if let Some(mv) = mv {
+                    let twists = twists_for_move(mv);
+                    let mut expected = Hypercube::solved();
+                    expected.apply_move(mv);
+                    let mut replayed = Hypercube::solved();
+                    for (twist_grip, twist_direction, twist_slicemask) in twists {
+                        let twist = move_from_twist(twist_grip, twist_direction, twist_slicemask)
+                            .unwrap()
+                            .unwrap();
+                        replayed.apply_move(twist);
+                    }
+                    assert_eq!(
+                        replayed, expected,
+                        "grip {grip} direction {direction} slicemask {slicemask}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn format_then_parse_round_trips_a_log() {
+        let log = MoveLog {
+            start: None,
+            scramble: Hypercube::solved().apply_random_moves(25, &mut fastrand::Rng::with_seed(9)),
+            moves: parse_moves("RU OBR' IUFR2 LD RD2 {2}FU {1-2}IRD'").unwrap(),
+        };
+        let text = format_log(&log);
+        assert!(text.starts_with("MagicCube4D 3 2 8 {4,3,3} 3\n"));
+        assert!(
+            text.lines()
+                .skip(6)
+                .all(|line| line.split_whitespace().count() <= TWISTS_PER_LINE)
+        );
+        let parsed = parse_log(&text).unwrap();
+        assert_eq!(parsed.scramble, log.scramble);
+        // The face-type half turn `RD2` comes back as two single clicks.
+        let mut expected = Hypercube::solved();
+        let mut replayed = Hypercube::solved();
+        for &mv in &log.moves {
+            expected.apply_move(mv);
+        }
+        for &mv in &parsed.moves {
+            replayed.apply_move(mv);
+        }
+        assert_eq!(replayed, expected);
+        assert_eq!(parsed.moves.len(), 8);
+    }
+
+    #[test]
+    fn parse_log_without_scramble_reads_every_twist_as_solve() {
+        let log = parse_log(
+            "MagicCube4D 3 0 2 {4,3,3} 3\n\
+             1 0 0 0\n0 1 0 0\n0 0 1 0\n0 0 0 1\n\
+             *\n\
+             1,1,1\n1,-1,1.\n",
+        )
+        .unwrap();
+        assert!(log.scramble.is_empty());
+        assert_eq!(log.moves.len(), 2);
+        assert_eq!(log.moves[0], log.moves[1].inverse());
+    }
+
+    #[test]
+    fn parse_log_reads_center_twists_as_reorientations() {
+        let center = cell_for_letter('I').unwrap();
+        let center_grip = |cell: (usize, i8)| {
+            (0..FACET_TABLE.len())
+                .find(|&index| {
+                    let facet = &FACET_TABLE[index];
+                    !facet.is_actionable && (facet.axis, facet.side_sign) == cell
+                })
+                .map(grip_of)
+                .unwrap()
+        };
+        let other = cell_for_letter('R').unwrap();
+        let log = parse_log(&format!(
+            "MagicCube4D 3 0 2 {{4,3,3}} 3\n\
+             1 0 0 0\n0 1 0 0\n0 0 1 0\n0 0 0 1\n\
+             *\n\
+             {},-1,7 {},1,1.\n",
+            center_grip(other),
+            center_grip(center),
+        ))
+        .unwrap();
+        assert_eq!(log.moves, vec![reorientation(other, center).unwrap()]);
+    }
+
+    /// A log laid out as MC4D writes one - Schläfli symbol in the header, a
+    /// rotated view, twists wrapped ten to a line with MC4D's own marks
+    /// among them - holding a scramble and a solution that undoes it. It's
+    /// written with this converter's grip numbering, not exported by MC4D.
+    #[test]
+    fn scramble_then_solution_from_an_mc4d_layout_log_is_solved() {
+        let log = parse_log(
+            "MagicCube4D 3 2 12 {4,3,3} 3\n\
+             0.8660254 -0.5 0.0 0.0\n\
+             0.5 0.8660254 0.0 0.0\n\
+             0.0 0.0 1.0 0.0\n\
+             0.0 0.0 0.0 1.0\n\
+             *\n\
+             1,1,1 32,-1,1 60,1,1 89,1,1 115,-1,1 140,1,1 170,-1,1 199,1,1 5,1,3 34,-1,2\n\
+             m| 34,1,2 5,-1,3 199,-1,1 170,1,1 140,-1,1 m< 115,1,1 89,-1,1 60,-1,1 32,1,1\n\
+             m> 1,-1,1 7,1,7 7,-1,7.\n",
+        )
+        .unwrap();
+        assert_eq!(log.scramble.len(), 10);
+        assert_eq!(log.moves.len(), 12);
+        let mut cube = Hypercube::solved();
+        for &mv in &log.scramble {
+            cube.apply_move(mv);
+        }
+        assert!(!cube.is_solved());
+        for &mv in &log.moves {
+            cube.apply_move(mv);
+        }
+        assert!(cube.is_solved());
+    }
+
+    #[test]
+    fn parse_log_reports_what_is_wrong() {
+        let with_twists = |twists: &str| {
+            format!(
+                "MagicCube4D 3 0 0 {{4,3,3}} 3\n1 0 0 0\n0 1 0 0\n0 0 1 0\n0 0 0 1\n*\n{twists}"
+            )
+        };
+        assert_eq!(
+            parse_log("MagicCube4D 3 0 0 {4,3,3} 4\n"),
+            Err(Mc4dLogError::BadHeader)
+        );
+        assert_eq!(
+            parse_log("MagicCube4D 3 0 0 3\n"),
+            Err(Mc4dLogError::BadHeader)
+        );
+        assert_eq!(
+            parse_log("MagicCube4D 3 0 0 {4,3,3} 3\n1 0 0\n*\n."),
+            Err(Mc4dLogError::BadViewMatrix)
+        );
+        assert_eq!(
+            parse_log("MagicCube4D 3 0 0 {4,3,3} 3\n1 0 0 0\n0 1 0 0\n0 0 1 0\n0 0 0 1\n1,1,1."),
+            Err(Mc4dLogError::BadViewMatrix)
+        );
+        assert_eq!(
+            parse_log("MagicCube4D 3 0 0 {4,3,3} 3\n1 0 0 0\n0 1 0 0\n0 0 1 0\n0 0 0 1\n"),
+            Err(Mc4dLogError::MissingTwistSection)
+        );
+        assert_eq!(
+            parse_log(&with_twists("1,1,1")),
+            Err(Mc4dLogError::MissingTerminator)
+        );
+        assert_eq!(
+            parse_log(&with_twists("1,1.")),
+            Err(Mc4dLogError::BadTwist("1,1".to_string()))
+        );
+        assert_eq!(
+            parse_log(&with_twists("216,1,1.")),
+            Err(Mc4dLogError::UnsupportedGrip(216))
+        );
+        assert_eq!(
+            parse_log(&with_twists("1,1,4.")),
+            Err(Mc4dLogError::UnsupportedSlices(4))
+        );
+    }
+}
@@ -0,0 +1,90 @@
+//! Move-count metrics for a solve, as the 4D twisty-puzzle community
+//! counts them.
+//!
+//! - ETM (execution turn metric): every move as it was executed, whole-puzzle
+//!   rotations included, with no merging.
+//! - STM (slice turn metric): twists of any layers count one each once the
+//!   sequence is simplified; rotations are free.
+//! - QTM (quarter-twist metric): like STM, but a twist counts once per
+//!   `base_angle` turn in it, so a face half turn is two while edge (180°)
+//!   and corner (120°) twists, already minimal, are one.
+
+use serde::{Deserialize, Serialize};
+
+use crate::moves::{Move, simplify_moves};
+
+/// The three counts of one move sequence.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Metrics {
+    pub(crate) etm: usize,
+    pub(crate) stm: usize,
+    pub(crate) qtm: usize,
+}
+
+impl Metrics {
+    pub(crate) fn of(moves: &[Move]) -> Self {
+        let twists: Vec<Move> = simplify_moves(moves)
+            .into_iter()
+            .filter(|mv| !mv.is_rotation())
+            .collect();
+        Self {
+            etm: moves.len(),
+            stm: twists.len(),
+            qtm: twists
+                .iter()
+                .map(|mv| usize::from(mv.turns().unsigned_abs()))
+                .sum(),
+        }
+    }
+}
+
+impl std::fmt::Display for Metrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ETM, {} STM, {} QTM", self.etm, self.stm, self.qtm)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::moves::parse_moves;
+
+    fn metrics(text: &str) -> Metrics {
+        Metrics::of(&parse_moves(text).unwrap())
+    }
+
+    #[test]
+    fn metrics_count_turn_types_and_rotations() {
+        // Face quarter and half turn, edge twist, corner twist.
+        assert_eq!(
+            metrics("RU LB2 FUR BURO"),
+            Metrics {
+                etm: 4,
+                stm: 4,
+                qtm: 5
+            }
+        );
+        // Slices and wide moves are one twist; rotations only count in ETM.
+        assert_eq!(
+            metrics("{2}RU {1-2}LB {1-3}RU"),
+            Metrics {
+                etm: 3,
+                stm: 2,
+                qtm: 2
+            }
+        );
+    }
+
+    #[test]
+    fn only_etm_counts_moves_that_merge_or_cancel() {
+        assert_eq!(
+            metrics("RU RU LB LB'"),
+            Metrics {
+                etm: 4,
+                stm: 1,
+                qtm: 2
+            }
+        );
+        assert_eq!(metrics(""), Metrics::default());
+    }
+}
@@ -5,27 +5,240 @@
 //! clicked piece's own position restricted to the 3 "free" axes
 //! (`local_coords`); the number of nonzero local coords determines whether
 //! it's a 90 degree face-type, 180 degree edge-type, or 120 degree
-//! corner-type turn.
+//! corner-type turn. The middle slice parallel to a side (the 27 pieces at
+//! 0 on its axis) can be turned the same way, alone or together with the
+//! side (`Layers`), and turning all three layers at once reorients the
+//! whole puzzle. `Move` is the value type for one such twist, with a text
+//! notation (`parse_moves`/`format_moves`) for sharing sequences.
 
 use std::f32::consts::{FRAC_PI_2, PI, TAU};
+use std::fmt;
+use std::ops::Range;
 
 use nalgebra::{Matrix4, Rotation3, Unit, Vector3, Vector4};
+use serde::{Deserialize, Serialize};
 
 use crate::math::{VIEWER_DISTANCE, project_4d_to_3d};
-use crate::piece::{FACET_TABLE, FacetGeometry, Hypercube, Piece, free_axes, index_of};
+use crate::piece::{
+    FACE_AXIS_SIGN, FACET_TABLE, FacetGeometry, Hypercube, Piece, face_id_for, free_axes, index_of,
+};
 
-/// Rounds a continuous 3D rotation matrix (about `local_coords`, by `angle`)
+/// Which layers parallel to a side a move turns, counting in from the side.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub(crate) enum Layers {
+    /// The side itself: the 27 pieces at `side_sign` on its axis.
+    #[default]
+    Outer,
+    /// The middle slice: the 27 pieces at 0 on the side's axis.
+    Middle,
+    /// The side and the middle slice together, as one 3x3x3x2 block.
+    Wide,
+    /// Every layer: the whole puzzle, reoriented as one rigid body.
+    All,
+}
+
+impl Layers {
+    /// Whether a piece at `coordinate` on the side's axis is in these
+    /// layers of the side at `side_sign`.
+    fn contain(self, side_sign: i8, coordinate: i8) -> bool {
+        match self {
+            Layers::Outer => coordinate == side_sign,
+            Layers::Middle => coordinate == 0,
+            Layers::Wide => coordinate == side_sign || coordinate == 0,
+            Layers::All => true,
+        }
+    }
+}
+
+/// One twist of one side: the side (`side_axis`/`side_sign`, the 27 pieces
+/// sharing that coordinate) and which layers of it turn, the grip (a local
+/// position on the side's 3 free axes, which doubles as the rotation axis),
+/// and a whole number of `base_angle` turns about it.
+///
+/// Always stored in a canonical form, so two `Move`s compare (and hash)
+/// equal exactly when they have the same effect on the puzzle: the grip's
+/// first nonzero coordinate is positive (turning about `-grip` is turning
+/// about `grip` the other way), `turns` is reduced modulo `order()` into
+/// `(-order/2, order/2]` - e.g. a face-type grip's three quarter turns is
+/// one quarter turn back, `-1` - and a middle slice move names the positive
+/// side (both sides share the slice and the free axes it turns about). A
+/// whole-puzzle rotation does too, and a face-type one names the lower of
+/// the two axes it fixes as its side: turning the plane of the other two
+/// reads as a face-type grip from either.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub(crate) struct Move {
+    side_axis: usize,
+    side_sign: i8,
+    layers: Layers,
+    grip: [i8; 3],
+    turns: i8,
+}
+
+impl Move {
+    /// Builds a canonical outer-layer move. `grip` must have 1 to 3 nonzero
+    /// coordinates, each in `{-1, 0, 1}`; `turns` may be any integer.
+    pub(crate) fn new(side_axis: usize, side_sign: i8, grip: [i8; 3], turns: i8) -> Self {
+        Self::canonical(side_axis, side_sign, Layers::Outer, grip, turns)
+    }
+
+    /// The same twist, of `layers` instead.
+    pub(crate) fn with_layers(self, layers: Layers) -> Self {
+        Self::canonical(
+            self.side_axis,
+            self.side_sign,
+            layers,
+            self.grip,
+            self.turns,
+        )
+    }
+
+    fn canonical(
+        side_axis: usize,
+        side_sign: i8,
+        layers: Layers,
+        grip: [i8; 3],
+        turns: i8,
+    ) -> Self {
+        debug_assert!(side_axis < 4, "side_axis out of range: {side_axis}");
+        debug_assert!(
+            side_sign == 1 || side_sign == -1,
+            "bad side_sign {side_sign}"
+        );
+        debug_assert!(
+            grip.iter().all(|c| (-1..=1).contains(c)) && grip.iter().any(|c| *c != 0),
+            "grip isn't an actionable local position: {grip:?}"
+        );
+        let flip = grip.iter().find(|c| **c != 0).is_some_and(|c| *c < 0);
+        let (grip, turns) = if flip {
+            (grip.map(|c| -c), -turns)
+        } else {
+            (grip, turns)
+        };
+        let order = order_of(grip) as i8;
+        let mut turns = turns.rem_euclid(order);
+        if turns > order / 2 {
+            turns -= order;
+        }
+        let side_sign = if matches!(layers, Layers::Middle | Layers::All) {
+            1
+        } else {
+            side_sign
+        };
+        let mv = Self {
+            side_axis,
+            side_sign,
+            layers,
+            grip,
+            turns,
+        };
+        if layers != Layers::All || order != 4 {
+            return mv;
+        }
+
+        let grip_slot = grip.iter().position(|c| *c != 0).expect("face-type grip");
+        let grip_axis = free_axes(side_axis)[grip_slot];
+        if grip_axis > side_axis {
+            return mv;
+        }
+        let mut swapped_grip = [0; 3];
+        swapped_grip[free_axes(grip_axis)
+            .iter()
+            .position(|&a| a == side_axis)
+            .expect("the side axis is free for the grip's axis")] = 1;
+        let swapped = |turns| Self {
+            side_axis: grip_axis,
+            side_sign: 1,
+            layers,
+            grip: swapped_grip,
+            turns,
+        };
+        if discrete_rotation_4d(swapped(turns)) == discrete_rotation_4d(mv) {
+            swapped(turns)
+        } else {
+            swapped(-turns)
+        }
+    }
+
+    pub(crate) fn side_axis(self) -> usize {
+        self.side_axis
+    }
+
+    pub(crate) fn side_sign(self) -> i8 {
+        self.side_sign
+    }
+
+    pub(crate) fn layers(self) -> Layers {
+        self.layers
+    }
+
+    /// Whether this turns the whole puzzle rather than twisting it.
+    pub(crate) fn is_rotation(self) -> bool {
+        self.layers == Layers::All
+    }
+
+    /// Whether the piece at `position` turns with this move.
+    pub(crate) fn turns_piece_at(self, position: [i8; 4]) -> bool {
+        self.layers
+            .contain(self.side_sign, position[self.side_axis])
+    }
+
+    /// The canonical grip: the rotation axis, in the side's local frame.
+    pub(crate) fn grip(self) -> [i8; 3] {
+        self.grip
+    }
+
+    /// Signed number of `base_angle` turns, in `(-order/2, order/2]`.
+    pub(crate) fn turns(self) -> i8 {
+        self.turns
+    }
+
+    /// How many repeats of a single turn bring the side back to where it
+    /// started: 4 for a face-type grip, 2 for edge-type, 3 for corner-type.
+    pub(crate) fn order(self) -> u8 {
+        order_of(self.grip)
+    }
+
+    /// The move that undoes this one.
+    pub(crate) fn inverse(self) -> Self {
+        Self::canonical(
+            self.side_axis,
+            self.side_sign,
+            self.layers,
+            self.grip,
+            -self.turns,
+        )
+    }
+
+    /// Signed rotation angle about `grip`, as `rotate_local_position` and
+    /// the move animation consume it.
+    pub(crate) fn angle(self) -> f32 {
+        self.turns as f32 * base_angle(nonzero_count(self.grip))
+    }
+}
+
+fn nonzero_count(local_coords: [i8; 3]) -> usize {
+    local_coords.iter().filter(|c| **c != 0).count()
+}
+
+fn order_of(grip: [i8; 3]) -> u8 {
+    match nonzero_count(grip) {
+        1 => 4,
+        2 => 2,
+        3 => 3,
+        n => unreachable!("non-actionable or malformed grip: {n} nonzero"),
+    }
+}
+
+/// Rounds a continuous 3D rotation matrix (about `mv`'s grip, by its angle)
 /// to an exact signed permutation: `new[row] = sign[row] * old[perm[row]]`.
 /// Panics via `debug_assert` if the matrix isn't actually a signed
 /// permutation at this angle/axis - a real bug indicator (e.g. an angle that
 /// isn't a valid lattice symmetry for this axis), not a case to paper over.
-pub(crate) fn discrete_rotation(local_coords: [i8; 3], angle: f32) -> ([usize; 3], [i8; 3]) {
-    let axis = Vector3::new(
-        local_coords[0] as f32,
-        local_coords[1] as f32,
-        local_coords[2] as f32,
-    );
-    let rotation = Rotation3::from_axis_angle(&Unit::new_normalize(axis), angle);
+pub(crate) fn discrete_rotation(mv: Move) -> ([usize; 3], [i8; 3]) {
+    let grip = mv.grip();
+    let axis = Vector3::new(grip[0] as f32, grip[1] as f32, grip[2] as f32);
+    let rotation = Rotation3::from_axis_angle(&Unit::new_normalize(axis), mv.angle());
     let matrix = rotation.matrix();
 
     let mut perm = [usize::MAX; 3];
@@ -56,6 +269,21 @@ pub(crate) fn discrete_rotation(local_coords: [i8; 3], angle: f32) -> ([usize; 3
     (perm, sign)
 }
 
+/// `discrete_rotation` over all 4 axes, in the same form (`new[axis] =
+/// sign[axis] * old[perm[axis]]`): the free axes permute among themselves
+/// and the side's own axis stays put.
+pub(crate) fn discrete_rotation_4d(mv: Move) -> ([usize; 4], [i8; 4]) {
+    let axes = free_axes(mv.side_axis());
+    let (perm, sign) = discrete_rotation(mv);
+    let mut perm_4d = [0, 1, 2, 3];
+    let mut sign_4d = [1; 4];
+    for slot in 0..3 {
+        perm_4d[axes[slot]] = axes[perm[slot]];
+        sign_4d[axes[slot]] = sign[slot];
+    }
+    (perm_4d, sign_4d)
+}
+
 /// Rotates a continuous local-space position by `angle` about `local_coords`,
 /// without rounding to the lattice. Used to render the in-between frames of
 /// a move's animation; `discrete_rotation` is used for the final, snapped
@@ -147,57 +375,417 @@ pub(crate) fn clockwise_sign(facet: &FacetGeometry) -> f32 {
 }
 
 /// Picks a uniformly random actionable facet from `FACET_TABLE` and derives
-/// a legal move from it: `(side_axis, side_sign, local_coords, angle)`, with
-/// `angle`'s magnitude from `base_angle` and a random sign. Unlike
+/// a legal single-turn move from it, in a random direction. Unlike
 /// `clockwise_sign`, which picks a sign to match what a viewer would call
 /// clockwise, a random move has no visual referent to match, so this stays
 /// pure puzzle logic with no dependency on `math::project_4d_to_3d`.
-pub(crate) fn random_move(rng: &mut fastrand::Rng) -> (usize, i8, [i8; 3], f32) {
+pub(crate) fn random_move(rng: &mut fastrand::Rng) -> Move {
     let actionable: Vec<&FacetGeometry> = FACET_TABLE.iter().filter(|f| f.is_actionable).collect();
     let facet = actionable[rng.usize(..actionable.len())];
-    let nonzero = facet.local_coords.iter().filter(|c| **c != 0).count();
-    let sign = if rng.bool() { 1.0 } else { -1.0 };
-    (
-        facet.axis,
-        facet.side_sign,
-        facet.local_coords,
-        base_angle(nonzero) * sign,
-    )
+    let turns = if rng.bool() { 1 } else { -1 };
+    Move::new(facet.axis, facet.side_sign, facet.local_coords, turns)
+}
+
+/// Every canonical outer-layer move on every side, one per distinct effect:
+/// for each side, every nonzero multiple of every grip's turn.
+pub(crate) fn all_moves() -> Vec<Move> {
+    let mut moves = Vec::new();
+    for &(side_axis, side_sign) in &FACE_AXIS_SIGN {
+        for facet in FACET_TABLE
+            .iter()
+            .filter(|f| f.is_actionable && f.axis == side_axis && f.side_sign == side_sign)
+        {
+            let order = Move::new(side_axis, side_sign, facet.local_coords, 1).order() as i8;
+            for turns in 1..order {
+                let mv = Move::new(side_axis, side_sign, facet.local_coords, turns);
+                if !moves.contains(&mv) {
+                    moves.push(mv);
+                }
+            }
+        }
+    }
+    moves
+}
+
+/// Every canonical whole-puzzle rotation, one per distinct effect: each of
+/// the tesseract's rotations that fixes some axis. Together they generate
+/// all 192 of its rotations.
+pub(crate) fn all_rotations() -> Vec<Move> {
+    let mut rotations = Vec::new();
+    for mv in all_moves() {
+        let rotation = mv.with_layers(Layers::All);
+        if !rotations.contains(&rotation) {
+            rotations.push(rotation);
+        }
+    }
+    rotations
+}
+
+/// The whole-puzzle rotation carrying the cell at `from` onto the cell at
+/// `to` (each an `(axis, sign)` pair): a quarter turn of the plane they
+/// span for neighboring cells, a half turn for opposite ones. `None` when
+/// they're the same cell.
+pub(crate) fn reorientation(from: (usize, i8), to: (usize, i8)) -> Option<Move> {
+    if from == to {
+        return None;
+    }
+    all_rotations()
+        .into_iter()
+        .filter(|&mv| {
+            let (perm, sign) = discrete_rotation_4d(mv);
+            let axis = perm
+                .iter()
+                .position(|&src| src == from.0)
+                .expect("perm is a bijection");
+            (axis, sign[axis] * from.1) == to
+        })
+        .min_by_key(|mv| (nonzero_count(mv.grip()), mv.turns().abs()))
+}
+
+/// The sequence that undoes `moves`: each move's inverse, last first.
+pub(crate) fn invert_moves(moves: &[Move]) -> Vec<Move> {
+    moves.iter().rev().map(|mv| mv.inverse()).collect()
+}
+
+/// Whether `a` and `b` turn disjoint layers of the same axis (opposite
+/// sides, say), so doing them in either order has the same effect.
+pub(crate) fn commute(a: Move, b: Move) -> bool {
+    a.side_axis == b.side_axis
+        && (-1..=1).all(|coordinate| {
+            !(a.layers.contain(a.side_sign, coordinate)
+                && b.layers.contain(b.side_sign, coordinate))
+        })
+}
+
+/// `moves` with turns of the same layers about the same grip merged into
+/// one (modulo the grip's order, as `Move` always is) and any that add up
+/// to nothing dropped, so inverse pairs cancel. A move merges past
+/// anything in between that `commute`s with it, so `RU LU RU'` is just
+/// `LU`. Has the same effect on the puzzle as `moves`, in no more moves.
+pub(crate) fn simplify_moves(moves: &[Move]) -> Vec<Move> {
+    let mut simplified: Vec<Move> = Vec::with_capacity(moves.len());
+    'moves: for &mv in moves {
+        for index in (0..simplified.len()).rev() {
+            let earlier = simplified[index];
+            if (
+                earlier.side_axis,
+                earlier.side_sign,
+                earlier.layers,
+                earlier.grip,
+            ) == (mv.side_axis, mv.side_sign, mv.layers, mv.grip)
+            {
+                let merged = Move::canonical(
+                    mv.side_axis,
+                    mv.side_sign,
+                    mv.layers,
+                    mv.grip,
+                    earlier.turns + mv.turns,
+                );
+                if merged.turns == 0 {
+                    simplified.remove(index);
+                } else {
+                    simplified[index] = merged;
+                }
+                continue 'moves;
+            }
+            if !commute(earlier, mv) {
+                break;
+            }
+        }
+        simplified.push(mv);
+    }
+    simplified
+}
+
+/// Notation letter for each of the 8 cells, indexed by `face_id_for`:
+/// Hyperspeedcube/MC4D-style In/Left/Down/Front/Back/Up/Right/Out, matching
+/// the center/left/bottom/front/back/top/right/void roles `piece.rs`'s
+/// `COLORS` table assigns the same face ids.
+pub(crate) const CELL_LETTERS: [char; 8] = ['I', 'L', 'D', 'F', 'B', 'U', 'R', 'O'];
+
+/// `(axis, sign)` of the cell named by `letter`, if it names one.
+pub(crate) fn cell_for_letter(letter: char) -> Option<(usize, i8)> {
+    let face_id = CELL_LETTERS.iter().position(|&c| c == letter)?;
+    Some(FACE_AXIS_SIGN[face_id])
+}
+
+fn cell_letter(axis: usize, sign: i8) -> char {
+    CELL_LETTERS[face_id_for(axis, sign)]
+}
+
+/// Names the piece at `position` by the cells its stickers face, in axis
+/// order, e.g. `BURO` for the corner at `[1, 1, 1, 1]`.
+pub(crate) fn piece_name(position: [i8; 4]) -> String {
+    (0..4)
+        .filter(|&axis| position[axis] != 0)
+        .map(|axis| cell_letter(axis, position[axis]))
+        .collect()
+}
+
+/// The facet a player clicks to grip `side` at `grip`: the one whose
+/// `clockwise_sign` defines what a single notation turn means.
+fn grip_facet(side_axis: usize, side_sign: i8, grip: [i8; 3]) -> &'static FacetGeometry {
+    FACET_TABLE
+        .iter()
+        .find(|f| f.axis == side_axis && f.side_sign == side_sign && f.local_coords == grip)
+        .expect("every actionable grip has a facet on its side")
+}
+
+/// What went wrong parsing move notation; see `ParseMoveError`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ParseMoveErrorKind {
+    /// A letter that isn't one of the 8 cell names.
+    UnknownCell(char),
+    /// A cell name with no grip letters after it.
+    MissingGrip,
+    /// A grip letter naming the twisted cell itself, or the cell opposite it.
+    GripOnSideAxis(char),
+    /// Two grip letters on the same axis (e.g. both `U` and `D`).
+    DuplicateGripAxis(char),
+    /// Anything after the grip other than an optional count and `'`.
+    BadSuffix,
+    /// A `{...}` layer prefix other than `{1}`, `{2}`, `{1-2}` or `{1-3}`,
+    /// or one with no move after it.
+    BadLayers,
+}
+
+impl fmt::Display for ParseMoveErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseMoveErrorKind::UnknownCell(c) => write!(f, "'{c}' is not a cell name"),
+            ParseMoveErrorKind::MissingGrip => write!(f, "cell name has no grip after it"),
+            ParseMoveErrorKind::GripOnSideAxis(c) => {
+                write!(f, "grip '{c}' is on the twisted cell's own axis")
+            }
+            ParseMoveErrorKind::DuplicateGripAxis(c) => {
+                write!(f, "grip '{c}' repeats an axis already in this grip")
+            }
+            ParseMoveErrorKind::BadSuffix => write!(f, "expected a turn count and/or '"),
+            ParseMoveErrorKind::BadLayers => {
+                write!(
+                    f,
+                    "expected {{1}}, {{2}}, {{1-2}} or {{1-3}} and then a move"
+                )
+            }
+        }
+    }
+}
+
+/// A notation parse failure, with the byte range of the offending input so
+/// a UI can point at it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ParseMoveError {
+    pub(crate) span: Range<usize>,
+    pub(crate) kind: ParseMoveErrorKind,
+}
+
+impl fmt::Display for ParseMoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (at {}..{})",
+            self.kind, self.span.start, self.span.end
+        )
+    }
+}
+
+impl std::error::Error for ParseMoveError {}
+
+/// Parses one move token, e.g. `RU`, `OBR'`, `{2}IUFR2`: an optional layer
+/// prefix (`{2}` the middle slice, `{1-2}` the side and middle slice, `{1}`
+/// the side alone, as by default, `{1-3}` the whole puzzle), the twisted
+/// cell's letter, then 1-3 grip
+/// letters (neighboring cells, one per grip axis, in any order), then an
+/// optional turn count and an optional `'` to reverse it. One turn is what a
+/// plain click on that grip's sticker does (`clockwise_sign`), so the
+/// notation reads the same as clicking. `offset` is `token`'s byte position
+/// in the larger input, for error spans.
+pub(crate) fn parse_move(token: &str, offset: usize) -> Result<Move, ParseMoveError> {
+    let error = |range: Range<usize>, kind| ParseMoveError {
+        span: offset + range.start..offset + range.end,
+        kind,
+    };
+    if token.starts_with('{') {
+        let bad_layers = |end| error(0..end, ParseMoveErrorKind::BadLayers);
+        let end = token.find('}').ok_or_else(|| bad_layers(token.len()))? + 1;
+        let layers = match &token[1..end - 1] {
+            "1" => Layers::Outer,
+            "2" => Layers::Middle,
+            "1-2" => Layers::Wide,
+            "1-3" => Layers::All,
+            _ => return Err(bad_layers(end)),
+        };
+        if end == token.len() {
+            return Err(bad_layers(end));
+        }
+        return parse_move(&token[end..], offset + end).map(|mv| mv.with_layers(layers));
+    }
+    let mut chars = token.char_indices().peekable();
+
+    let (_, side_letter) = chars.next().expect("tokens are never empty");
+    let (side_axis, side_sign) = cell_for_letter(side_letter).ok_or_else(|| {
+        error(
+            0..side_letter.len_utf8(),
+            ParseMoveErrorKind::UnknownCell(side_letter),
+        )
+    })?;
+    let axes = free_axes(side_axis);
+
+    let mut grip = [0i8; 3];
+    let mut grip_end = side_letter.len_utf8();
+    while let Some(&(i, letter)) = chars.peek() {
+        if !letter.is_alphabetic() {
+            break;
+        }
+        chars.next();
+        let span = i..i + letter.len_utf8();
+        let (axis, sign) = cell_for_letter(letter)
+            .ok_or_else(|| error(span.clone(), ParseMoveErrorKind::UnknownCell(letter)))?;
+        let slot = axes
+            .iter()
+            .position(|&a| a == axis)
+            .ok_or_else(|| error(span.clone(), ParseMoveErrorKind::GripOnSideAxis(letter)))?;
+        if grip[slot] != 0 {
+            return Err(error(span, ParseMoveErrorKind::DuplicateGripAxis(letter)));
+        }
+        grip[slot] = sign;
+        grip_end = span.end;
+    }
+    if grip == [0; 3] {
+        return Err(error(
+            0..side_letter.len_utf8(),
+            ParseMoveErrorKind::MissingGrip,
+        ));
+    }
+
+    let suffix = &token[grip_end..];
+    let (count, reversed) = match suffix.strip_suffix('\'') {
+        Some(count) => (count, true),
+        None => (suffix, false),
+    };
+    let count: i8 = if count.is_empty() {
+        1
+    } else if count.bytes().all(|b| b.is_ascii_digit()) {
+        count
+            .parse::<u8>()
+            .ok()
+            .and_then(|n| i8::try_from(n).ok())
+            .ok_or_else(|| error(grip_end..token.len(), ParseMoveErrorKind::BadSuffix))?
+    } else {
+        return Err(error(grip_end..token.len(), ParseMoveErrorKind::BadSuffix));
+    };
+    let clicks = if reversed { -count } else { count };
+
+    let clockwise = clockwise_sign(grip_facet(side_axis, side_sign, grip)) as i8;
+    Ok(Move::new(side_axis, side_sign, grip, clicks * clockwise))
+}
+
+/// Parses a whitespace-separated move sequence (see `parse_move` for a
+/// single token). An empty or all-whitespace input is an empty sequence.
+pub(crate) fn parse_moves(input: &str) -> Result<Vec<Move>, ParseMoveError> {
+    let mut moves = Vec::new();
+    let mut start = None;
+    for (i, c) in input
+        .char_indices()
+        .chain(std::iter::once((input.len(), ' ')))
+    {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some(i),
+            (true, Some(token_start)) => {
+                moves.push(parse_move(&input[token_start..i], token_start)?);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    Ok(moves)
+}
+
+/// Formats a move sequence, space-separated, such that `parse_moves` reads
+/// it back as the same moves.
+pub(crate) fn format_moves(moves: &[Move]) -> String {
+    moves
+        .iter()
+        .map(Move::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl fmt::Display for Move {
+    /// Writes the layer prefix (none for the outer layer), the cell, the
+    /// canonical grip's letters in ascending free-axis order, then the click
+    /// count relative to that grip's `clockwise_sign`: no suffix for one
+    /// click, `'` for one click back, `2` for a half turn.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.layers {
+            Layers::Outer => {}
+            Layers::Middle => write!(f, "{{2}}")?,
+            Layers::Wide => write!(f, "{{1-2}}")?,
+            Layers::All => write!(f, "{{1-3}}")?,
+        }
+        write!(f, "{}", cell_letter(self.side_axis, self.side_sign))?;
+        for (slot, &axis) in free_axes(self.side_axis).iter().enumerate() {
+            if self.grip[slot] != 0 {
+                write!(f, "{}", cell_letter(axis, self.grip[slot]))?;
+            }
+        }
+        let clockwise = clockwise_sign(grip_facet(self.side_axis, self.side_sign, self.grip)) as i8;
+        match self.turns * clockwise {
+            1 => Ok(()),
+            -1 => write!(f, "'"),
+            n if n < 0 => write!(f, "{}'", -n),
+            n => write!(f, "{n}"),
+        }
+    }
+}
+
+/// Stored as notation in session files, so they stay human-readable.
+impl From<Move> for String {
+    fn from(mv: Move) -> Self {
+        mv.to_string()
+    }
+}
+
+impl TryFrom<String> for Move {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        match parse_moves(&text).map_err(|err| err.to_string())?[..] {
+            [mv] => Ok(mv),
+            _ => Err(format!("expected exactly one move, found {text:?}")),
+        }
+    }
 }
 
 impl Hypercube {
-    /// Applies a move: `side_axis`/`side_sign` select the 27-piece affected
-    /// side; `local_coords` is the clicked piece's local position (the
-    /// rotation axis); `angle` is the signed target angle (its sign encodes
-    /// direction, its magnitude should come from `base_angle`).
-    pub(crate) fn apply_move(
-        &mut self,
-        side_axis: usize,
-        side_sign: i8,
-        local_coords: [i8; 3],
-        angle: f32,
-    ) {
-        let axes = free_axes(side_axis);
-        let (perm, sign) = discrete_rotation(local_coords, angle);
+    /// Applies a move to the 27 pieces in each of its layers, rotating them
+    /// as a rigid block about its grip.
+    pub(crate) fn apply_move(&mut self, mv: Move) {
+        let (perm, sign) = discrete_rotation_4d(mv);
 
         let affected = self
             .pieces
             .iter()
             .enumerate()
-            .filter(|(_, p)| p.position[side_axis] == side_sign)
+            .filter(|(_, p)| mv.turns_piece_at(p.position))
             .map(|(i, _)| i);
-        debug_assert_eq!(affected.clone().count(), 27);
+        debug_assert_eq!(
+            affected.clone().count(),
+            match mv.layers() {
+                Layers::Outer | Layers::Middle => 27,
+                Layers::Wide => 54,
+                Layers::All => 81,
+            }
+        );
 
         let snapshot: Vec<Piece> = affected.map(|i| self.pieces[i]).collect();
 
         for old in &snapshot {
             let mut new_position = old.position;
             let mut new_colors = old.colors;
-            for slot in 0..3 {
-                let dst = axes[slot];
-                let src = axes[perm[slot]];
-                new_position[dst] = sign[slot] * old.position[src];
-                new_colors[dst] = old.colors[src];
+            for axis in 0..4 {
+                new_position[axis] = sign[axis] * old.position[perm[axis]];
+                new_colors[axis] = old.colors[perm[axis]];
             }
             self.pieces[index_of(new_position)] = Piece {
                 position: new_position,
@@ -208,17 +796,22 @@ impl Hypercube {
 
     /// Applies `count` random legal moves in sequence, instantly (no
     /// animation) - used by the 1/2/3-random-move and Scramble UI actions.
-    pub(crate) fn apply_random_moves(&mut self, count: u32, rng: &mut fastrand::Rng) {
-        for _ in 0..count {
-            let (side_axis, side_sign, local_coords, angle) = random_move(rng);
-            self.apply_move(side_axis, side_sign, local_coords, angle);
-        }
+    /// Returns the moves applied, in order, so the scramble can be logged.
+    pub(crate) fn apply_random_moves(&mut self, count: u32, rng: &mut fastrand::Rng) -> Vec<Move> {
+        (0..count)
+            .map(|_| {
+                let mv = random_move(rng);
+                self.apply_move(mv);
+                mv
+            })
+            .collect()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::piece::position_of;
 
     /// side_axis=W(3), side_sign=+1, free_axes=[X(0),Y(1),Z(2)] throughout.
     const SIDE_AXIS: usize = 3;
@@ -227,7 +820,7 @@ mod tests {
     #[test]
     fn discrete_rotation_matches_worked_180_edge_example() {
         // local_coords=(1,1,0): X<->Y swap, Z negated.
-        let (perm, sign) = discrete_rotation([1, 1, 0], PI);
+        let (perm, sign) = discrete_rotation(Move::new(SIDE_AXIS, SIDE_SIGN, [1, 1, 0], 1));
         assert_eq!(perm, [1, 0, 2]);
         assert_eq!(sign, [1, 1, -1]);
     }
@@ -235,7 +828,7 @@ mod tests {
     #[test]
     fn discrete_rotation_matches_worked_120_corner_example() {
         // local_coords=(1,1,1): cyclic X<-Z<-Y<-X.
-        let (perm, sign) = discrete_rotation([1, 1, 1], TAU / 3.0);
+        let (perm, sign) = discrete_rotation(Move::new(SIDE_AXIS, SIDE_SIGN, [1, 1, 1], 1));
         assert_eq!(perm, [2, 0, 1]);
         assert_eq!(sign, [1, 1, 1]);
     }
@@ -243,7 +836,7 @@ mod tests {
     #[test]
     fn discrete_rotation_90_face_is_a_valid_signed_permutation() {
         for &local_coords in &[[1i8, 0, 0], [0, 1, 0], [0, 0, 1]] {
-            let (perm, sign) = discrete_rotation(local_coords, FRAC_PI_2);
+            let (perm, sign) = discrete_rotation(Move::new(SIDE_AXIS, SIDE_SIGN, local_coords, 1));
             let mut sorted = perm;
             sorted.sort_unstable();
             assert_eq!(sorted, [0, 1, 2]);
@@ -269,7 +862,7 @@ mod tests {
             ([1, 1, 0], PI),
             ([1, 1, 1], TAU / 3.0),
         ] {
-            let (perm, sign) = discrete_rotation(local_coords, angle);
+            let (perm, sign) = discrete_rotation(Move::new(SIDE_AXIS, SIDE_SIGN, local_coords, 1));
             let position = [1.0, -1.0, 0.5];
             let expected = [
                 sign[0] as f32 * position[perm[0]],
@@ -289,14 +882,7 @@ mod tests {
     }
 
     fn apply_click(cube: &mut Hypercube, local_coords: [i8; 3], direction: i8) {
-        let nonzero = local_coords.iter().filter(|c| **c != 0).count();
-        let magnitude = base_angle(nonzero);
-        cube.apply_move(
-            SIDE_AXIS,
-            SIDE_SIGN,
-            local_coords,
-            magnitude * direction as f32,
-        );
+        cube.apply_move(Move::new(SIDE_AXIS, SIDE_SIGN, local_coords, direction));
     }
 
     fn colors_position_invariant_holds(cube: &Hypercube) -> bool {
@@ -446,12 +1032,406 @@ mod tests {
         assert_eq!(cube, solved);
     }
 
+    #[test]
+    fn move_canonicalizes_grip_sign_and_turns() {
+        let mv = Move::new(SIDE_AXIS, SIDE_SIGN, [-1, 0, 0], 1);
+        assert_eq!(mv.grip(), [1, 0, 0]);
+        assert_eq!(mv.turns(), -1);
+        assert_eq!(mv, Move::new(SIDE_AXIS, SIDE_SIGN, [1, 0, 0], 3));
+        assert_eq!(Move::new(SIDE_AXIS, SIDE_SIGN, [0, 1, 0], -2).turns(), 2);
+        assert_eq!(Move::new(SIDE_AXIS, SIDE_SIGN, [1, -1, 0], -1).turns(), 1);
+        assert_eq!(Move::new(SIDE_AXIS, SIDE_SIGN, [1, 1, 1], 2).turns(), -1);
+    }
+
+    #[test]
+    fn move_equality_and_hashing_follow_effect_on_the_puzzle() {
+        use std::collections::HashSet;
+
+        let same: HashSet<Move> = [
+            Move::new(SIDE_AXIS, SIDE_SIGN, [1, 1, 0], 1),
+            Move::new(SIDE_AXIS, SIDE_SIGN, [-1, -1, 0], 1),
+            Move::new(SIDE_AXIS, SIDE_SIGN, [1, 1, 0], -1),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(same.len(), 1);
+
+        for (a, b) in [
+            (
+                Move::new(SIDE_AXIS, SIDE_SIGN, [1, 0, 0], 1),
+                Move::new(SIDE_AXIS, SIDE_SIGN, [-1, 0, 0], -1),
+            ),
+            (
+                Move::new(SIDE_AXIS, SIDE_SIGN, [1, 1, 1], 1),
+                Move::new(SIDE_AXIS, SIDE_SIGN, [-1, -1, -1], -1),
+            ),
+        ] {
+            assert_eq!(a, b);
+            let mut via_a = Hypercube::solved();
+            via_a.apply_move(a);
+            let mut via_b = Hypercube::solved();
+            via_b.apply_move(b);
+            assert_eq!(via_a, via_b);
+        }
+    }
+
+    #[test]
+    fn move_order_matches_grip_type_and_period() {
+        for (grip, order) in [([1i8, 0, 0], 4u8), ([1, 1, 0], 2), ([1, 1, 1], 3)] {
+            let mv = Move::new(SIDE_AXIS, SIDE_SIGN, grip, 1);
+            assert_eq!(mv.order(), order);
+            let mut cube = Hypercube::solved();
+            for _ in 0..order {
+                cube.apply_move(mv);
+            }
+            assert!(cube.is_solved());
+        }
+    }
+
+    #[test]
+    fn move_inverse_undoes_it() {
+        for grip in [[1i8, 0, 0], [1, 1, 0], [1, 1, 1], [0, -1, 1]] {
+            for turns in [1, 2] {
+                let mv = Move::new(SIDE_AXIS, SIDE_SIGN, grip, turns);
+                assert_eq!(mv.inverse().inverse(), mv);
+                let mut cube = Hypercube::solved();
+                cube.apply_move(mv);
+                cube.apply_move(mv.inverse());
+                assert!(cube.is_solved());
+            }
+        }
+    }
+
+    #[test]
+    fn move_angle_is_turns_times_base_angle() {
+        assert_eq!(
+            Move::new(SIDE_AXIS, SIDE_SIGN, [1, 0, 0], 1).angle(),
+            FRAC_PI_2
+        );
+        assert_eq!(Move::new(SIDE_AXIS, SIDE_SIGN, [1, 0, 0], 2).angle(), PI);
+        assert_eq!(Move::new(SIDE_AXIS, SIDE_SIGN, [1, 1, 0], 1).angle(), PI);
+        assert_eq!(
+            Move::new(SIDE_AXIS, SIDE_SIGN, [1, 1, 1], -1).angle(),
+            -TAU / 3.0
+        );
+    }
+
+    #[test]
+    fn notation_round_trips_every_move() {
+        let moves = all_moves();
+        // 8 sides * (6 face grips * 3 + 12 edge grips / 2 + 8 corner grips)
+        assert_eq!(moves.len(), 8 * (3 * 3 + 6 + 8));
+        for mv in &moves {
+            let text = mv.to_string();
+            assert_eq!(parse_moves(&text), Ok(vec![*mv]), "{text}");
+        }
+        let text = format_moves(&moves);
+        assert_eq!(parse_moves(&text), Ok(moves));
+    }
+
+    #[test]
+    fn notation_single_turn_matches_a_plain_click() {
+        for facet in FACET_TABLE.iter().filter(|f| f.is_actionable) {
+            let clicked = Move::new(
+                facet.axis,
+                facet.side_sign,
+                facet.local_coords,
+                clockwise_sign(facet) as i8,
+            );
+            let mut text = cell_letter(facet.axis, facet.side_sign).to_string();
+            for (slot, &axis) in facet.free_axes.iter().enumerate() {
+                if facet.local_coords[slot] != 0 {
+                    text.push(cell_letter(axis, facet.local_coords[slot]));
+                }
+            }
+            assert_eq!(parse_moves(&text), Ok(vec![clicked]), "{text}");
+        }
+    }
+
+    #[test]
+    fn notation_accepts_grip_letters_in_any_order_and_suffixes() {
+        assert_eq!(parse_moves("RUF"), parse_moves("RFU"));
+        let single = parse_moves("OB").unwrap()[0];
+        assert_eq!(parse_moves("OB'"), Ok(vec![single.inverse()]));
+        assert_eq!(parse_moves("OB3"), Ok(vec![single.inverse()]));
+        assert_eq!(parse_moves("OB2'"), parse_moves("OB2"));
+        assert_eq!(
+            parse_moves("  OB \t OB'\n"),
+            Ok(vec![single, single.inverse()])
+        );
+        assert_eq!(parse_moves(""), Ok(vec![]));
+    }
+
+    #[test]
+    fn notation_errors_point_at_the_offending_input() {
+        let err = |input: &str| parse_moves(input).unwrap_err();
+        assert_eq!(
+            err("RU XU"),
+            ParseMoveError {
+                span: 3..4,
+                kind: ParseMoveErrorKind::UnknownCell('X'),
+            }
+        );
+        assert_eq!(
+            err("RU R"),
+            ParseMoveError {
+                span: 3..4,
+                kind: ParseMoveErrorKind::MissingGrip,
+            }
+        );
+        assert_eq!(
+            err("RL"),
+            ParseMoveError {
+                span: 1..2,
+                kind: ParseMoveErrorKind::GripOnSideAxis('L'),
+            }
+        );
+        assert_eq!(
+            err("RUD"),
+            ParseMoveError {
+                span: 2..3,
+                kind: ParseMoveErrorKind::DuplicateGripAxis('D'),
+            }
+        );
+        assert_eq!(
+            err("RU2x"),
+            ParseMoveError {
+                span: 2..4,
+                kind: ParseMoveErrorKind::BadSuffix,
+            }
+        );
+        assert_eq!(
+            err("RU {3}RU"),
+            ParseMoveError {
+                span: 3..6,
+                kind: ParseMoveErrorKind::BadLayers,
+            }
+        );
+        assert_eq!(
+            err("{1-2}"),
+            ParseMoveError {
+                span: 0..5,
+                kind: ParseMoveErrorKind::BadLayers,
+            }
+        );
+        assert_eq!(
+            err("{2}RL"),
+            ParseMoveError {
+                span: 4..5,
+                kind: ParseMoveErrorKind::GripOnSideAxis('L'),
+            }
+        );
+    }
+
+    #[test]
+    fn middle_slice_turns_the_27_pieces_between_the_sides() {
+        for grip in [[1i8, 0, 0], [1, 1, 0], [1, 1, 1]] {
+            let mv = Move::new(SIDE_AXIS, SIDE_SIGN, grip, 1).with_layers(Layers::Middle);
+            let mut cube = Hypercube::solved();
+            cube.apply_move(mv);
+            let moved: Vec<[i8; 4]> = (0..81)
+                .map(position_of)
+                .filter(|&position| {
+                    cube.pieces[index_of(position)]
+                        != Hypercube::solved().pieces[index_of(position)]
+                })
+                .collect();
+            assert!(!moved.is_empty());
+            assert!(moved.iter().all(|position| position[SIDE_AXIS] == 0));
+            // Unlike an outer twist, a slice carries cell centers along.
+            assert!(
+                moved
+                    .iter()
+                    .any(|position| position.iter().filter(|c| **c != 0).count() == 1)
+            );
+        }
+    }
+
+    #[test]
+    fn wide_move_is_the_outer_and_middle_layers_together() {
+        for grip in [[1i8, 0, 0], [1, 1, 0], [1, 1, 1]] {
+            let outer = Move::new(SIDE_AXIS, SIDE_SIGN, grip, 1);
+            let mut wide = Hypercube::solved();
+            wide.apply_move(outer.with_layers(Layers::Wide));
+            let mut separately = Hypercube::solved();
+            separately.apply_move(outer);
+            separately.apply_move(outer.with_layers(Layers::Middle));
+            assert_eq!(wide, separately);
+        }
+    }
+
+    #[test]
+    fn middle_slice_is_the_same_move_from_either_side() {
+        let from_plus = Move::new(SIDE_AXIS, 1, [1, 1, 0], 1).with_layers(Layers::Middle);
+        let from_minus = Move::new(SIDE_AXIS, -1, [1, 1, 0], 1).with_layers(Layers::Middle);
+        assert_eq!(from_plus, from_minus);
+        assert_eq!(from_plus.inverse().layers(), Layers::Middle);
+        // Wide moves on opposite sides turn different blocks.
+        assert_ne!(
+            from_plus.with_layers(Layers::Wide),
+            Move::new(SIDE_AXIS, -1, [1, 1, 0], 1).with_layers(Layers::Wide)
+        );
+    }
+
+    #[test]
+    fn rotations_are_canonical_and_generate_all_192_tesseract_rotations() {
+        use std::collections::HashSet;
+
+        let rotations = all_rotations();
+        let effects: HashSet<_> = rotations
+            .iter()
+            .map(|&mv| discrete_rotation_4d(mv))
+            .collect();
+        assert_eq!(effects.len(), rotations.len(), "one move per effect");
+
+        // Closing under composition reaches every rotation and, since there
+        // are only 192 of the 384 signed permutations, no reflection.
+        let mut reached = HashSet::from([([0, 1, 2, 3], [1; 4])]);
+        let mut frontier: Vec<([usize; 4], [i8; 4])> = reached.iter().copied().collect();
+        while let Some((perm, sign)) = frontier.pop() {
+            for &mv in &rotations {
+                let (then_perm, then_sign) = discrete_rotation_4d(mv);
+                let next = (
+                    then_perm.map(|axis| perm[axis]),
+                    [0, 1, 2, 3].map(|axis| then_sign[axis] * sign[then_perm[axis]]),
+                );
+                if reached.insert(next) {
+                    frontier.push(next);
+                }
+            }
+        }
+        assert_eq!(reached.len(), 192);
+    }
+
+    #[test]
+    fn rotation_turns_every_piece_and_keeps_the_puzzle_solved() {
+        for mv in all_rotations() {
+            assert!(mv.is_rotation());
+            let mut cube = Hypercube::solved();
+            cube.apply_move(mv);
+            assert!(cube.is_solved(), "{mv}");
+            assert_ne!(cube, Hypercube::solved(), "{mv}");
+            assert_eq!(total_facet_count(&cube), 216);
+        }
+    }
+
+    #[test]
+    fn face_type_rotation_is_the_same_move_from_either_fixed_axis() {
+        // Both turn the Y-Z plane: X's grip from side W, W's from side X,
+        // and so does turning all three of W's layers by that grip.
+        let from_w = Move::new(3, -1, [1, 0, 0], 1).with_layers(Layers::All);
+        let from_x = Move::new(0, 1, [0, 0, 1], 1).with_layers(Layers::All);
+        assert_eq!(from_x, from_w);
+        assert_eq!(from_w.side_axis(), 0);
+        let (mut via_w, mut via_x) = (Hypercube::solved(), Hypercube::solved());
+        via_w.apply_move(Move::new(3, -1, [1, 0, 0], 1).with_layers(Layers::Wide));
+        via_w.apply_move(Move::new(3, 1, [1, 0, 0], 1).with_layers(Layers::Outer));
+        via_x.apply_move(from_x);
+        assert_eq!(via_w, via_x);
+    }
+
+    #[test]
+    fn reorientation_carries_one_cell_onto_another() {
+        for from in FACE_AXIS_SIGN {
+            for to in FACE_AXIS_SIGN {
+                let Some(mv) = reorientation(from, to) else {
+                    assert_eq!(from, to);
+                    continue;
+                };
+                assert_eq!(nonzero_count(mv.grip()), 1);
+                assert_eq!(mv.turns().abs(), if from.0 == to.0 { 2 } else { 1 });
+                let mut cube = Hypercube::solved();
+                cube.apply_move(mv);
+                let home = crate::piece::side_color(from.0, from.1);
+                assert!(
+                    cube.pieces
+                        .iter()
+                        .filter(|p| p.position[to.0] == to.1)
+                        .all(|p| p.colors[to.0] == Some(home)),
+                    "{from:?} -> {to:?} via {mv}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn notation_round_trips_slice_and_wide_moves() {
+        for layers in [Layers::Middle, Layers::Wide, Layers::All] {
+            let moves: Vec<Move> = all_moves()
+                .into_iter()
+                .map(|mv| mv.with_layers(layers))
+                .collect();
+            for mv in &moves {
+                let text = mv.to_string();
+                assert!(text.starts_with('{'), "{text}");
+                assert_eq!(parse_moves(&text), Ok(vec![*mv]), "{text}");
+            }
+        }
+        assert_eq!(parse_moves("{1}RU"), parse_moves("RU"));
+        // The opposite cell names the same slice, written from the positive
+        // side's point of view.
+        let from_left = parse_moves("{2}LU").unwrap()[0];
+        assert_eq!(from_left.side_sign(), 1);
+        assert!(parse_moves("{2}RU {2}RU'").unwrap().contains(&from_left));
+    }
+
+    #[test]
+    fn simplify_merges_turns_and_cancels_inverses() {
+        let simplified = |text| simplify_moves(&parse_moves(text).unwrap());
+        let moves = |text| parse_moves(text).unwrap();
+        assert_eq!(simplified("RU RU"), moves("RU2"));
+        assert_eq!(simplified("RU2 RU"), moves("RU'"));
+        assert_eq!(simplified("RU RU'"), moves(""));
+        assert_eq!(simplified("RUO RUO"), moves(""));
+        assert_eq!(simplified("RUFO RUFO RUFO"), moves(""));
+        assert_eq!(simplified("FU RU LB LB' RU' FU2"), moves("FU'"));
+        // One slice, which a click from the left turns the other way.
+        assert_eq!(simplified("{2}RU {2}LU"), moves(""));
+        assert_eq!(simplified("{2}RU {2}LU'"), moves("{2}RU2"));
+        // Not the same layers, so they don't merge.
+        assert_eq!(simplified("RU {1-2}RU'"), moves("RU {1-2}RU'"));
+    }
+
+    #[test]
+    fn simplify_looks_past_moves_on_disjoint_layers() {
+        let simplified = |text| simplify_moves(&parse_moves(text).unwrap());
+        let moves = |text| parse_moves(text).unwrap();
+        assert_eq!(simplified("RU LU RU'"), moves("LU"));
+        assert_eq!(simplified("RU {2}RF LB RU"), moves("RU2 {2}RF LB"));
+        // F turns pieces R does, so R can't move past it.
+        assert_eq!(simplified("RU FU RU'"), moves("RU FU RU'"));
+        assert_eq!(simplified("RU {1-3}FU RU'"), moves("RU {1-3}FU RU'"));
+    }
+
+    #[test]
+    fn simplify_keeps_the_effect_of_random_sequences() {
+        let mut rng = fastrand::Rng::with_seed(14);
+        let layers = [Layers::Outer, Layers::Middle, Layers::Wide, Layers::All];
+        // Few distinct moves, so plenty of them meet and cancel.
+        let pool: Vec<Move> = all_moves()
+            .into_iter()
+            .filter(|mv| [0, 4].contains(&mv.side_axis()))
+            .collect();
+        for _ in 0..50 {
+            let moves: Vec<Move> = (0..40)
+                .map(|_| pool[rng.usize(..pool.len())].with_layers(layers[rng.usize(..4)]))
+                .collect();
+            let simplified = simplify_moves(&moves);
+            assert!(simplified.len() <= moves.len());
+            assert_eq!(simplify_moves(&simplified), simplified);
+            let (mut original, mut shorter) = (Hypercube::solved(), Hypercube::solved());
+            moves.iter().for_each(|&mv| original.apply_move(mv));
+            simplified.iter().for_each(|&mv| shorter.apply_move(mv));
+            assert_eq!(original, shorter);
+        }
+    }
+
     #[test]
     fn random_move_always_targets_an_actionable_facet() {
         let mut rng = fastrand::Rng::with_seed(1);
         for _ in 0..200 {
-            let (_, _, local_coords, _) = random_move(&mut rng);
-            let nonzero = local_coords.iter().filter(|c| **c != 0).count();
+            let grip = random_move(&mut rng).grip();
+            let nonzero = grip.iter().filter(|c| **c != 0).count();
             assert!((1..=3).contains(&nonzero));
         }
     }
@@ -467,6 +1447,18 @@ mod tests {
         assert_eq!(cube_a, cube_b);
     }
 
+    #[test]
+    fn apply_random_moves_returns_the_moves_it_applied() {
+        let mut cube = Hypercube::solved();
+        let moves = cube.apply_random_moves(10, &mut fastrand::Rng::with_seed(5));
+        assert_eq!(moves.len(), 10);
+        let mut replayed = Hypercube::solved();
+        for mv in moves {
+            replayed.apply_move(mv);
+        }
+        assert_eq!(replayed, cube);
+    }
+
     #[test]
     fn apply_random_moves_preserves_facet_count_invariant() {
         let mut cube = Hypercube::solved();
@@ -482,7 +1474,7 @@ mod tests {
         let solved = Hypercube::solved();
         let mut cube = solved.clone();
         let mut rng = fastrand::Rng::with_seed(3);
-        cube.apply_random_moves(0, &mut rng);
+        assert!(cube.apply_random_moves(0, &mut rng).is_empty());
         assert_eq!(cube, solved);
     }
 }
@@ -0,0 +1,322 @@
+//! Sticker painting: entering a state by coloring stickers, e.g. to copy a
+//! physical puzzle in, and checking the result is one twisting can reach
+//! before it's committed.
+//!
+//! Painting works on a loose `Hypercube` whose pieces needn't be real;
+//! `validate` checks, in order, that every piece's colors can exist
+//! together, that no piece appears twice, that no corner is a mirror image,
+//! that the cell centers are a rotation of the solved ones, and then the
+//! invariants `Hypercube::random_state` describes (with the centers turned
+//! home): corners evenly permuted, faces and edges permuted with the same
+//! parity, and each type's `orientation_twist`s summing to 0.
+
+use std::fmt;
+
+use crate::geometry::Color;
+use crate::moves::piece_name;
+use crate::piece::{
+    COLORS, FACET_TABLE, Hypercube, index_of, orientation_twist, permutation_is_odd, position_of,
+    side_of_color, slots_with, twist_modulus,
+};
+use crate::solver::center_reorientation;
+
+/// What a click on a sticker does in painting mode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum Brush {
+    /// Steps the sticker to the next color, in `COLORS` order.
+    #[default]
+    Cycle,
+    Paint(Color),
+}
+
+impl Brush {
+    pub(crate) const ALL: [Brush; 9] = [
+        Brush::Cycle,
+        Brush::Paint(COLORS[0]),
+        Brush::Paint(COLORS[1]),
+        Brush::Paint(COLORS[2]),
+        Brush::Paint(COLORS[3]),
+        Brush::Paint(COLORS[4]),
+        Brush::Paint(COLORS[5]),
+        Brush::Paint(COLORS[6]),
+        Brush::Paint(COLORS[7]),
+    ];
+}
+
+impl fmt::Display for Brush {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Brush::Cycle => write!(f, "Cycle colors"),
+            Brush::Paint(color) => write!(f, "{color:?}"),
+        }
+    }
+}
+
+/// Colors the sticker `FACET_TABLE[sticker]` with `brush`.
+pub(crate) fn paint(cube: &mut Hypercube, sticker: usize, brush: Brush) {
+    let facet = &FACET_TABLE[sticker];
+    let color = &mut cube.pieces[facet.piece_slot].colors[facet.axis];
+    *color = Some(match brush {
+        Brush::Paint(new) => new,
+        Brush::Cycle => {
+            let current =
+                color.expect("FACET_TABLE entries are only built where colors[axis] is Some");
+            let index = COLORS.iter().position(|&c| c == current).unwrap();
+            COLORS[(index + 1) % COLORS.len()]
+        }
+    });
+}
+
+/// Why a painted state can't be reached by twisting. Pieces are named by
+/// the slot they're painted in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum InvalidState {
+    /// The piece has two stickers of one color, or of opposite cells'.
+    ImpossibleColors { slot: usize },
+    /// Two slots hold the same piece.
+    DuplicatePiece { slot: usize, other: usize },
+    /// The corner's colors go round the wrong way.
+    MirroredCorner { slot: usize },
+    /// The cell centers aren't where any rotation of the puzzle puts them
+    /// (they're a mirror image of such an arrangement).
+    MirroredCenters,
+    /// Two corners (or any odd number of pairs) are swapped.
+    OddCorners,
+    /// Faces and edges are permuted with different parities.
+    FaceEdgeParity,
+    /// The pieces of this type (2-4 stickers) are turned in place in a way
+    /// no twists can, e.g. one face piece flipped.
+    Twisted { facet_count: usize },
+}
+
+impl fmt::Display for InvalidState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = |slot: usize| piece_name(position_of(slot));
+        match self {
+            InvalidState::ImpossibleColors { slot } => {
+                write!(
+                    f,
+                    "The {} piece has colors no piece has together",
+                    name(*slot)
+                )
+            }
+            InvalidState::DuplicatePiece { slot, other } => {
+                write!(
+                    f,
+                    "The {} and {} pieces are the same piece",
+                    name(*slot),
+                    name(*other)
+                )
+            }
+            InvalidState::MirroredCorner { slot } => {
+                write!(
+                    f,
+                    "The {} corner is a mirror image of a real one",
+                    name(*slot)
+                )
+            }
+            InvalidState::MirroredCenters => {
+                write!(
+                    f,
+                    "The cell centers are a mirror image of a real arrangement"
+                )
+            }
+            InvalidState::OddCorners => write!(f, "Bad parity: two corners are swapped"),
+            InvalidState::FaceEdgeParity => {
+                write!(f, "Bad parity: two faces or two edges are swapped")
+            }
+            InvalidState::Twisted { facet_count } => {
+                let kind = ["faces", "edges", "corners"][facet_count - 2];
+                write!(f, "Bad parity: one of the {kind} is twisted in place")
+            }
+        }
+    }
+}
+
+impl std::error::Error for InvalidState {}
+
+/// Checks that twisting can reach `cube`, reporting the first thing wrong.
+pub(crate) fn validate(cube: &Hypercube) -> Result<(), InvalidState> {
+    for (slot, piece) in cube.pieces.iter().enumerate() {
+        let axes: Vec<usize> = piece
+            .colors
+            .iter()
+            .flatten()
+            .map(|&color| side_of_color(color).0)
+            .collect();
+        if (1..axes.len()).any(|i| axes[..i].contains(&axes[i])) {
+            return Err(InvalidState::ImpossibleColors { slot });
+        }
+    }
+
+    let mut owner = [None; 81];
+    for (slot, piece) in cube.pieces.iter().enumerate() {
+        let home = index_of(piece.home());
+        if let Some(other) = owner[home] {
+            return Err(InvalidState::DuplicatePiece {
+                slot: other,
+                other: slot,
+            });
+        }
+        owner[home] = Some(slot);
+    }
+
+    for slot in slots_with(4) {
+        let orientation = cube.pieces[slot].orientation();
+        if permutation_is_odd(&orientation.map(usize::from)) {
+            return Err(InvalidState::MirroredCorner { slot });
+        }
+    }
+
+    let mut cube = cube.clone();
+    for mv in center_reorientation(&cube).ok_or(InvalidState::MirroredCenters)? {
+        cube.apply_move(mv);
+    }
+
+    for facet_count in 2..=4 {
+        let twist: u32 = slots_with(facet_count)
+            .into_iter()
+            .map(|slot| {
+                u32::from(orientation_twist(
+                    cube.pieces[slot].orientation(),
+                    facet_count,
+                ))
+            })
+            .sum();
+        if !twist.is_multiple_of(u32::from(twist_modulus(facet_count))) {
+            return Err(InvalidState::Twisted { facet_count });
+        }
+    }
+    if permuted_oddly(&cube, 4) {
+        return Err(InvalidState::OddCorners);
+    }
+    if permuted_oddly(&cube, 2) != permuted_oddly(&cube, 3) {
+        return Err(InvalidState::FaceEdgeParity);
+    }
+    Ok(())
+}
+
+/// Whether the pieces with `facet_count` stickers are an odd permutation
+/// of their homes.
+fn permuted_oddly(cube: &Hypercube, facet_count: usize) -> bool {
+    let slots = slots_with(facet_count);
+    let homes: Vec<usize> = slots
+        .iter()
+        .map(|&slot| {
+            let home = index_of(cube.pieces[slot].home());
+            slots.iter().position(|&s| s == home).unwrap()
+        })
+        .collect();
+    permutation_is_odd(&homes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::moves::parse_moves;
+    use crate::piece::Piece;
+
+    /// The solved puzzle with the pieces in slots `a` and `b` exchanged,
+    /// each keeping its sticker order in its new slot's frame.
+    fn swapped(a: [i8; 4], b: [i8; 4]) -> Hypercube {
+        let mut cube = Hypercube::solved();
+        let orientation = crate::piece::IDENTITY_ORIENTATION;
+        cube.pieces[index_of(a)] = Piece::placed(b, a, orientation);
+        cube.pieces[index_of(b)] = Piece::placed(a, b, orientation);
+        cube
+    }
+
+    #[test]
+    fn reachable_states_are_valid() {
+        assert_eq!(validate(&Hypercube::solved()), Ok(()));
+        for seed in 0..5 {
+            let cube = Hypercube::random_state(&mut fastrand::Rng::with_seed(seed));
+            assert_eq!(validate(&cube), Ok(()));
+        }
+        // Moved cell centers are fine as long as a rotation moved them.
+        let mut cube = Hypercube::solved();
+        for mv in parse_moves("{2}RU {1-3}LB RUF").unwrap() {
+            cube.apply_move(mv);
+        }
+        assert_eq!(validate(&cube), Ok(()));
+    }
+
+    #[test]
+    fn painting_cycles_and_paints_stickers() {
+        let mut cube = Hypercube::solved();
+        let facet = &FACET_TABLE[0];
+        let start = cube.pieces[facet.piece_slot].colors[facet.axis].unwrap();
+        for _ in 0..COLORS.len() {
+            paint(&mut cube, 0, Brush::Cycle);
+            assert_ne!(cube, Hypercube::solved());
+            paint(&mut cube, 0, Brush::Paint(start));
+            assert_eq!(cube, Hypercube::solved());
+            paint(&mut cube, 0, Brush::Cycle);
+        }
+    }
+
+    #[test]
+    fn impossible_pieces_are_named() {
+        // A face piece with the opposite cell's color on its other sticker.
+        let slot = index_of([0, 1, 1, 0]);
+        let mut cube = Hypercube::solved();
+        cube.pieces[slot].colors[1] = Some(crate::piece::side_color(2, -1));
+        assert_eq!(
+            validate(&cube),
+            Err(InvalidState::ImpossibleColors { slot })
+        );
+        assert_eq!(
+            validate(&cube).unwrap_err().to_string(),
+            "The UR piece has colors no piece has together"
+        );
+
+        // The same piece painted in two slots.
+        let mut cube = Hypercube::solved();
+        cube.pieces[index_of([0, 1, 1, 0])] = Piece::placed(
+            [0, 1, -1, 0],
+            [0, 1, 1, 0],
+            crate::piece::IDENTITY_ORIENTATION,
+        );
+        assert_eq!(
+            validate(&cube),
+            Err(InvalidState::DuplicatePiece {
+                slot: index_of([0, 1, -1, 0]),
+                other: index_of([0, 1, 1, 0]),
+            })
+        );
+
+        // A corner with two stickers swapped.
+        let slot = index_of([1, 1, 1, 1]);
+        let mut cube = Hypercube::solved();
+        cube.pieces[slot].colors.swap(0, 1);
+        assert_eq!(validate(&cube), Err(InvalidState::MirroredCorner { slot }));
+
+        // Two opposite cell centers swapped: a reflection.
+        let mut cube = Hypercube::solved();
+        let (r, l) = (index_of([0, 0, 1, 0]), index_of([0, 0, -1, 0]));
+        let color = cube.pieces[r].colors;
+        cube.pieces[r].colors = cube.pieces[l].colors;
+        cube.pieces[l].colors = color;
+        assert_eq!(validate(&cube), Err(InvalidState::MirroredCenters));
+    }
+
+    #[test]
+    fn parity_and_twist_are_checked() {
+        let cube = swapped([1, 1, 1, 1], [1, 1, 1, -1]);
+        assert_eq!(validate(&cube), Err(InvalidState::OddCorners));
+        let cube = swapped([0, 1, 1, 0], [0, 1, -1, 0]);
+        assert_eq!(validate(&cube), Err(InvalidState::FaceEdgeParity));
+
+        let slot = index_of([0, 1, 1, 0]);
+        let mut cube = Hypercube::solved();
+        cube.pieces[slot].colors.swap(1, 2);
+        assert_eq!(
+            validate(&cube),
+            Err(InvalidState::Twisted { facet_count: 2 })
+        );
+        assert_eq!(
+            validate(&cube).unwrap_err().to_string(),
+            "Bad parity: one of the faces is twisted in place"
+        );
+    }
+}
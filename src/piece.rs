@@ -26,10 +26,151 @@ impl Piece {
     /// Number of nonzero axes in `position`, i.e. how many stickers this
     /// piece has: 0 = invisible center, 1 = cell-center, 2 = face, 3 = edge,
     /// 4 = corner.
-    #[cfg(test)]
     pub(crate) fn facet_count(&self) -> u8 {
         self.position.iter().filter(|c| **c != 0).count() as u8
     }
+
+    /// Where this piece sits in the solved puzzle, read off its colors.
+    pub(crate) fn home(&self) -> [i8; 4] {
+        let mut home = [0; 4];
+        for color in self.colors.iter().flatten() {
+            let (axis, sign) = side_of_color(*color);
+            home[axis] = sign;
+        }
+        home
+    }
+
+    /// How this piece is turned relative to how it sits at home: entry `j`
+    /// is the index, in its current slot's `frame`, of the axis its
+    /// sticker from axis `frame(home)[j]` now faces. Only the first
+    /// `facet_count` entries are meaningful; the rest stay put.
+    ///
+    /// Measured against per-slot frames rather than raw axes so that every
+    /// piece type's orientations form a fixed group - the symmetric group
+    /// on 2 or 3 stickers, and the alternating group on 4 (a corner's
+    /// stickers can only be turned, never mirrored) - and a move's effect
+    /// on a piece is a group element applied on the left.
+    pub(crate) fn orientation(&self) -> Orientation {
+        let home = self.home();
+        let home_frame = frame(home);
+        let frame = frame(self.position);
+        let mut orientation = IDENTITY_ORIENTATION;
+        for j in 0..self.facet_count() as usize {
+            let color = side_color(home_frame[j], home[home_frame[j]]);
+            let axis = (0..4)
+                .find(|&axis| self.colors[axis] == Some(color))
+                .expect("piece carries a sticker of every home color");
+            orientation[j] = frame.iter().position(|&a| a == axis).unwrap() as u8;
+        }
+        orientation
+    }
+
+    /// The piece from `home`, sitting at `position` (of the same piece
+    /// type) turned by `orientation` - the inverse of `home`/`orientation`.
+    pub(crate) fn placed(home: [i8; 4], position: [i8; 4], orientation: Orientation) -> Self {
+        let home_frame = frame(home);
+        let frame = frame(position);
+        let mut colors = [None; 4];
+        for j in 0..home.iter().filter(|c| **c != 0).count() {
+            colors[frame[orientation[j] as usize]] =
+                Some(side_color(home_frame[j], home[home_frame[j]]));
+        }
+        Self { position, colors }
+    }
+}
+
+/// A piece's orientation: a permutation of its sticker indices, see
+/// `Piece::orientation`.
+pub(crate) type Orientation = [u8; 4];
+
+pub(crate) const IDENTITY_ORIENTATION: Orientation = [0, 1, 2, 3];
+
+/// Applies `first`, then `then`.
+pub(crate) fn compose_orientations(first: Orientation, then: Orientation) -> Orientation {
+    first.map(|j| then[j as usize])
+}
+
+/// Reference frame of a slot: its nonzero axes in a fixed order, the order
+/// `Piece::orientation` numbers stickers in. Ascending, except that a
+/// corner whose coordinates multiply to -1 swaps its first two axes, so
+/// that a corner's orientation always comes out an even permutation.
+fn frame(position: [i8; 4]) -> [usize; 4] {
+    let mut frame = [0; 4];
+    let mut len = 0;
+    for (axis, _) in position.iter().enumerate().filter(|(_, c)| **c != 0) {
+        frame[len] = axis;
+        len += 1;
+    }
+    for (slot, axis) in (len..4).zip((0..4).filter(|&axis| position[axis] == 0)) {
+        frame[slot] = axis;
+    }
+    if len == 4 && position.iter().product::<i8>() < 0 {
+        frame.swap(0, 1);
+    }
+    frame
+}
+
+/// Every orientation a piece with `facet_count` stickers can have: all
+/// permutations of 2 or 3 stickers, the even ones of 4.
+pub(crate) fn orientations(facet_count: usize) -> Vec<Orientation> {
+    (0..facet_count.pow(facet_count as u32))
+        .map(|code| {
+            let mut orientation = IDENTITY_ORIENTATION;
+            for (j, entry) in orientation.iter_mut().take(facet_count).enumerate() {
+                *entry = (code / facet_count.pow(j as u32) % facet_count) as u8;
+            }
+            orientation
+        })
+        .filter(|o| (0..facet_count).all(|j| o[..facet_count].contains(&(j as u8))))
+        .filter(|o| facet_count < 4 || !permutation_is_odd(&o.map(usize::from)))
+        .collect()
+}
+
+/// The part of an orientation that moves can't change on one piece alone:
+/// summed over every piece of a type, it's always 0 mod `TWIST_MODULUS` -
+/// the 4D analogue of a 3x3x3's corner twist and edge flip. A face piece
+/// is flipped or not; an edge piece's stickers are in an odd order or not;
+/// a corner's is which of the three ways of pairing up its four stickers
+/// its first two stickers land in (the quotient of its even permutations
+/// by the double transpositions).
+pub(crate) fn orientation_twist(orientation: Orientation, facet_count: usize) -> u8 {
+    match facet_count {
+        2 => u8::from(orientation[0] != 0),
+        3 => u8::from(permutation_is_odd(&[
+            usize::from(orientation[0]),
+            usize::from(orientation[1]),
+            usize::from(orientation[2]),
+        ])),
+        4 => match orientation[0] + orientation[1] {
+            // Pairs {0,1}/{2,3} sum to 1 or 5, {0,2}/{1,3} to 2 or 4,
+            // {0,3}/{1,2} to 3.
+            1 | 5 => 0,
+            2 | 4 => 1,
+            _ => 2,
+        },
+        _ => 0,
+    }
+}
+
+/// Modulus `orientation_twist` sums are taken in, by `facet_count`.
+pub(crate) fn twist_modulus(facet_count: usize) -> u8 {
+    if facet_count == 4 { 3 } else { 2 }
+}
+
+/// Whether a permutation (as a list of images) has odd parity.
+pub(crate) fn permutation_is_odd(images: &[usize]) -> bool {
+    let mut seen = vec![false; images.len()];
+    let mut odd = false;
+    for start in 0..images.len() {
+        seen[start] = true;
+        let mut at = images[start];
+        while !seen[at] {
+            seen[at] = true;
+            at = images[at];
+            odd = !odd;
+        }
+    }
+    odd
 }
 
 /// The complete piece-based puzzle state: always exactly 81 pieces (80
@@ -60,7 +201,7 @@ pub struct StickerInstance {
 }
 
 /// Colors for the 8 sides of the puzzle, indexed by `face_id_for`.
-const COLORS: [Color; 8] = [
+pub(crate) const COLORS: [Color; 8] = [
     // center
     Color::Cyan,
     // left
@@ -101,7 +242,7 @@ pub(crate) fn face_id_for(axis: usize, sign: i8) -> usize {
 /// `build_facet_table` iterates faces in this order so `FACET_TABLE` comes
 /// out grouped into 8 contiguous per-`face_id` blocks of 27 —
 /// `renderer.rs`'s per-face draw calls depend on that grouping.
-const FACE_AXIS_SIGN: [(usize, i8); 8] = [
+pub(crate) const FACE_AXIS_SIGN: [(usize, i8); 8] = [
     (3, -1),
     (2, -1),
     (1, -1),
@@ -117,6 +258,15 @@ pub(crate) fn side_color(axis: usize, sign: i8) -> Color {
     COLORS[face_id_for(axis, sign)]
 }
 
+/// Inverse of `side_color`: the side whose home color `color` is.
+pub(crate) fn side_of_color(color: Color) -> (usize, i8) {
+    let face_id = COLORS
+        .iter()
+        .position(|&c| c == color)
+        .expect("every color belongs to a side");
+    FACE_AXIS_SIGN[face_id]
+}
+
 /// The 3 axes other than `fixed`, in ascending order.
 pub(crate) fn free_axes(fixed: usize) -> [usize; 3] {
     let mut out = [0usize; 3];
@@ -155,6 +305,13 @@ pub(crate) fn position_of(mut index: usize) -> [i8; 4] {
     pos
 }
 
+/// Slots of the pieces with `facet_count` stickers, in `index_of` order.
+pub(crate) fn slots_with(facet_count: usize) -> Vec<usize> {
+    (0..81)
+        .filter(|&slot| position_of(slot).iter().filter(|c| **c != 0).count() == facet_count)
+        .collect()
+}
+
 impl Hypercube {
     /// Builds the solved puzzle: all 81 lattice positions, each piece's
     /// colors matching `side_color` for every axis where its position is
@@ -180,18 +337,72 @@ impl Hypercube {
         Self { pieces }
     }
 
-    /// True iff every piece's colors match its position's home-side colors,
-    /// i.e. no piece has ever been moved out of its solved orientation.
-    #[cfg(test)]
+    /// True iff every cell shows a single color - solved up to a symmetry
+    /// of the tesseract, not only in the home colors `solved()` paints, so
+    /// a puzzle solved while reoriented still counts.
     pub(crate) fn is_solved(&self) -> bool {
-        self.pieces.iter().all(|p| {
-            (0..4).all(|axis| match (p.position[axis], p.colors[axis]) {
-                (0, None) => true,
-                (n, Some(c)) if n != 0 => c == side_color(axis, n),
-                _ => false,
-            })
+        FACE_AXIS_SIGN.iter().all(|&(axis, sign)| {
+            let mut colors = self
+                .pieces
+                .iter()
+                .filter(|p| p.position[axis] == sign)
+                .map(|p| p.colors[axis]);
+            let first = colors.next().flatten();
+            colors.all(|c| c == first)
         })
     }
+
+    /// A state drawn uniformly from every state twisting can reach, built
+    /// directly rather than by random moves (a random walk of any practical
+    /// length still favors states near where it started).
+    ///
+    /// Cell centers never move. The other pieces can be arranged and turned
+    /// any way within their type except as these invariants rule out:
+    /// corners are only ever evenly permuted, faces and edges are permuted
+    /// with the same parity (a quarter turn is odd on both), and each
+    /// type's `orientation_twist`s sum to 0. Each choice is drawn uniformly
+    /// and the last one adjusted to fit, which keeps the draw uniform.
+    pub(crate) fn random_state(rng: &mut fastrand::Rng) -> Self {
+        let mut cube = Self::solved();
+        let mut faces_odd = false;
+        for facet_count in 2..=4 {
+            let slots = slots_with(facet_count);
+            let last = slots.len() - 1;
+            let mut homes: Vec<usize> = (0..slots.len()).collect();
+            rng.shuffle(&mut homes);
+            let odd = permutation_is_odd(&homes);
+            let should_be_odd = match facet_count {
+                2 => {
+                    faces_odd = odd;
+                    odd
+                }
+                3 => faces_odd,
+                _ => false,
+            };
+            if odd != should_be_odd {
+                homes.swap(last - 1, last);
+            }
+
+            let orientations = orientations(facet_count);
+            let modulus = twist_modulus(facet_count);
+            let mut twist = 0;
+            for (i, (&slot, &home)) in slots.iter().zip(&homes).enumerate() {
+                let fitting: Vec<Orientation> = orientations
+                    .iter()
+                    .copied()
+                    .filter(|&o| {
+                        i < last
+                            || (twist + orientation_twist(o, facet_count)).is_multiple_of(modulus)
+                    })
+                    .collect();
+                let orientation = fitting[rng.usize(..fitting.len())];
+                twist = (twist + orientation_twist(orientation, facet_count)) % modulus;
+                cube.pieces[slot] =
+                    Piece::placed(position_of(slots[home]), position_of(slot), orientation);
+            }
+        }
+        cube
+    }
 }
 
 /// Total number of rendered facets: sum of `facet_count()` over all 81
@@ -361,6 +572,109 @@ mod tests {
         assert!(!cube.is_solved());
     }
 
+    #[test]
+    fn reoriented_solved_puzzle_is_solved() {
+        // Whole-puzzle quarter turn in the x-y plane: x -> y, y -> -x.
+        let solved = Hypercube::solved();
+        let mut reoriented = solved.clone();
+        for piece in &solved.pieces {
+            let [x, y, z, w] = piece.position;
+            let [cx, cy, cz, cw] = piece.colors;
+            let position = [-y, x, z, w];
+            reoriented.pieces[index_of(position)] = Piece {
+                position,
+                colors: [cy, cx, cz, cw],
+            };
+        }
+        assert_ne!(reoriented, solved);
+        assert!(reoriented.is_solved());
+    }
+
+    #[test]
+    fn orientation_round_trips_through_placed() {
+        let mut cube = Hypercube::solved();
+        cube.apply_random_moves(40, &mut fastrand::Rng::with_seed(3));
+        for piece in cube.pieces.iter().filter(|p| p.facet_count() >= 2) {
+            assert_eq!(
+                Piece::placed(piece.home(), piece.position, piece.orientation()),
+                *piece
+            );
+        }
+        for piece in &Hypercube::solved().pieces {
+            assert_eq!(piece.home(), piece.position);
+            assert_eq!(piece.orientation(), IDENTITY_ORIENTATION);
+        }
+    }
+
+    #[test]
+    fn orientations_of_each_piece_type() {
+        assert_eq!(orientations(2).len(), 2);
+        assert_eq!(orientations(3).len(), 6);
+        assert_eq!(orientations(4).len(), 12);
+    }
+
+    /// Permutation parities and twist sums of every type, as `random_state`
+    /// constrains them.
+    fn invariants(cube: &Hypercube) -> Vec<(bool, u8)> {
+        (2..=4)
+            .map(|facet_count| {
+                let slots = slots_with(facet_count);
+                let homes: Vec<usize> = slots
+                    .iter()
+                    .map(|&slot| {
+                        let home = index_of(cube.pieces[slot].home());
+                        slots.iter().position(|&s| s == home).unwrap()
+                    })
+                    .collect();
+                let twist = slots
+                    .iter()
+                    .map(|&slot| orientation_twist(cube.pieces[slot].orientation(), facet_count))
+                    .sum::<u8>()
+                    % twist_modulus(facet_count);
+                (permutation_is_odd(&homes), twist)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn moves_preserve_the_invariants_random_state_keeps() {
+        let mut rng = fastrand::Rng::with_seed(4);
+        let mut cube = Hypercube::solved();
+        for _ in 0..50 {
+            cube.apply_random_moves(1, &mut rng);
+            let [faces, edges, corners] = invariants(&cube)[..] else {
+                unreachable!()
+            };
+            assert_eq!(faces.0, edges.0, "face and edge parity differ");
+            assert!(!corners.0, "odd corner permutation");
+            assert_eq!([faces.1, edges.1, corners.1], [0, 0, 0]);
+        }
+    }
+
+    #[test]
+    fn random_state_is_a_legal_arrangement_of_every_piece() {
+        let mut rng = fastrand::Rng::with_seed(5);
+        let mut faces_odd = [false; 2];
+        for _ in 0..100 {
+            let cube = Hypercube::random_state(&mut rng);
+            let mut homes: Vec<usize> = cube.pieces.iter().map(|p| index_of(p.home())).collect();
+            homes.sort_unstable();
+            assert_eq!(
+                homes,
+                (0..81).collect::<Vec<_>>(),
+                "every piece exactly once"
+            );
+            let [faces, edges, corners] = invariants(&cube)[..] else {
+                unreachable!()
+            };
+            assert_eq!(faces.0, edges.0);
+            assert!(!corners.0);
+            assert_eq!([faces.1, edges.1, corners.1], [0, 0, 0]);
+            faces_odd[usize::from(faces.0)] = true;
+        }
+        assert_eq!(faces_odd, [true, true], "both parities turn up");
+    }
+
     #[test]
     fn center_piece_has_no_facets() {
         let cube = Hypercube::solved();
@@ -0,0 +1,173 @@
+//! Playback: watching a move list (a scramble, a log, an algorithm) turn
+//! through the move animation one move at a time, with play/pause/step and
+//! a speed.
+//!
+//! The moves are handed to the shader widget as a `Replay` with them
+//! waiting to be redone, so stepping forward is Redo and stepping back is
+//! Undo, animated and recorded like any other; `HypercubeApp` paces the
+//! Redos from frame ticks while playing. Anything that changes the history
+//! some other way (a twist mid-playback drops the moves still to play)
+//! ends it.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::history::MoveHistory;
+use crate::moves::Move;
+
+/// Slowest and fastest playback speeds, as multiples of the normal
+/// animation speed.
+pub(crate) const SPEED_RANGE: std::ops::RangeInclusive<f32> = 0.25..=4.0;
+/// Time from one played move's start to the next's, in move animations:
+/// a beat between moves, to follow each one.
+const BEAT: f32 = 1.5;
+
+/// What's being played, and what happens once it's all played.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum PlaybackKind {
+    /// A new scramble, played from solved. Once played it's the scramble,
+    /// and inspection starts.
+    Scramble { seed: u64 },
+    /// Random moves, played on the puzzle as it was. Once played they join
+    /// the scramble, like instant random moves; `seed` is kept if they
+    /// started from solved.
+    RandomMoves { seed: Option<u64> },
+    /// An imported log or a recorded solve, from its scramble.
+    Log,
+    /// A saved algorithm (by name), played on the puzzle as it was.
+    Algorithm(String),
+}
+
+impl fmt::Display for PlaybackKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlaybackKind::Scramble { .. } => write!(f, "Scramble"),
+            PlaybackKind::RandomMoves { .. } => write!(f, "Random moves"),
+            PlaybackKind::Log => write!(f, "Log"),
+            PlaybackKind::Algorithm(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+/// A move list being played, tracked against the history it was loaded
+/// into.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Playback {
+    pub(crate) kind: PlaybackKind,
+    moves: Vec<Move>,
+    /// How many moves the history had applied before the first played one.
+    start: usize,
+    playing: bool,
+    /// When the next move is due while playing; `None` until the first
+    /// tick after (re)starting, which schedules it a beat later.
+    next_step: Option<Instant>,
+}
+
+impl Playback {
+    /// Playback of `moves`, loaded after `start` applied moves.
+    pub(crate) fn new(kind: PlaybackKind, moves: Vec<Move>, start: usize, playing: bool) -> Self {
+        Self {
+            kind,
+            moves,
+            start,
+            playing,
+            next_step: None,
+        }
+    }
+
+    pub(crate) fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    pub(crate) fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub(crate) fn set_playing(&mut self, playing: bool) {
+        self.playing = playing;
+        self.next_step = None;
+    }
+
+    /// How many of the moves `history` has played, or `None` if it no
+    /// longer holds them (what's applied since `start` isn't a prefix of
+    /// them, or the rest aren't waiting to be redone).
+    pub(crate) fn progress(&self, history: &MoveHistory) -> Option<usize> {
+        let played = history.applied().get(self.start..)?;
+        let waiting = history.can_redo() || played.len() == self.moves.len();
+        (self.moves.starts_with(played) && waiting).then_some(played.len())
+    }
+
+    /// While playing, whether the next move is due at `now`, moves taking
+    /// `move_duration` to turn; each `true` schedules the one after.
+    pub(crate) fn step_due(&mut self, now: Instant, move_duration: Duration) -> bool {
+        if !self.playing {
+            return false;
+        }
+        let beat = move_duration.mul_f32(BEAT);
+        match self.next_step {
+            Some(at) if now < at => false,
+            Some(_) => {
+                self.next_step = Some(now + beat);
+                true
+            }
+            None => {
+                self.next_step = Some(now + beat);
+                false
+            }
+        }
+    }
+}
+
+/// `duration_ms` at playback `speed`.
+pub(crate) fn scaled_duration_ms(duration_ms: u32, speed: f32) -> u32 {
+    (duration_ms as f32 / speed).round() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::{MoveLog, Replay};
+    use crate::moves::parse_moves;
+
+    #[test]
+    fn progress_follows_the_history_it_was_loaded_into() {
+        let before = parse_moves("RU").unwrap();
+        let moves = parse_moves("LB FUR OBR'").unwrap();
+        let playback = Playback::new(PlaybackKind::Log, moves.clone(), 1, true);
+        let from = MoveLog {
+            moves: before.clone(),
+            ..MoveLog::default()
+        };
+        let mut history = Replay::continuing(from, &moves).history;
+        assert_eq!(playback.progress(&history), Some(0));
+        history.redo();
+        history.redo();
+        assert_eq!(playback.progress(&history), Some(2));
+        history.redo();
+        assert_eq!(playback.progress(&history), Some(3));
+
+        // Stepping back past the start, or twisting, ends it.
+        let mut undone = history.clone();
+        for _ in 0..4 {
+            undone.undo();
+        }
+        assert_eq!(playback.progress(&undone), None);
+        history.undo();
+        history.push(before[0]);
+        assert_eq!(playback.progress(&history), None);
+    }
+
+    #[test]
+    fn steps_come_a_beat_apart_while_playing() {
+        let mut playback = Playback::new(PlaybackKind::Log, Vec::new(), 0, false);
+        let now = Instant::now();
+        let second = Duration::from_secs(1);
+        assert!(!playback.step_due(now, second), "paused");
+        playback.set_playing(true);
+        assert!(!playback.step_due(now, second), "a beat first");
+        assert!(!playback.step_due(now + second, second));
+        assert!(playback.step_due(now + second.mul_f32(BEAT), second));
+        assert!(!playback.step_due(now + second * 2, second));
+        assert!(playback.step_due(now + second * 3, second));
+    }
+}
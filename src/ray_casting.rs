@@ -9,7 +9,7 @@ use nalgebra::{Matrix4, Point3, Vector3, Vector4};
 
 use crate::app::AABBMode;
 use crate::camera::{Camera, Projection};
-use crate::geometry::NORMAL_TO_BASE_INDICES;
+use crate::geometry::{BASE_CUBE_VERTICES, NORMAL_TO_BASE_INDICES};
 use crate::math::{
     BASE_STICKER_SIZE, GRID_EXTENT, face_push_offset_3d, is_face_visible, project_cube_point,
     transform_sticker_vertices_to_3d,
@@ -89,6 +89,28 @@ pub(crate) fn calculate_mouse_ray(
     }
 }
 
+/// Project a 3D scene point to widget-local screen coordinates: the inverse
+/// of `calculate_mouse_ray`. `None` for points behind the camera.
+pub(crate) fn project_to_screen(
+    point: Point3<f32>,
+    bounds: Rectangle,
+    camera: &Camera,
+    projection: &Projection,
+) -> Option<Point> {
+    let view_proj_matrix = projection.build_projection_matrix() * camera.build_view_matrix();
+    let clip = view_proj_matrix * point.to_homogeneous();
+    if clip.w <= 0.0 {
+        return None;
+    }
+
+    let ndc_x = clip.x / clip.w;
+    let ndc_y = clip.y / clip.w;
+    Some(Point::new(
+        (ndc_x + 1.0) * bounds.width / 2.0,
+        (1.0 - ndc_y) * bounds.height / 2.0,
+    ))
+}
+
 /// Test ray intersection with 3D axis-aligned bounding box using the slab method
 ///
 /// Returns Some(distance) if the ray intersects the box, None otherwise.
@@ -126,10 +148,15 @@ pub(crate) fn ray_intersects_aabb(ray: &Ray, aabb: &AABB) -> bool {
 }
 
 /// Test ray intersection with actual sticker geometry using transformed vertices
-/// Returns Some(distance) if ray intersects any triangle of the sticker
-fn ray_sticker_intersection(ray: &Ray, world_vertices: &[Point3<f32>]) -> Option<f32> {
+/// Returns Some((distance, point)) if ray intersects any triangle of the
+/// sticker, where `point` is the nearest hit in the unit cube
+/// `BASE_CUBE_VERTICES` spans (interpolated within the hit triangle)
+fn ray_sticker_intersection(
+    ray: &Ray,
+    world_vertices: &[Point3<f32>],
+) -> Option<(f32, Vector3<f32>)> {
     let mut closest_distance = f32::INFINITY;
-    let mut hit = false;
+    let mut closest_point = None;
 
     // Test ray against each triangle (36 vertices = 12 triangles)
     for triangle_vertices in NORMAL_TO_BASE_INDICES.chunks(3) {
@@ -140,25 +167,28 @@ fn ray_sticker_intersection(ray: &Ray, world_vertices: &[Point3<f32>]) -> Option
         let v2 = world_vertices[triangle_vertices[2]];
         let v2 = Point3::new(v2[0], v2[1], v2[2]);
 
-        if let Some(distance) = ray_triangle_intersection(ray, v0, v1, v2)
+        if let Some((distance, u, v)) = ray_triangle_intersection(ray, v0, v1, v2)
             && distance < closest_distance
         {
             closest_distance = distance;
-            hit = true;
+            let [b0, b1, b2] =
+                [0, 1, 2].map(|i| Vector3::from(BASE_CUBE_VERTICES[triangle_vertices[i]]));
+            closest_point = Some(b0 * (1.0 - u - v) + b1 * u + b2 * v);
         }
     }
 
-    if hit { Some(closest_distance) } else { None }
+    closest_point.map(|point| (closest_distance, point))
 }
 
 /// Test ray intersection with a triangle using Möller-Trumbore algorithm
-/// Returns Some(distance) if ray intersects the triangle
+/// Returns Some((distance, u, v)) if ray intersects the triangle, `u`/`v`
+/// being the hit's barycentric weights of `v1`/`v2`
 fn ray_triangle_intersection(
     ray: &Ray,
     v0: Point3<f32>,
     v1: Point3<f32>,
     v2: Point3<f32>,
-) -> Option<f32> {
+) -> Option<(f32, f32, f32)> {
     const EPSILON: f32 = 1e-8;
 
     // Calculate triangle edges from v0
@@ -199,7 +229,7 @@ fn ray_triangle_intersection(
 
     // Return distance if intersection is in front of ray origin
     if t > EPSILON {
-        Some(t)
+        Some((t, u, v))
     } else {
         None // Intersection is behind ray origin
     }
@@ -238,7 +268,7 @@ fn calculate_face_aabb(
     gap_distance: f32,
     viewer_distance: f32,
 ) -> AABB {
-    use crate::geometry::{BASE_CUBE_VERTICES, FACE_CENTERS, FIXED_DIMS};
+    use crate::geometry::{FACE_CENTERS, FIXED_DIMS};
 
     // Get face center and orientation info
     let face_center_4d = FACE_CENTERS[face_id];
@@ -308,8 +338,18 @@ fn get_face_debug_color(face_id: usize) -> [f32; 4] {
     }
 }
 
+/// Where a mouse ray meets a sticker.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct StickerHit {
+    /// Index into `FACET_TABLE`.
+    pub(crate) sticker: usize,
+    /// The hit's offset from the sticker's center along its `basis`, i.e.
+    /// in the same local units as the sticker's 4D position.
+    pub(crate) offset: Vector3<f32>,
+}
+
 /// Find the sticker that the 3D mouse ray intersects
-/// Returns the sticker index and debug AABBs for intersected faces/stickers
+/// Returns the hit and debug AABBs for intersected faces/stickers
 pub(crate) fn find_intersected_sticker(
     ray: &Ray,
     state: &HypercubeShaderState,
@@ -317,7 +357,7 @@ pub(crate) fn find_intersected_sticker(
     gap_distance: f32,
     viewer_distance: f32,
     aabb_mode: AABBMode,
-) -> (Option<usize>, Vec<DebugInstanceWithDistance>) {
+) -> (Option<StickerHit>, Vec<DebugInstanceWithDistance>) {
     let camera_pos = [state.camera.eye.x, state.camera.eye.y, state.camera.eye.z];
 
     // First, determine which faces are visible and ray-intersectable
@@ -384,12 +424,15 @@ pub(crate) fn find_intersected_sticker(
             }
 
             // Second check: Actual sticker geometry intersection (accurate)
-            if let Some(distance) = ray_sticker_intersection(ray, &world_vertices)
+            if let Some((distance, point)) = ray_sticker_intersection(ray, &world_vertices)
                 && distance < closest_distance
             {
                 closest_distance = distance;
                 closest_sticker = if sticker.is_actionable {
-                    Some(sticker_index)
+                    Some(StickerHit {
+                        sticker: sticker_index,
+                        offset: point * BASE_STICKER_SIZE * sticker_scale,
+                    })
                 } else {
                     // Don't highlight the center piece. No actions can be performed on it.
                     None
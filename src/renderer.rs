@@ -15,6 +15,7 @@ use wgpu::util::DeviceExt;
 use crate::app::RenderMode;
 use crate::camera::{Camera, CameraUniform, Projection};
 use crate::geometry::{CUBE_VERTICES, VERTEX_NORMAL_INDICES};
+use crate::hint::Click;
 use crate::math::{BASE_STICKER_SIZE, VIEWER_DISTANCE};
 use crate::piece::{FACET_TABLE, Hypercube, StickerInstance, generate_sticker_instances};
 use crate::shader_widget::UiControls;
@@ -139,8 +140,25 @@ pub(crate) struct HighlightingUniform {
     highlight_color: [f32; 4],
     /// Color and intensity (in `a`) for the rest of the hovered piece's stickers
     piece_highlight_color: [f32; 4],
+    /// `Hypercube::pieces` slots to mark, as a bitmask (bit `s % 32` of word `s / 32`)
+    highlighted_slots: [u32; 4],
+    /// Color and intensity (in `a`) for the stickers of pieces in `highlighted_slots`
+    slot_highlight_color: [f32; 4],
+    /// Index of the sticker hint mode says to click next (u32::MAX if none)
+    hint_sticker_index: u32,
+    /// Padding for vec4 alignment
+    _padding2: [u32; 3],
+    /// Color and intensity (in `a`) for the hint sticker: `HINT_CLOCKWISE_COLOR`
+    /// for a plain click, `HINT_COUNTERCLOCKWISE_COLOR` for a Shift-click
+    hint_color: [f32; 4],
 }
 
+/// Hint sticker color for a plain (clockwise) click: green, 60% intensity.
+const HINT_CLOCKWISE_COLOR: [f32; 4] = [0.1, 1.0, 0.3, 0.6];
+/// Hint sticker color for a Shift (counterclockwise) click: magenta, 60%
+/// intensity.
+const HINT_COUNTERCLOCKWISE_COLOR: [f32; 4] = [1.0, 0.2, 0.9, 0.6];
+
 /// Debug instance data for GPU vertex attributes (transparent bounding box rendering)
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
@@ -403,6 +421,11 @@ impl Renderer {
             _padding: [0; 2],
             highlight_color: [1.0, 1.0, 0.0, 0.3], // Yellow, 30% intensity
             piece_highlight_color: [0.2, 0.2, 0.2, 0.6], // Gray, 60% intensity
+            highlighted_slots: [0; 4],             // No slots marked
+            slot_highlight_color: [0.0, 0.8, 1.0, 0.35], // Cyan, 35% intensity
+            hint_sticker_index: u32::MAX,          // No hint shown
+            _padding2: [0; 3],
+            hint_color: HINT_CLOCKWISE_COLOR,
         };
 
         let highlighting_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -1335,15 +1358,20 @@ impl Renderer {
     }
 
     /// Updates the highlighting uniform buffer with the currently hovered
-    /// sticker and its owning piece (looked up via `FACET_TABLE`).
+    /// sticker and its owning piece (looked up via `FACET_TABLE`), the
+    /// marked piece slots, and hint mode's next click.
     ///
     /// # Arguments
     /// * `queue` - GPU command queue for buffer updates
     /// * `hovered_sticker_index` - Index of the sticker being hovered (None if no hover)
+    /// * `highlighted_slots` - Bitmask of piece slots to mark, e.g. those an algorithm moves
+    /// * `hint` - The sticker to click next and which way, colored by direction
     pub(crate) fn update_highlighting(
         &mut self,
         queue: &Queue,
         hovered_sticker_index: Option<usize>,
+        highlighted_slots: [u32; 4],
+        hint: Option<Click>,
     ) {
         self.highlighting_uniform.hovered_sticker_index = hovered_sticker_index
             .map(|index| index as u32)
@@ -1351,6 +1379,16 @@ impl Renderer {
         self.highlighting_uniform.hovered_piece_slot = hovered_sticker_index
             .map(|index| FACET_TABLE[index].piece_slot as u32)
             .unwrap_or(u32::MAX);
+        self.highlighting_uniform.highlighted_slots = highlighted_slots;
+        self.highlighting_uniform.hint_sticker_index =
+            hint.map(|click| click.sticker as u32).unwrap_or(u32::MAX);
+        if let Some(click) = hint {
+            self.highlighting_uniform.hint_color = if click.counterclockwise {
+                HINT_COUNTERCLOCKWISE_COLOR
+            } else {
+                HINT_CLOCKWISE_COLOR
+            };
+        }
 
         queue.write_buffer(
             &self.highlighting_buffer,
@@ -0,0 +1,632 @@
+//! Shortest move sequences with a given effect on chosen pieces, for
+//! discovering algorithms: iterative-deepening A* (IDA*) over just those
+//! pieces, pruned by pattern databases.
+//!
+//! Only the tracked pieces count. A sequence is a solution if it leaves each
+//! of them where, and turned how, the target does, whatever it does to the
+//! rest - so a target that 3-cycles three corners, with every corner
+//! tracked and nothing else, finds corner 3-cycles that may scramble faces
+//! and edges.
+//!
+//! A tracked piece's state is a coordinate: its slot and the index of its
+//! orientation among `piece::orientations`. Pattern databases are
+//! breadth-first searches over the coordinates of two tracked pieces at a
+//! time, from their targets; the most moves any of them says are left is a
+//! lower bound for the whole, which prunes the depth-first search below
+//! each depth. Building them can take a while, so it reports progress and
+//! stops early if the search is cancelled. Each depth's first moves are
+//! shared out between worker threads, which stop early the same way.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+
+use crate::moves::{Move, all_moves, cell_for_letter, commute};
+use crate::piece::{
+    Hypercube, IDENTITY_ORIENTATION, Orientation, compose_orientations, index_of, orientations,
+    position_of, slots_with,
+};
+
+/// Orientations a piece can have, at most (a corner's 12).
+const ORIENTATIONS: usize = 12;
+/// Number of piece coordinates: slot times `ORIENTATIONS`, plus orientation.
+const COORDS: usize = 81 * ORIENTATIONS;
+const UNREACHED: u8 = u8::MAX;
+/// How many coordinates a pattern database build expands between checks
+/// for cancellation.
+const CANCEL_CHECK_INTERVAL: usize = 4096;
+
+/// What a running search has to say, in the order it happens.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum SearchEvent {
+    /// `built` of the `tables` pattern databases are done.
+    Tables {
+        built: usize,
+        tables: usize,
+    },
+    /// Every sequence of `depth` moves is being tried.
+    Depth(usize),
+    /// One more of this depth's first moves has been searched below.
+    Progress {
+        depth: usize,
+        branches_done: usize,
+        branches: usize,
+        nodes: u64,
+    },
+    Solution(Vec<Move>),
+    /// Every sequence of `depth` moves was tried; `solutions` work.
+    DepthDone {
+        depth: usize,
+        solutions: usize,
+    },
+    Finished {
+        cancelled: bool,
+    },
+}
+
+/// Which pieces a search tracks, by type.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum TrackedPieces {
+    Faces,
+    Edges,
+    #[default]
+    Corners,
+    All,
+}
+
+impl TrackedPieces {
+    pub(crate) const ALL: [TrackedPieces; 4] = [
+        TrackedPieces::Faces,
+        TrackedPieces::Edges,
+        TrackedPieces::Corners,
+        TrackedPieces::All,
+    ];
+
+    /// Home slots of the pieces tracked.
+    pub(crate) fn slots(self) -> Vec<usize> {
+        match self {
+            TrackedPieces::Faces => slots_with(2),
+            TrackedPieces::Edges => slots_with(3),
+            TrackedPieces::Corners => slots_with(4),
+            TrackedPieces::All => (1..=4).flat_map(slots_with).collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for TrackedPieces {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TrackedPieces::Faces => write!(f, "Faces"),
+            TrackedPieces::Edges => write!(f, "Edges"),
+            TrackedPieces::Corners => write!(f, "Corners"),
+            TrackedPieces::All => write!(f, "All pieces"),
+        }
+    }
+}
+
+/// The outer twists of the cells named in `cells` (letters as in move
+/// notation, e.g. `IL`; spaces ignored), or of every cell if it's blank.
+/// `Err` holds the first character that isn't a cell name.
+pub(crate) fn twists_of_cells(cells: &str) -> Result<Vec<Move>, char> {
+    let mut sides = Vec::new();
+    for letter in cells.chars().filter(|c| !c.is_whitespace()) {
+        sides.push(cell_for_letter(letter).ok_or(letter)?);
+    }
+    Ok(all_moves()
+        .into_iter()
+        .filter(|mv| sides.is_empty() || sides.contains(&(mv.side_axis(), mv.side_sign())))
+        .collect())
+}
+
+/// A lower bound on the moves left, from the coordinates of a few tracked
+/// pieces (by index into the tracked list).
+struct PatternDb {
+    pieces: Vec<usize>,
+    distances: Vec<u8>,
+}
+
+impl PatternDb {
+    fn index(&self, state: &[u16]) -> usize {
+        table_index(self.pieces.iter().map(|&piece| state[piece]))
+    }
+}
+
+fn table_index(coords: impl Iterator<Item = u16>) -> usize {
+    coords.fold(0, |index, coord| index * COORDS + usize::from(coord))
+}
+
+/// An IDA* search for sequences of `generators` taking the tracked pieces
+/// from solved to where a target sequence takes them.
+pub(crate) struct Search {
+    generators: Vec<Move>,
+    /// Per generator, per coordinate: the coordinate it's taken to.
+    next: Vec<Vec<u16>>,
+    /// Per generator, per generator: whether the second may follow the
+    /// first (see `Search::new`).
+    may_follow: Vec<Vec<bool>>,
+    start: Vec<u16>,
+    target: Vec<u16>,
+    tables: Vec<PatternDb>,
+}
+
+impl Search {
+    /// Searches with `generators` (and their inverses) for sequences that
+    /// leave the pieces whose home slots are `tracked` where `target`
+    /// leaves them. Builds the pattern databases, so it takes a while for
+    /// many tracked pieces or generators: `report`s a `SearchEvent::Tables`
+    /// before each and once they're done, and gives up with `None` (having
+    /// reported `Finished { cancelled: true }`) once `cancel` is set.
+    ///
+    /// A move may not follow one it merges with - two twists of the same
+    /// layers are one twist of them, or none - and of two that commute,
+    /// only one order is tried.
+    pub(crate) fn new(
+        generators: &[Move],
+        tracked: &[usize],
+        target: &[Move],
+        cancel: &AtomicBool,
+        report: impl Fn(SearchEvent),
+    ) -> Option<Self> {
+        let mut closed = generators.to_vec();
+        for mv in generators {
+            if !closed.contains(&mv.inverse()) {
+                closed.push(mv.inverse());
+            }
+        }
+        let generators = closed;
+
+        let orientation_lists: Vec<Vec<Orientation>> = (0..=4).map(orientations).collect();
+        let coord = |slot: usize, orientation: Orientation| -> u16 {
+            let list = &orientation_lists[facet_count(slot)];
+            let index = list.iter().position(|&o| o == orientation).unwrap();
+            (slot * ORIENTATIONS + index) as u16
+        };
+        // Where `moves` take the piece from each home slot, and how turned.
+        let effect = |moves: &[Move]| -> Vec<(usize, Orientation)> {
+            let mut cube = Hypercube::solved();
+            for &mv in moves {
+                cube.apply_move(mv);
+            }
+            let mut effect = vec![(0, IDENTITY_ORIENTATION); 81];
+            for (slot, piece) in cube.pieces.iter().enumerate() {
+                effect[index_of(piece.home())] = (slot, piece.orientation());
+            }
+            effect
+        };
+
+        let next = generators
+            .iter()
+            .map(|&mv| {
+                let effect = effect(&[mv]);
+                let mut next = vec![0; COORDS];
+                for (slot, &(to, turn)) in effect.iter().enumerate() {
+                    for &orientation in &orientation_lists[facet_count(slot)] {
+                        next[usize::from(coord(slot, orientation))] =
+                            coord(to, compose_orientations(orientation, turn));
+                    }
+                }
+                next
+            })
+            .collect();
+
+        let states: Vec<Hypercube> = generators
+            .iter()
+            .map(|&mv| {
+                let mut cube = Hypercube::solved();
+                cube.apply_move(mv);
+                cube
+            })
+            .collect();
+        let may_follow = generators
+            .iter()
+            .enumerate()
+            .map(|(first, &a)| {
+                generators
+                    .iter()
+                    .enumerate()
+                    .map(|(second, &b)| {
+                        if commute(a, b) && first > second {
+                            return false;
+                        }
+                        if a.side_axis() != b.side_axis() {
+                            return true;
+                        }
+                        let mut both = states[first].clone();
+                        both.apply_move(b);
+                        both != Hypercube::solved()
+                            && !generators.iter().zip(&states).any(|(mv, state)| {
+                                mv.side_axis() == a.side_axis() && *state == both
+                            })
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let start = tracked
+            .iter()
+            .map(|&slot| coord(slot, IDENTITY_ORIENTATION))
+            .collect();
+        let target_effect = effect(target);
+        let target = tracked
+            .iter()
+            .map(|&slot| {
+                let (to, orientation) = target_effect[slot];
+                coord(to, orientation)
+            })
+            .collect();
+
+        let mut search = Self {
+            generators,
+            next,
+            may_follow,
+            start,
+            target,
+            tables: Vec::new(),
+        };
+        let tables = tracked.len().div_ceil(2);
+        for first in (0..tracked.len()).step_by(2) {
+            report(SearchEvent::Tables {
+                built: search.tables.len(),
+                tables,
+            });
+            let pieces = (first..tracked.len().min(first + 2)).collect();
+            match search.pattern_db(pieces, cancel) {
+                Some(table) => search.tables.push(table),
+                None => {
+                    report(SearchEvent::Finished { cancelled: true });
+                    return None;
+                }
+            }
+        }
+        report(SearchEvent::Tables {
+            built: tables,
+            tables,
+        });
+        Some(search)
+    }
+
+    /// Breadth-first search outwards from `pieces`' targets, or `None` if
+    /// `cancel` is set before it's done.
+    fn pattern_db(&self, pieces: Vec<usize>, cancel: &AtomicBool) -> Option<PatternDb> {
+        let mut distances = vec![UNREACHED; COORDS.pow(pieces.len() as u32)];
+        let target: Vec<u16> = pieces.iter().map(|&piece| self.target[piece]).collect();
+        distances[table_index(target.iter().copied())] = 0;
+        let mut queue = VecDeque::from([target]);
+        let mut expanded = 0;
+        while let Some(coords) = queue.pop_front() {
+            if expanded % CANCEL_CHECK_INTERVAL == 0 && cancel.load(Ordering::Relaxed) {
+                return None;
+            }
+            expanded += 1;
+            let distance = distances[table_index(coords.iter().copied())];
+            for next in &self.next {
+                let moved: Vec<u16> = coords.iter().map(|&c| next[usize::from(c)]).collect();
+                let index = table_index(moved.iter().copied());
+                if distances[index] == UNREACHED {
+                    distances[index] = distance + 1;
+                    queue.push_back(moved);
+                }
+            }
+        }
+        Some(PatternDb { pieces, distances })
+    }
+
+    /// The fewest moves the pattern databases say `state` is from the
+    /// target.
+    fn bound(&self, state: &[u16]) -> usize {
+        self.tables
+            .iter()
+            .map(|table| usize::from(table.distances[table.index(state)]))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Tries every sequence of up to `max_depth` moves, shortest first,
+    /// on `threads` threads, reporting as it goes. Returns every solution
+    /// found, by depth and then first move, until `cancel` is set.
+    pub(crate) fn run(
+        &self,
+        max_depth: usize,
+        threads: usize,
+        cancel: &AtomicBool,
+        report: impl Fn(SearchEvent) + Sync,
+    ) -> Vec<Vec<Move>> {
+        let mut solutions = Vec::new();
+        if self.start == self.target {
+            // The empty sequence already does it.
+            report(SearchEvent::Finished { cancelled: false });
+            return solutions;
+        }
+        let first_depth = self.bound(&self.start).max(1);
+        for depth in first_depth..=max_depth {
+            if cancel.load(Ordering::Relaxed) {
+                break;
+            }
+            report(SearchEvent::Depth(depth));
+            let found = self.search_depth(depth, threads, cancel, &report);
+            if cancel.load(Ordering::Relaxed) {
+                solutions.extend(found);
+                break;
+            }
+            report(SearchEvent::DepthDone {
+                depth,
+                solutions: found.len(),
+            });
+            solutions.extend(found);
+        }
+        report(SearchEvent::Finished {
+            cancelled: cancel.load(Ordering::Relaxed),
+        });
+        solutions
+    }
+
+    /// Every solution of exactly `depth` moves, in order of first move.
+    fn search_depth(
+        &self,
+        depth: usize,
+        threads: usize,
+        cancel: &AtomicBool,
+        report: &(impl Fn(SearchEvent) + Sync),
+    ) -> Vec<Vec<Move>> {
+        let branches = self.generators.len();
+        let next_branch = AtomicUsize::new(0);
+        let branches_done = AtomicUsize::new(0);
+        let nodes = AtomicU64::new(0);
+        let found: Mutex<Vec<Vec<Vec<usize>>>> = Mutex::new(vec![Vec::new(); branches]);
+        std::thread::scope(|scope| {
+            for _ in 0..threads.max(1) {
+                scope.spawn(|| {
+                    let mut states = vec![self.start.clone(); depth + 1];
+                    loop {
+                        let branch = next_branch.fetch_add(1, Ordering::Relaxed);
+                        if branch >= branches || cancel.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        let mut path = vec![branch];
+                        let mut branch_found = Vec::new();
+                        let mut branch_nodes = 0;
+                        if self.step(&mut states, branch, depth) {
+                            self.descend(
+                                &mut states[1..],
+                                depth - 1,
+                                &mut path,
+                                &mut branch_nodes,
+                                cancel,
+                                &mut |path: &[usize]| {
+                                    report(SearchEvent::Solution(self.moves(path)));
+                                    branch_found.push(path.to_vec());
+                                },
+                            );
+                        }
+                        found.lock().unwrap()[branch] = branch_found;
+                        report(SearchEvent::Progress {
+                            depth,
+                            branches_done: branches_done.fetch_add(1, Ordering::Relaxed) + 1,
+                            branches,
+                            nodes: nodes.fetch_add(branch_nodes, Ordering::Relaxed) + branch_nodes,
+                        });
+                    }
+                });
+            }
+        });
+        found
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .map(|path| self.moves(&path))
+            .collect()
+    }
+
+    /// Applies generator `mv` to `states[0]` into `states[1]`, saying
+    /// whether that's worth searching below with `depth_left` moves to go
+    /// (counting this one): it must move a tracked piece, and the pattern
+    /// databases must allow it.
+    fn step(&self, states: &mut [Vec<u16>], mv: usize, depth_left: usize) -> bool {
+        let (current, deeper) = states.split_first_mut().unwrap();
+        let next = &mut deeper[0];
+        for (next, &coord) in next.iter_mut().zip(current.iter()) {
+            *next = self.next[mv][usize::from(coord)];
+        }
+        next != current && self.bound(next) < depth_left
+    }
+
+    /// Depth-first search below `states[0]` (reached by `path`) for
+    /// sequences of exactly `depth_left` more moves ending on the target.
+    /// Doesn't go on through the target: what comes after would be a
+    /// sequence that leaves the tracked pieces alone.
+    fn descend(
+        &self,
+        states: &mut [Vec<u16>],
+        depth_left: usize,
+        path: &mut Vec<usize>,
+        nodes: &mut u64,
+        cancel: &AtomicBool,
+        found: &mut impl FnMut(&[usize]),
+    ) {
+        *nodes += 1;
+        if states[0] == self.target {
+            if depth_left == 0 {
+                found(path);
+            }
+            return;
+        }
+        if depth_left == 0 || cancel.load(Ordering::Relaxed) {
+            return;
+        }
+        let last = *path.last().unwrap();
+        for mv in 0..self.generators.len() {
+            if self.may_follow[last][mv] && self.step(states, mv, depth_left) {
+                path.push(mv);
+                self.descend(&mut states[1..], depth_left - 1, path, nodes, cancel, found);
+                path.pop();
+            }
+        }
+    }
+
+    fn moves(&self, path: &[usize]) -> Vec<Move> {
+        path.iter().map(|&mv| self.generators[mv]).collect()
+    }
+}
+
+fn facet_count(slot: usize) -> usize {
+    position_of(slot).iter().filter(|c| **c != 0).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::moves::parse_moves;
+
+    fn generators() -> Vec<Move> {
+        twists_of_cells("R U").unwrap()
+    }
+
+    /// The corners on the R cell.
+    fn tracked() -> Vec<usize> {
+        slots_with(4)
+            .into_iter()
+            .filter(|&slot| position_of(slot)[2] == 1)
+            .collect()
+    }
+
+    fn search_for(target: &[Move]) -> Search {
+        Search::new(
+            &generators(),
+            &tracked(),
+            target,
+            &AtomicBool::new(false),
+            |_| {},
+        )
+        .unwrap()
+    }
+
+    fn run(search: &Search, max_depth: usize, threads: usize) -> Vec<Vec<Move>> {
+        search.run(max_depth, threads, &AtomicBool::new(false), |_| {})
+    }
+
+    #[test]
+    fn finds_every_shortest_sequence_with_the_effect() {
+        let target = parse_moves("RU UF'").unwrap();
+        let search = search_for(&target);
+        let solutions = run(&search, 2, 1);
+        assert!(solutions.contains(&target), "{solutions:?}");
+        for solution in &solutions {
+            assert_eq!(solution.len(), 2);
+            let on_tracked = |moves: &[Move]| {
+                let mut cube = Hypercube::solved();
+                for &mv in moves {
+                    cube.apply_move(mv);
+                }
+                tracked()
+                    .iter()
+                    .map(|&slot| cube.pieces[slot])
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(on_tracked(solution), on_tracked(&target));
+        }
+    }
+
+    #[test]
+    fn reports_depths_and_solutions_in_order() {
+        let target = parse_moves("RU2 UR").unwrap();
+        let search = search_for(&target);
+        let events = Mutex::new(Vec::new());
+        let solutions = search.run(3, 2, &AtomicBool::new(false), |event| {
+            events.lock().unwrap().push(event)
+        });
+        let events = events.into_inner().unwrap();
+
+        let depths: Vec<usize> = events
+            .iter()
+            .filter_map(|event| match event {
+                SearchEvent::Depth(depth) => Some(*depth),
+                _ => None,
+            })
+            .collect();
+        // From whatever the pattern databases allow up to the last depth,
+        // none skipped.
+        assert_eq!(depths, (depths[0]..=3).collect::<Vec<_>>());
+        assert!(solutions.contains(&target));
+        assert!(solutions.iter().all(|solution| solution.len() >= 2));
+        let reported = events
+            .iter()
+            .filter(|event| matches!(event, SearchEvent::Solution(_)))
+            .count();
+        assert_eq!(reported, solutions.len());
+        assert!(events.contains(&SearchEvent::DepthDone {
+            depth: 2,
+            solutions: solutions.iter().filter(|s| s.len() == 2).count(),
+        }));
+        assert_eq!(
+            events.last(),
+            Some(&SearchEvent::Finished { cancelled: false })
+        );
+
+        // However the first moves are shared out, the results are the same.
+        assert_eq!(run(&search, 3, 1), solutions);
+    }
+
+    #[test]
+    fn twists_of_cells_reads_cell_letters() {
+        assert_eq!(twists_of_cells(" ").unwrap(), all_moves());
+        let twists = twists_of_cells("IL").unwrap();
+        assert_eq!(twists.len(), 2 * all_moves().len() / 8);
+        assert!(twists.iter().all(|mv| mv.side_sign() == -1));
+        assert_eq!(twists_of_cells("RX"), Err('X'));
+    }
+
+    #[test]
+    fn cancelled_search_stops() {
+        let search = search_for(&parse_moves("RU UR").unwrap());
+        let events = Mutex::new(Vec::new());
+        let solutions = search.run(5, 2, &AtomicBool::new(true), |event| {
+            events.lock().unwrap().push(event)
+        });
+        assert!(solutions.is_empty());
+        assert_eq!(
+            events.into_inner().unwrap(),
+            [SearchEvent::Finished { cancelled: true }]
+        );
+    }
+
+    #[test]
+    fn building_the_tables_reports_progress_and_stops_when_cancelled() {
+        let target = parse_moves("RU UR").unwrap();
+        let events = Mutex::new(Vec::new());
+        let report = |event| events.lock().unwrap().push(event);
+        assert!(
+            Search::new(
+                &generators(),
+                &tracked(),
+                &target,
+                &AtomicBool::new(false),
+                report
+            )
+            .is_some()
+        );
+        let tables = tracked().len().div_ceil(2);
+        let built: Vec<SearchEvent> = (0..=tables)
+            .map(|built| SearchEvent::Tables { built, tables })
+            .collect();
+        assert_eq!(events.lock().unwrap().drain(..).collect::<Vec<_>>(), built);
+
+        assert!(
+            Search::new(
+                &generators(),
+                &tracked(),
+                &target,
+                &AtomicBool::new(true),
+                report
+            )
+            .is_none()
+        );
+        assert_eq!(
+            events.into_inner().unwrap(),
+            [
+                SearchEvent::Tables { built: 0, tables },
+                SearchEvent::Finished { cancelled: true },
+            ]
+        );
+    }
+}
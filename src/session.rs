@@ -0,0 +1,225 @@
+//! Saved puzzle sessions: everything needed to resume a solve exactly where
+//! it was left - puzzle state, 4D orientation, camera, sticker layout and
+//! move history - as one JSON file.
+//!
+//! The shader widget owns most of this, so a session is split in two:
+//! `PuzzleSnapshot` is what the widget captures and restores, and `Session`
+//! adds the sticker scale/face gap that `HypercubeApp` owns.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::history::MoveHistory;
+use crate::moves::Move;
+use crate::piece::{Hypercube, position_of};
+
+/// Orbit camera placement, mirroring `CameraController`'s fields.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct CameraPose {
+    pub(crate) yaw: f32,
+    pub(crate) pitch: f32,
+    pub(crate) distance: f32,
+}
+
+/// How the puzzle is being looked at: its 4D orientation and the camera.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct ViewPose {
+    /// `rotation_4d`, as nalgebra's column-major arrays.
+    pub(crate) rotation_4d: [[f32; 4]; 4],
+    pub(crate) camera: CameraPose,
+}
+
+/// The shader widget's share of a session.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct PuzzleSnapshot {
+    pub(crate) hypercube: Hypercube,
+    #[serde(flatten)]
+    pub(crate) view: ViewPose,
+    pub(crate) history: MoveHistory,
+}
+
+/// A complete session file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Session {
+    #[serde(flatten)]
+    pub(crate) puzzle: PuzzleSnapshot,
+    /// State the scramble started from, if not solved (see `MoveLog`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) scramble_start: Option<Hypercube>,
+    /// Moves that scrambled the puzzle before `puzzle.history` began, so
+    /// the session can still be exported as a complete log.
+    #[serde(default)]
+    pub(crate) scramble: Vec<Move>,
+    /// Rendered sticker scale (the value the slider tooltip shows, not the
+    /// slider's inverted position).
+    pub(crate) sticker_scale: f32,
+    pub(crate) face_gap: f32,
+}
+
+/// Why a session couldn't be saved or opened.
+#[derive(Debug)]
+pub(crate) enum SessionError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    /// Parsed fine, but the puzzle isn't one `Hypercube` can hold.
+    InvalidPuzzle(&'static str),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionError::Io(err) => write!(f, "{err}"),
+            SessionError::Json(err) => write!(f, "malformed session file: {err}"),
+            SessionError::InvalidPuzzle(reason) => write!(f, "invalid puzzle: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+impl From<std::io::Error> for SessionError {
+    fn from(err: std::io::Error) -> Self {
+        SessionError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for SessionError {
+    fn from(err: serde_json::Error) -> Self {
+        SessionError::Json(err)
+    }
+}
+
+/// Where the Save/Open path box starts out: `session.json` in the same
+/// `ProjectDirs` data dir the rest of the app's files live under.
+pub(crate) fn default_path() -> Option<PathBuf> {
+    directories::ProjectDirs::from("", "", "hypercube")
+        .map(|dirs| dirs.data_dir().join("session.json"))
+}
+
+/// Writes `session` to `path`, creating its parent directory if needed.
+pub(crate) fn save(path: &Path, session: &Session) -> Result<(), SessionError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let contents = serde_json::to_string_pretty(session)?;
+    std::fs::write(path, contents)?;
+    Ok(())
+}
+
+/// Reads a session from `path`, rejecting puzzles whose pieces aren't laid
+/// out the way the rest of the crate relies on.
+pub(crate) fn load(path: &Path) -> Result<Session, SessionError> {
+    let contents = std::fs::read_to_string(path)?;
+    let session: Session = serde_json::from_str(&contents)?;
+    check_puzzle(&session.puzzle.hypercube)?;
+    if let Some(start) = &session.scramble_start {
+        check_puzzle(start)?;
+    }
+    Ok(session)
+}
+
+/// Structural checks only: 81 pieces, each in its `index_of` slot with a
+/// color on exactly its nonzero axes. Whether the coloring is reachable by
+/// legal moves isn't checked.
+fn check_puzzle(hypercube: &Hypercube) -> Result<(), SessionError> {
+    if hypercube.pieces.len() != 81 {
+        return Err(SessionError::InvalidPuzzle("expected 81 pieces"));
+    }
+    for (slot, piece) in hypercube.pieces.iter().enumerate() {
+        if piece.position != position_of(slot) {
+            return Err(SessionError::InvalidPuzzle("piece out of its slot"));
+        }
+        for axis in 0..4 {
+            if (piece.position[axis] != 0) != piece.colors[axis].is_some() {
+                return Err(SessionError::InvalidPuzzle(
+                    "piece colors don't match its position",
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::moves::parse_moves;
+
+    fn scrambled_session() -> Session {
+        let mut hypercube = Hypercube::solved();
+        let mut history = MoveHistory::default();
+        for mv in parse_moves("RU OBR' IUFR2 LD").unwrap() {
+            hypercube.apply_move(mv);
+            history.push(mv);
+        }
+        history.undo();
+        Session {
+            puzzle: PuzzleSnapshot {
+                hypercube,
+                view: ViewPose {
+                    rotation_4d: nalgebra::Matrix4::new_rotation(nalgebra::Vector3::z() * 0.5)
+                        .into(),
+                    camera: CameraPose {
+                        yaw: 12.5,
+                        pitch: -30.0,
+                        distance: 9.0,
+                    },
+                },
+                history,
+            },
+            scramble_start: Some(Hypercube::random_state(&mut fastrand::Rng::with_seed(5))),
+            scramble: parse_moves("UF DB2").unwrap(),
+            sticker_scale: 0.4,
+            face_gap: 1.25,
+        }
+    }
+
+    /// A unique path per test under the system temp dir, so tests running
+    /// in parallel don't clobber each other's files.
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("hypercube-session-test-{}", std::process::id()))
+            .join(name)
+    }
+
+    #[test]
+    fn save_then_load_round_trips_every_field() {
+        let session = scrambled_session();
+        let path = temp_path("round_trip.json");
+        save(&path, &session).unwrap();
+        let loaded = load(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(loaded, session);
+        assert!(loaded.puzzle.history.can_redo());
+    }
+
+    #[test]
+    fn view_is_stored_at_the_top_level() {
+        let json = serde_json::to_value(scrambled_session()).unwrap();
+        assert!(json.get("rotation_4d").is_some());
+        assert_eq!(json["camera"]["yaw"], 12.5);
+        assert!(json.get("view").is_none());
+    }
+
+    #[test]
+    fn load_rejects_a_puzzle_with_misplaced_pieces() {
+        let mut session = scrambled_session();
+        session.puzzle.hypercube.pieces.swap(0, 1);
+        let path = temp_path("misplaced.json");
+        save(&path, &session).unwrap();
+        let result = load(&path);
+        std::fs::remove_file(&path).ok();
+        assert!(matches!(result, Err(SessionError::InvalidPuzzle(_))));
+    }
+
+    #[test]
+    fn load_rejects_a_history_cursor_past_its_moves() {
+        let session = scrambled_session();
+        let json = serde_json::to_string(&session).unwrap();
+        assert!(json.contains("\"cursor\":3"));
+        let json = json.replace("\"cursor\":3", "\"cursor\":9");
+        assert!(serde_json::from_str::<Session>(&json).is_err());
+    }
+}
@@ -45,12 +45,52 @@ impl std::fmt::Display for RotateButton {
 pub(crate) const ANIMATION_DURATION_MS_RANGE: std::ops::RangeInclusive<u32> = 100..=3000;
 const DEFAULT_ANIMATION_DURATION_MS: u32 = 250;
 
+/// A named algorithm expression (see `algorithm.rs`), kept as the text it
+/// was saved as and parsed again when played.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct SavedAlgorithm {
+    pub(crate) name: String,
+    pub(crate) expression: String,
+}
+
+/// Keys for twisting from the keyboard (see `keyboard_twist.rs`), one per
+/// cell, indexed by `face_id_for` like `moves::CELL_LETTERS`. Each is the
+/// character a key types, lowercase.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct KeyBindings {
+    /// Held to grip a cell.
+    pub(crate) grips: [String; 8],
+    /// Pressed while a cell is gripped to pick the grip: a neighboring cell,
+    /// or two or three pressed together for an edge or corner grip. The
+    /// twist happens once they're all released.
+    pub(crate) twists: [String; 8],
+}
+
+impl Default for KeyBindings {
+    /// Grips under the left hand and twists under the right, each laid out
+    /// roughly like the cells: U above D, L left of R, F/B and I/O beside.
+    fn default() -> Self {
+        let keys = |keys: [&str; 8]| keys.map(str::to_string);
+        Self {
+            grips: keys(["f", "a", "s", "e", "q", "w", "d", "r"]),
+            twists: keys(["h", "j", "k", "u", "o", "i", "l", ";"]),
+        }
+    }
+}
+
 /// Settings persisted across application runs.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct AppSettings {
     pub(crate) rotate_button: RotateButton,
     /// Duration of a move's turn animation, in milliseconds.
     pub(crate) animation_duration_ms: u32,
+    /// Algorithms with a button each, in the order they were saved.
+    /// Defaulted so settings files from before there were any still load.
+    #[serde(default)]
+    pub(crate) algorithms: Vec<SavedAlgorithm>,
+    /// Defaulted like `algorithms`.
+    #[serde(default)]
+    pub(crate) key_bindings: KeyBindings,
 }
 
 impl Default for AppSettings {
@@ -58,6 +98,8 @@ impl Default for AppSettings {
         Self {
             rotate_button: RotateButton::default(),
             animation_duration_ms: DEFAULT_ANIMATION_DURATION_MS,
+            algorithms: Vec::new(),
+            key_bindings: KeyBindings::default(),
         }
     }
 }
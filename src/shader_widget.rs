@@ -4,6 +4,7 @@
 //! logic, camera controls, and 4D transformations. It follows Option C architecture
 //! where the shader widget manages its own state independently.
 
+use std::collections::VecDeque;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
@@ -14,33 +15,41 @@ use nalgebra::{Matrix4, UnitQuaternion, Vector3, Vector4};
 
 use crate::app::{AABBMode, Message, RenderMode};
 use crate::camera::{Camera, CameraController, Projection};
+use crate::drag_twist::drag_twist;
 use crate::geometry::{
     BASE_CUBE_VERTICES, FACE_CENTERS, FIXED_DIMS, NORMAL_TO_BASE_INDICES, VERTEX_NORMAL_INDICES,
 };
+use crate::hint::Click;
+use crate::history::{MoveHistory, Replay};
+use crate::keyboard_twist::TwistKeys;
 use crate::math::{
     GRID_EXTENT, VIEWER_DISTANCE, compose_so4, create_4d_plane_rotation, decompose_so4,
-    process_4d_rotation, project_cube_point, quat_slerp_exact, shortest_arc_plane, visible_faces,
+    face_push_offset_3d, process_4d_rotation, project_4d_to_3d, project_cube_point,
+    quat_slerp_exact, shortest_arc_plane, visible_faces,
 };
-use crate::moves::{base_angle, clockwise_sign, rotate_local_position};
+use crate::moves::{Layers, Move, clockwise_sign, reorientation, rotate_local_position};
+use crate::painting::{self, Brush};
 use crate::piece::{
-    FACET_TABLE, Hypercube, Piece, StickerInstance, free_axes, generate_sticker_instances,
+    FACE_AXIS_SIGN, FACET_TABLE, Hypercube, Piece, StickerInstance, free_axes,
+    generate_sticker_instances,
 };
-use crate::ray_casting::{calculate_mouse_ray, find_intersected_sticker};
+use crate::ray_casting::{calculate_mouse_ray, find_intersected_sticker, project_to_screen};
 use crate::renderer::{DebugInstanceWithDistance, Renderer};
-use crate::settings::RotateButton;
+use crate::session::{CameraPose, PuzzleSnapshot, ViewPose};
+use crate::settings::{KeyBindings, RotateButton};
 
 /// An in-progress move's animation: piece state has already been committed
 /// atomically by `apply_move`; this only drives the visual sweep from the
 /// pre-move snapshot toward the (already-final) post-move positions.
 struct AnimatingMove {
-    side_axis: usize,
-    side_sign: i8,
-    local_coords: [i8; 3],
-    /// Signed target angle (its sign already encodes direction).
-    angle: f32,
+    mv: Move,
     pre_move_pieces: Vec<Piece>,
     elapsed: Duration,
     duration: Duration,
+    /// When `mv` was committed, i.e. when the player actually made the
+    /// move; reported with `Message::Solved` so a solve's time doesn't
+    /// include its closing animation.
+    committed_at: Instant,
 }
 
 /// Outcome of advancing the move animation by one tick.
@@ -54,7 +63,7 @@ enum AnimationTick {
 }
 
 /// An in-progress "center this face" animation, triggered by double-clicking
-/// a sticker: sweeps `rotation_4d` from its value when the double-click
+/// a sticker on the cell already in front (see `focus_face`): sweeps `rotation_4d` from its value when the double-click
 /// landed toward `start_rotation` rotated by `total_angle` in `plane`, which
 /// by construction carries the double-clicked face's normal onto the
 /// screen-centered pole (see `shortest_arc_plane` and its call site below).
@@ -105,6 +114,13 @@ const CLICK_DRAG_THRESHOLD_PX: f32 = 4.0;
 /// Max gap between two qualifying clicks on the same face for them to count
 /// as a double-click.
 const DOUBLE_CLICK_WINDOW: Duration = Duration::from_millis(400);
+/// Factor each move still waiting in `queued_moves` scales a starting
+/// move's animation by, so a long queue catches up instead of lagging ever
+/// further behind the input.
+const QUEUE_SPEEDUP: f32 = 0.7;
+/// Shortest a queue makes a move's animation, as a fraction of
+/// `animation_duration_ms`.
+const MIN_QUEUED_DURATION: f32 = 0.2;
 
 /// Duration of the reveal/hide flourish (camera spin + scale/gap animation),
 /// independent of `animation_duration_ms` which is tuned for quick move/focus
@@ -124,12 +140,15 @@ const SECONDARY_FACE_GAP: f32 = 1.5;
 
 /// Builds the GPU instance list for the current frame. Piece state is
 /// already final (`apply_move` commits atomically) - while a move is
-/// animating, the 27 affected facets are instead swept from their pre-move
+/// animating, the facets of the pieces it turns are instead swept from their pre-move
 /// position/color toward that already-committed final position, using the
 /// exact same rotation formula `apply_move` used, so the last animated
 /// frame always lines up perfectly with the static post-move render it
 /// hands off to.
 pub fn sticker_instances_for_render(state: &HypercubeShaderState) -> Vec<StickerInstance> {
+    if let Some(draft) = &state.paint_draft {
+        return generate_sticker_instances(draft);
+    }
     let Some(animating) = &state.animating_move else {
         return generate_sticker_instances(&state.hypercube);
     };
@@ -139,8 +158,10 @@ pub fn sticker_instances_for_render(state: &HypercubeShaderState) -> Vec<Sticker
     } else {
         (animating.elapsed.as_secs_f32() / animating.duration.as_secs_f32()).clamp(0.0, 1.0)
     };
-    let partial_angle = animating.angle * ease(t);
-    let axes = free_axes(animating.side_axis);
+    let partial_angle = animating.mv.angle() * ease(t);
+    let side_axis = animating.mv.side_axis();
+    let grip = animating.mv.grip();
+    let axes = free_axes(side_axis);
 
     FACET_TABLE
         .iter()
@@ -149,9 +170,9 @@ pub fn sticker_instances_for_render(state: &HypercubeShaderState) -> Vec<Sticker
             let color = pre_move_piece.colors[facet.axis]
                 .expect("FACET_TABLE entries are only built where colors[axis] is Some");
 
-            let (position_4d, basis, face_normal_4d) = if pre_move_piece.position
-                [animating.side_axis]
-                == animating.side_sign
+            let (position_4d, basis, face_normal_4d) = if animating
+                .mv
+                .turns_piece_at(pre_move_piece.position)
             {
                 // `facet_position_4d`'s static convention is `pos *
                 // GRID_EXTENT + extension`, where `extension` is zero except
@@ -173,12 +194,12 @@ pub fn sticker_instances_for_render(state: &HypercubeShaderState) -> Vec<Sticker
                         pre_move_piece.position[facet.axis] as f32 * (1.0 - GRID_EXTENT);
                 }
                 let rotated =
-                    rotate_local_position(animating.local_coords, partial_angle, local_combined);
+                    rotate_local_position(grip, partial_angle, local_combined);
 
                 let mut position_4d = [0.0f32; 4];
-                position_4d[animating.side_axis] = pre_move_piece.position[animating.side_axis]
+                position_4d[side_axis] = pre_move_piece.position[side_axis]
                     as f32
-                    * if facet.axis == animating.side_axis {
+                    * if facet.axis == side_axis {
                         1.0
                     } else {
                         GRID_EXTENT
@@ -195,7 +216,7 @@ pub fn sticker_instances_for_render(state: &HypercubeShaderState) -> Vec<Sticker
                 // basis vector's new direction directly, by linearity.
                 let mut basis = [[0.0f32; 4]; 3];
                 for (i, &a) in facet.free_axes.iter().enumerate() {
-                    basis[i] = if a == animating.side_axis {
+                    basis[i] = if a == side_axis {
                         let mut v = [0.0f32; 4];
                         v[a] = 1.0;
                         v
@@ -206,7 +227,7 @@ pub fn sticker_instances_for_render(state: &HypercubeShaderState) -> Vec<Sticker
                         let mut one_hot = [0.0f32; 3];
                         one_hot[j] = 1.0;
                         let rotated =
-                            rotate_local_position(animating.local_coords, partial_angle, one_hot);
+                            rotate_local_position(grip, partial_angle, one_hot);
                         let mut v = [0.0f32; 4];
                         for k in 0..3 {
                             v[axes[k]] = rotated[k];
@@ -227,7 +248,7 @@ pub fn sticker_instances_for_render(state: &HypercubeShaderState) -> Vec<Sticker
                     let mut one_hot = [0.0f32; 3];
                     one_hot[j] = facet.side_sign as f32;
                     let rotated =
-                        rotate_local_position(animating.local_coords, partial_angle, one_hot);
+                        rotate_local_position(grip, partial_angle, one_hot);
                     let mut v = [0.0f32; 4];
                     for k in 0..3 {
                         v[axes[k]] = rotated[k];
@@ -252,6 +273,12 @@ pub fn sticker_instances_for_render(state: &HypercubeShaderState) -> Vec<Sticker
         .collect()
 }
 
+/// The message telling `HypercubeApp` whether its Undo/Redo buttons have
+/// anything to act on after `history` changed.
+fn history_changed(history: &MoveHistory) -> Message {
+    Message::HistoryChanged(history.clone())
+}
+
 /// Parameters controlled from the ui.
 #[derive(Debug, Clone, Copy)]
 pub(crate) struct UiControls {
@@ -279,6 +306,8 @@ pub(crate) struct HypercubePrimitive {
     pub(crate) cached_indices: Arc<[u16]>,
     pub(crate) indices_generation: u64,
     pub(crate) hovered_sticker: Option<usize>,
+    pub(crate) highlighted_slots: [u32; 4],
+    pub(crate) hint: Option<Click>,
     pub(crate) debug_instances: Vec<DebugInstanceWithDistance>,
     pub(crate) sticker_instances: Arc<[StickerInstance]>,
     pub(crate) sticker_generation: u64,
@@ -308,7 +337,12 @@ impl shader::Primitive for HypercubePrimitive {
         pipeline.update_camera(queue, &self.camera, &self.projection);
         pipeline.update_light(queue, &self.camera);
         pipeline.update_indices(queue, &self.cached_indices, self.indices_generation);
-        pipeline.update_highlighting(queue, self.hovered_sticker);
+        pipeline.update_highlighting(
+            queue,
+            self.hovered_sticker,
+            self.highlighted_slots,
+            self.hint,
+        );
         pipeline.update_debug_instances(queue, &self.debug_instances);
         pipeline.update_sticker_instances(queue, &self.sticker_instances, self.sticker_generation);
         pipeline.set_render_mode(self.ui_controls.render_mode);
@@ -337,6 +371,10 @@ pub struct HypercubeShaderState {
     mouse_pressed: bool,
     last_mouse_pos: Option<Point>,
     shift_pressed: bool,
+    /// Held modifiers choosing a turn-click's layers: Alt for the middle
+    /// slice, Ctrl for the side and middle slice together.
+    alt_pressed: bool,
+    control_pressed: bool,
     cached_indices: Arc<[u16]>,
     /// Bumped every time `cached_indices` is replaced; carried on
     /// `HypercubePrimitive` so `Renderer` can skip re-uploading the index
@@ -347,6 +385,9 @@ pub struct HypercubeShaderState {
     /// upload-skipping purpose as `indices_generation`.
     sticker_generation: u64,
     hovered_sticker: Option<usize>,
+    /// Where the cursor's ray meets `hovered_sticker`, as a
+    /// `ray_casting::StickerHit` offset.
+    hovered_offset: Vector3<f32>,
     debug_instances: Vec<DebugInstanceWithDistance>,
     hypercube: Hypercube,
     animating_move: Option<AnimatingMove>,
@@ -363,17 +404,46 @@ pub struct HypercubeShaderState {
     /// Position and hovered sticker (if any) recorded when the rotate button
     /// was last pressed, used at release time to tell a click from a drag.
     rotate_press: Option<(Point, Option<usize>)>,
+    /// Position, sticker and hit offset recorded when the click button
+    /// went down on a sticker: released in place it's a turn-click, dragged
+    /// past `CLICK_DRAG_THRESHOLD_PX` it's a `drag_twist` instead.
+    twist_press: Option<(Point, usize, Vector3<f32>)>,
     /// Time and face of an unmatched first click on the rotate button,
     /// waiting to see if a second click lands within `DOUBLE_CLICK_WINDOW`.
     pending_face_click: Option<(Instant, usize)>,
     last_redraw_instant: Option<Instant>,
     reset_generation: u64,
     random_moves_generation: u64,
-    /// Seeded once at construction, reused across every random-move press so
-    /// results are reproducible from a fixed seed in tests but still vary
-    /// run-to-run in the live app (`Rng::new()` seeds from OS entropy).
-    rng: fastrand::Rng,
     reveal_generation: u64,
+    /// Every move committed by a turn-click or pasted notation (or replayed
+    /// by redo), for undo/redo. Cleared by Reset and Random Move(s)/Scramble,
+    /// which start the puzzle over from a state the recorded moves don't
+    /// lead from; replaced wholesale by an opened session or imported log.
+    history: MoveHistory,
+    /// Unlike the other generation counters, these are advanced one step per
+    /// handled undo/redo rather than jumped straight to the program's value,
+    /// so presses that land while a move is still animating queue up instead
+    /// of collapsing into a single step (see `Program::update`).
+    undo_generation: u64,
+    redo_generation: u64,
+    apply_moves_generation: u64,
+    save_session_generation: u64,
+    load_session_generation: u64,
+    replay_generation: u64,
+    play_generation: u64,
+    /// Moves still to play, one animated turn after another: a played
+    /// algorithm's or the solver's, front first. Each is recorded in
+    /// `history` as it starts.
+    queued_moves: VecDeque<Move>,
+    /// The stickers being painted while painting mode is on, shown in place
+    /// of `hypercube`; not necessarily a real state (see `painting.rs`).
+    paint_draft: Option<Hypercube>,
+    apply_paint_generation: u64,
+    /// Grip and twist keys held towards a keyboard twist.
+    twist_keys: TwistKeys,
+    /// The view last published as `Message::ViewChanged`, while
+    /// `record_view` is on.
+    published_view: Option<ViewPose>,
 }
 
 impl HypercubeShaderState {
@@ -391,53 +461,102 @@ impl HypercubeShaderState {
         self.cached_sticker_instances = instances.into();
         self.sticker_generation += 1;
     }
-}
 
-/// The shader program that handles 4D hypercube rendering
-pub struct HypercubeShaderProgram {
-    sticker_scale: f32,
-    face_gap: f32,
-    render_mode: RenderMode,
-    aabb_mode: AABBMode,
-    rotate_button: RotateButton,
-    animation_duration_ms: u32,
-    reset_generation: u64,
-    random_moves_generation: u64,
-    random_move_count: u32,
-    reveal_generation: u64,
-    revealed_target: bool,
-}
+    /// The current 4D orientation and camera.
+    fn view_pose(&self) -> ViewPose {
+        ViewPose {
+            rotation_4d: self.rotation_4d.into(),
+            camera: CameraPose {
+                yaw: self.camera_controller.yaw,
+                pitch: self.camera_controller.pitch,
+                distance: self.camera_controller.distance,
+            },
+        }
+    }
 
-impl HypercubeShaderProgram {
-    /// Create a new shader program with the given parameters
-    #[allow(clippy::too_many_arguments)]
-    pub(crate) fn new(
-        sticker_scale: f32,
-        face_gap: f32,
-        render_mode: RenderMode,
-        aabb_mode: AABBMode,
-        rotate_button: RotateButton,
-        animation_duration_ms: u32,
-        reset_generation: u64,
-        random_moves_generation: u64,
-        random_move_count: u32,
-        reveal_generation: u64,
-        revealed_target: bool,
-    ) -> Self {
-        Self {
-            sticker_scale,
-            face_gap,
-            render_mode,
-            aabb_mode,
-            rotate_button,
-            animation_duration_ms,
-            reset_generation,
-            random_moves_generation,
-            random_move_count,
-            reveal_generation,
-            revealed_target,
+    /// Captures the parts of a saved session this widget owns.
+    fn snapshot(&self) -> PuzzleSnapshot {
+        PuzzleSnapshot {
+            hypercube: self.hypercube.clone(),
+            view: self.view_pose(),
+            history: self.history.clone(),
         }
     }
+
+    /// Puts the puzzle, orientation, camera and history back exactly as
+    /// `snapshot` recorded them, dropping any in-flight interaction.
+    fn restore(&mut self, snapshot: &PuzzleSnapshot) {
+        self.hypercube = snapshot.hypercube.clone();
+        self.rotation_4d = Matrix4::from(snapshot.view.rotation_4d);
+        self.camera_controller.yaw = snapshot.view.camera.yaw;
+        self.camera_controller.pitch = snapshot.view.camera.pitch;
+        self.camera_controller.distance = snapshot.view.camera.distance;
+        self.history = snapshot.history.clone();
+        self.queued_moves.clear();
+        self.animating_move = None;
+        self.animating_focus = None;
+        self.animating_reset = None;
+        self.rotate_press = None;
+        self.pending_face_click = None;
+        self.last_redraw_instant = None;
+        self.hovered_sticker = None;
+        self.debug_instances.clear();
+        self.set_cached_indices(HypercubeShaderProgram::calculate_indices(&self.rotation_4d));
+        let instances = sticker_instances_for_render(self);
+        self.set_cached_sticker_instances(instances);
+    }
+}
+
+/// The shader program that handles 4D hypercube rendering
+pub struct HypercubeShaderProgram {
+    pub(crate) sticker_scale: f32,
+    pub(crate) face_gap: f32,
+    pub(crate) render_mode: RenderMode,
+    pub(crate) aabb_mode: AABBMode,
+    pub(crate) rotate_button: RotateButton,
+    pub(crate) animation_duration_ms: u32,
+    pub(crate) reset_generation: u64,
+    pub(crate) random_moves_generation: u64,
+    pub(crate) random_move_count: u32,
+    /// Seed for the random moves, chosen by the app so a solve record can
+    /// say which scramble it started from.
+    pub(crate) random_seed: u64,
+    pub(crate) reveal_generation: u64,
+    pub(crate) revealed_target: bool,
+    pub(crate) undo_generation: u64,
+    pub(crate) redo_generation: u64,
+    pub(crate) apply_moves_generation: u64,
+    /// Moves carried alongside `apply_moves_generation`, e.g. parsed from
+    /// pasted notation.
+    pub(crate) pending_moves: Arc<[Move]>,
+    pub(crate) save_session_generation: u64,
+    pub(crate) load_session_generation: u64,
+    /// Session to restore, carried alongside `load_session_generation`.
+    pub(crate) pending_session: Option<Arc<PuzzleSnapshot>>,
+    pub(crate) replay_generation: u64,
+    /// Scramble and history to rebuild the puzzle from, carried alongside
+    /// `replay_generation`.
+    pub(crate) pending_replay: Option<Arc<Replay>>,
+    pub(crate) play_generation: u64,
+    /// Moves to play through the move animation, e.g. an expanded saved
+    /// algorithm, carried alongside `play_generation`.
+    pub(crate) pending_play: Arc<[Move]>,
+    /// Piece slots to mark in the viewport, e.g. those an analyzed
+    /// algorithm moves (`CycleAnalysis::slot_mask`).
+    pub(crate) highlighted_slots: [u32; 4],
+    /// Hint mode's next click, marked on its sticker.
+    pub(crate) hint: Option<Click>,
+    /// Painting mode's brush, or `None` when not painting: clicks paint
+    /// stickers of a draft instead of turning the puzzle.
+    pub(crate) paint_brush: Option<Brush>,
+    /// Bumped to validate the draft and, if it's a reachable state, publish
+    /// `Message::Painted` with it for the app to load.
+    pub(crate) apply_paint_generation: u64,
+    /// Keys for twisting from the keyboard (`keyboard_twist.rs`).
+    pub(crate) key_bindings: Arc<KeyBindings>,
+    /// Whether to publish `Message::ViewChanged` whenever the 4D
+    /// orientation or camera moves, e.g. to record a timed solve.
+    pub(crate) record_view: bool,
 }
 
 impl shader::Program<Message> for HypercubeShaderProgram {
@@ -451,8 +570,26 @@ impl shader::Program<Message> for HypercubeShaderProgram {
         bounds: Rectangle,
         cursor: mouse::Cursor,
     ) -> Option<Action<Message>> {
+        if self.paint_brush.is_some() != state.paint_draft.is_some() {
+            state.paint_draft = self.paint_brush.map(|_| state.hypercube.clone());
+            let instances = sticker_instances_for_render(state);
+            state.set_cached_sticker_instances(instances);
+        }
+
+        if self.apply_paint_generation != state.apply_paint_generation {
+            state.apply_paint_generation = self.apply_paint_generation;
+            if let Some(draft) = &state.paint_draft {
+                let painted = painting::validate(draft).map(|()| draft.clone());
+                return Some(Action::publish(Message::Painted(painted)));
+            }
+        }
+
         if self.reset_generation != state.reset_generation {
             state.hypercube = Hypercube::solved();
+            state.history.clear();
+            state.queued_moves.clear();
+            state.undo_generation = self.undo_generation;
+            state.redo_generation = self.redo_generation;
             state.animating_move = None;
             state.animating_focus = None;
             state.rotate_press = None;
@@ -476,9 +613,14 @@ impl shader::Program<Message> for HypercubeShaderProgram {
         }
 
         if self.random_moves_generation != state.random_moves_generation {
-            state
-                .hypercube
-                .apply_random_moves(self.random_move_count, &mut state.rng);
+            let scramble = state.hypercube.apply_random_moves(
+                self.random_move_count,
+                &mut fastrand::Rng::with_seed(self.random_seed),
+            );
+            state.history.clear();
+            state.queued_moves.clear();
+            state.undo_generation = self.undo_generation;
+            state.redo_generation = self.redo_generation;
             state.animating_move = None;
             state.animating_focus = None;
             state.rotate_press = None;
@@ -489,7 +631,65 @@ impl shader::Program<Message> for HypercubeShaderProgram {
             state.random_moves_generation = self.random_moves_generation;
             let instances = sticker_instances_for_render(state);
             state.set_cached_sticker_instances(instances);
-            return Some(Action::request_redraw());
+            return Some(Action::publish(Message::Scrambled(scramble)));
+        }
+
+        if self.replay_generation != state.replay_generation {
+            state.replay_generation = self.replay_generation;
+            if let Some(replay) = &self.pending_replay {
+                state.hypercube = replay.puzzle();
+                state.history = replay.history.clone();
+                state.queued_moves.clear();
+                state.undo_generation = self.undo_generation;
+                state.redo_generation = self.redo_generation;
+                state.animating_move = None;
+                state.animating_focus = None;
+                state.rotate_press = None;
+                state.pending_face_click = None;
+                state.last_redraw_instant = None;
+                state.hovered_sticker = None;
+                state.debug_instances.clear();
+                let instances = sticker_instances_for_render(state);
+                state.set_cached_sticker_instances(instances);
+                return Some(Action::publish(history_changed(&state.history)));
+            }
+        }
+
+        if self.apply_moves_generation != state.apply_moves_generation {
+            for &mv in self.pending_moves.iter() {
+                state.hypercube.apply_move(mv);
+                state.history.push(mv);
+            }
+            state.queued_moves.clear();
+            state.animating_move = None;
+            state.animating_focus = None;
+            state.rotate_press = None;
+            state.pending_face_click = None;
+            state.last_redraw_instant = None;
+            state.hovered_sticker = None;
+            state.debug_instances.clear();
+            state.apply_moves_generation = self.apply_moves_generation;
+            let instances = sticker_instances_for_render(state);
+            state.set_cached_sticker_instances(instances);
+            return Some(Action::publish(history_changed(&state.history)));
+        }
+
+        if self.save_session_generation != state.save_session_generation {
+            state.save_session_generation = self.save_session_generation;
+            return Some(Action::publish(Message::SessionCaptured(Box::new(
+                state.snapshot(),
+            ))));
+        }
+
+        if self.load_session_generation != state.load_session_generation {
+            state.load_session_generation = self.load_session_generation;
+            if let Some(snapshot) = &self.pending_session {
+                state.restore(snapshot);
+                state.undo_generation = self.undo_generation;
+                state.redo_generation = self.redo_generation;
+                state.camera_controller.update_camera(&mut state.camera);
+                return Some(Action::publish(history_changed(&state.history)));
+            }
         }
 
         // Once `HypercubeApp` has caught up to a completed reveal/hide
@@ -537,6 +737,37 @@ impl shader::Program<Message> for HypercubeShaderProgram {
             return Some(Action::request_redraw());
         }
 
+        if self.undo_generation != state.undo_generation && Self::can_start_move(state) {
+            state.undo_generation = state.undo_generation.wrapping_add(1);
+            if let Some(mv) = state.history.undo() {
+                self.start_move(state, mv.inverse());
+                let instances = sticker_instances_for_render(state);
+                state.set_cached_sticker_instances(instances);
+                return Some(Action::publish(history_changed(&state.history)));
+            }
+        }
+
+        if self.redo_generation != state.redo_generation && Self::can_start_move(state) {
+            state.redo_generation = state.redo_generation.wrapping_add(1);
+            if let Some(mv) = state.history.redo() {
+                self.start_move(state, mv);
+                let instances = sticker_instances_for_render(state);
+                state.set_cached_sticker_instances(instances);
+                return Some(Action::publish(history_changed(&state.history)));
+            }
+        }
+
+        if self.play_generation != state.play_generation {
+            state.play_generation = self.play_generation;
+            state.queued_moves.extend(self.pending_play.iter());
+        }
+
+        if self.start_queued_move(state) {
+            let instances = sticker_instances_for_render(state);
+            state.set_cached_sticker_instances(instances);
+            return Some(Action::publish(history_changed(&state.history)));
+        }
+
         // Update camera each frame
         state.camera_controller.update_camera(&mut state.camera);
 
@@ -554,37 +785,40 @@ impl shader::Program<Message> for HypercubeShaderProgram {
         let mut regenerate_stickers = false;
         let mut reveal_completed_message: Option<Message> = None;
         let mut reset_completed_message: Option<Message> = None;
+        let mut history_changed_message: Option<Message> = None;
+        let mut solved_message: Option<Message> = None;
+
+        // Clicks, drags, keys and finished animations can all start a
+        // queued move (recording it), so these are checked after any event.
+        let was_animating = state.animating_move.is_some();
+        let applied_before = state.history.applied().len();
 
         let status = match event {
             Event::Mouse(mouse_event) => {
                 let old_rotation = state.rotation_4d;
-                let was_animating = state.animating_move.is_some();
                 let result = self.handle_mouse_event(state, mouse_event, bounds, cursor);
                 if state.rotation_4d != old_rotation {
                     rotation_changed = true;
                 }
-                if was_animating || state.animating_move.is_some() {
-                    regenerate_stickers = true;
-                }
                 result
             }
-            Event::Keyboard(keyboard_event) => self.handle_keyboard_event(state, keyboard_event),
+            Event::Keyboard(keyboard_event) => self.handle_keyboard_event(
+                state,
+                keyboard_event,
+                cursor.position_in(bounds).is_some(),
+            ),
             Event::Window(iced::window::Event::RedrawRequested(now)) => {
                 let delta = state
                     .last_redraw_instant
                     .map(|last| now.duration_since(last))
                     .unwrap_or_default();
 
-                let was_animating = state.animating_move.is_some();
+                let move_committed_at = state.animating_move.as_ref().map(|a| a.committed_at);
                 let move_tick = Self::advance_animation(state, delta);
                 let focus_tick = Self::advance_focus_animation(state, delta);
                 let reset_tick = Self::advance_reset_animation(state, delta);
                 let reveal_tick = Self::advance_reveal_animation(state, delta);
 
-                if was_animating || state.animating_move.is_some() {
-                    regenerate_stickers = true;
-                }
-
                 if state.animating_move.is_none()
                     && state.animating_focus.is_none()
                     && state.animating_reset.is_none()
@@ -621,6 +855,13 @@ impl shader::Program<Message> for HypercubeShaderProgram {
                     reset_completed_message = Some(Message::ResetAnimationComplete);
                 }
 
+                if let (AnimationTick::Completed, Some(committed_at)) =
+                    (&move_tick, move_committed_at)
+                    && state.hypercube.is_solved()
+                {
+                    solved_message = Some(Message::Solved { committed_at });
+                }
+
                 if matches!(reveal_tick, AnimationTick::Completed) {
                     let (final_scale, final_gap) = if self.revealed_target {
                         (SECONDARY_STICKER_SCALE, SECONDARY_FACE_GAP)
@@ -633,6 +874,14 @@ impl shader::Program<Message> for HypercubeShaderProgram {
                     });
                 }
 
+                // Chain straight into the next queued move so a played
+                // algorithm turns without a pause - unless this one finished
+                // on a solved puzzle, which has to be reported first; the
+                // next update picks the queue back up.
+                if matches!(move_tick, AnimationTick::Completed) && solved_message.is_none() {
+                    self.start_queued_move(state);
+                }
+
                 if matches!(move_tick, AnimationTick::Ignored)
                     && matches!(focus_tick, AnimationTick::Ignored)
                     && matches!(reset_tick, AnimationTick::Ignored)
@@ -646,6 +895,13 @@ impl shader::Program<Message> for HypercubeShaderProgram {
             _ => event::Status::Ignored,
         };
 
+        if was_animating || state.animating_move.is_some() {
+            regenerate_stickers = true;
+        }
+        if state.history.applied().len() != applied_before {
+            history_changed_message = Some(history_changed(&state.history));
+        }
+
         // Recalculate indices if rotation changed
         if rotation_changed {
             state.set_cached_indices(Self::calculate_indices(&state.rotation_4d));
@@ -655,10 +911,26 @@ impl shader::Program<Message> for HypercubeShaderProgram {
             state.set_cached_sticker_instances(instances);
         }
 
-        if let Some(message) = reset_completed_message.or(reveal_completed_message) {
+        if let Some(message) = reset_completed_message
+            .or(reveal_completed_message)
+            .or(solved_message)
+            .or(history_changed_message)
+        {
             return Some(Action::publish(message));
         }
 
+        // Only with nothing else to publish: a view change made alongside
+        // another one goes out with the next event.
+        if self.record_view {
+            let view = state.view_pose();
+            if state.published_view != Some(view) {
+                state.published_view = Some(view);
+                return Some(Action::publish(Message::ViewChanged(view)));
+            }
+        } else {
+            state.published_view = None;
+        }
+
         match status {
             event::Status::Captured => Some(Action::request_redraw()),
             event::Status::Ignored => None,
@@ -683,6 +955,8 @@ impl shader::Program<Message> for HypercubeShaderProgram {
             cached_indices: state.cached_indices.clone(),
             indices_generation: state.indices_generation,
             hovered_sticker: state.hovered_sticker,
+            highlighted_slots: self.highlighted_slots,
+            hint: self.hint,
             debug_instances: state.debug_instances.clone(),
             sticker_instances: state.cached_sticker_instances.clone(),
             sticker_generation: state.sticker_generation,
@@ -843,10 +1117,21 @@ impl HypercubeShaderProgram {
                     }
                 }
 
+                if let Some((press_pos, sticker_index, offset)) = state.twist_press {
+                    let drag = position - press_pos;
+                    if (drag.x * drag.x + drag.y * drag.y).sqrt() >= CLICK_DRAG_THRESHOLD_PX {
+                        state.twist_press = None;
+                        if Self::accepts_twists(state) {
+                            self.handle_facet_drag(state, sticker_index, offset, drag, bounds);
+                        }
+                    }
+                }
+
                 // Perform ray casting for sticker hover detection (only when not
-                // dragging or mid-animation, since state has already moved past
-                // what's currently rendering)
-                if !state.mouse_pressed && state.animating_move.is_none() {
+                // dragging). Picking only depends on the fixed sticker slots,
+                // so it stays right mid-animation, which is what lets clicks
+                // queue up while a move is still turning.
+                if !state.mouse_pressed {
                     self.update_hover(state, position, bounds);
                 }
 
@@ -869,19 +1154,36 @@ impl HypercubeShaderProgram {
                     state.mouse_pressed = true;
                     return event::Status::Captured;
                 }
-                if cursor.position_in(bounds).is_some()
+                if let Some(position) = cursor.position_in(bounds)
                     && *button == self.rotate_button.click_button()
-                    && state.animating_move.is_none()
-                    && state.animating_focus.is_none()
-                    && state.animating_reset.is_none()
-                    && state.animating_reveal.is_none()
+                    && Self::accepts_twists(state)
                     && let Some(sticker_index) = state.hovered_sticker
                 {
-                    self.handle_facet_click(state, sticker_index);
+                    match (self.paint_brush, &mut state.paint_draft) {
+                        (Some(brush), Some(draft)) => {
+                            painting::paint(draft, sticker_index, brush);
+                            let instances = sticker_instances_for_render(state);
+                            state.set_cached_sticker_instances(instances);
+                        }
+                        // Twists wait to see whether this is a click or a drag.
+                        _ => {
+                            state.twist_press =
+                                Some((position, sticker_index, state.hovered_offset));
+                        }
+                    }
                     return event::Status::Captured;
                 }
             }
-            mouse::Event::ButtonReleased(_) => {
+            mouse::Event::ButtonReleased(button) => {
+                if *button == self.rotate_button.click_button()
+                    && let Some((_, sticker_index, _)) = state.twist_press.take()
+                {
+                    if Self::accepts_twists(state) {
+                        self.handle_facet_click(state, sticker_index);
+                    }
+                    return event::Status::Captured;
+                }
+
                 let was_dragging = state.mouse_pressed;
                 if was_dragging {
                     state.mouse_pressed = false;
@@ -930,7 +1232,7 @@ impl HypercubeShaderProgram {
         let sticker_scale = state.reveal_scale_override.unwrap_or(self.sticker_scale);
         let face_gap = state.reveal_gap_override.unwrap_or(self.face_gap);
 
-        let (hovered_sticker, debug_instances) = find_intersected_sticker(
+        let (hit, debug_instances) = find_intersected_sticker(
             &mouse_ray,
             state,
             sticker_scale,
@@ -938,7 +1240,8 @@ impl HypercubeShaderProgram {
             VIEWER_DISTANCE,
             self.aabb_mode,
         );
-        state.hovered_sticker = hovered_sticker;
+        state.hovered_sticker = hit.map(|hit| hit.sticker);
+        state.hovered_offset = hit.map_or(Vector3::zeros(), |hit| hit.offset);
         state.debug_instances = debug_instances;
     }
 
@@ -975,18 +1278,39 @@ impl HypercubeShaderProgram {
                 last_face == face_id && now.duration_since(last_time) <= DOUBLE_CLICK_WINDOW
             });
 
+        // A recorded reorientation would turn the puzzle behind the draft.
         if is_double_click
+            && state.paint_draft.is_none()
             && state.animating_move.is_none()
             && state.animating_focus.is_none()
             && state.animating_reset.is_none()
         {
-            self.start_focus_animation(state, face_id);
+            self.focus_face(state, face_id);
             state.pending_face_click = None;
         } else {
             state.pending_face_click = Some((now, face_id));
         }
     }
 
+    /// Brings the cell `face_id` to the front: a recorded whole-puzzle
+    /// rotation carrying it onto whichever cell faces the viewer now, so the
+    /// reorientation is part of the puzzle's own state and its history.
+    /// Double-clicking the cell already in front instead eases the view
+    /// round until it's exactly centered.
+    fn focus_face(&self, state: &mut HypercubeShaderState, face_id: usize) {
+        let facing = |face: &usize| (state.rotation_4d * FACE_CENTERS[*face]).dot(&FACE_CENTERS[0]);
+        let front = (0..FACE_CENTERS.len())
+            .max_by(|a, b| facing(a).total_cmp(&facing(b)))
+            .expect("there are faces");
+        match reorientation(FACE_AXIS_SIGN[face_id], FACE_AXIS_SIGN[front]) {
+            Some(mv) => {
+                state.history.push(mv);
+                self.start_move(state, mv);
+            }
+            None => self.start_focus_animation(state, face_id),
+        }
+    }
+
     /// Starts an animation that reorients the puzzle in 4D so `face_id`'s
     /// normal ends up centered and facing the viewer. The target is
     /// `FACE_CENTERS[0]` (W=-1), not `FACE_CENTERS[7]` (W=+1): the latter is
@@ -1008,45 +1332,139 @@ impl HypercubeShaderProgram {
         state.last_redraw_instant = None;
     }
 
-    /// Applies the move triggered by clicking the given facet, if any -
-    /// non-actionable facets (cell-centers, the invisible center) are a
-    /// no-op. A plain click always turns clockwise as viewed from beyond the
-    /// clicked facet, looking back in along its own rotation axis
-    /// (`moves::clockwise_sign`) - independent of the puzzle's current
-    /// orientation or camera position. Shift reverses it to
-    /// counterclockwise.
+    /// Applies the move triggered by clicking (pressing and releasing in
+    /// place) the given facet, if any - non-actionable facets (cell-centers,
+    /// the invisible center) are a no-op. A plain click always turns
+    /// clockwise as viewed from beyond the clicked facet, looking back in
+    /// along its own rotation axis (`moves::clockwise_sign`) - independent
+    /// of the puzzle's current orientation or camera position. Shift reverses it to
+    /// counterclockwise. Alt turns the middle slice parallel to the clicked
+    /// side instead, Ctrl the side and middle slice together, and both the
+    /// whole puzzle.
     fn handle_facet_click(&self, state: &mut HypercubeShaderState, sticker_index: usize) {
         let facet = &FACET_TABLE[sticker_index];
         if !facet.is_actionable {
             return;
         }
 
-        let local_nonzero_count = facet.local_coords.iter().filter(|c| **c != 0).count();
-        let magnitude = base_angle(local_nonzero_count);
+        let clockwise = clockwise_sign(facet) as i8;
+        let turns = if state.shift_pressed {
+            -clockwise
+        } else {
+            clockwise
+        };
+
+        let mv = Move::new(facet.axis, facet.side_sign, facet.local_coords, turns)
+            .with_layers(Self::held_layers(state));
+        self.queue_move(state, mv);
+    }
+
+    /// Makes the twist a drag from the sticker's `offset` follows, if any
+    /// (see `drag_twist.rs`), on the layers the held modifiers pick. The
+    /// drag's direction is all that matters, read through the same
+    /// projection the renderer draws the sticker with.
+    fn handle_facet_drag(
+        &self,
+        state: &mut HypercubeShaderState,
+        sticker_index: usize,
+        offset: Vector3<f32>,
+        drag: iced::Vector,
+        bounds: Rectangle,
+    ) {
+        let face_gap = state.reveal_gap_override.unwrap_or(self.face_gap);
+        let push = face_push_offset_3d(
+            FACE_CENTERS[FACET_TABLE[sticker_index].face_id],
+            &state.rotation_4d,
+            VIEWER_DISTANCE,
+        ) * face_gap;
+        let to_screen = |point_4d| {
+            let point = project_4d_to_3d(point_4d, &state.rotation_4d, VIEWER_DISTANCE) + push;
+            project_to_screen(point, bounds, &state.camera, &state.projection)
+                .map(|screen| nalgebra::Vector2::new(screen.x, screen.y))
+        };
+        let Some(mv) = drag_twist(
+            sticker_index,
+            offset,
+            nalgebra::Vector2::new(drag.x, drag.y),
+            to_screen,
+        ) else {
+            return;
+        };
+
+        self.queue_move(state, mv.with_layers(Self::held_layers(state)));
+    }
 
-        let sign = clockwise_sign(facet);
-        let angle = if state.shift_pressed {
-            -sign * magnitude
+    /// The layers the held modifiers pick for a twist: Alt the middle
+    /// slice, Ctrl the side and middle slice, both the whole puzzle.
+    fn held_layers(state: &HypercubeShaderState) -> Layers {
+        if state.alt_pressed && state.control_pressed {
+            Layers::All
+        } else if state.alt_pressed {
+            Layers::Middle
+        } else if state.control_pressed {
+            Layers::Wide
         } else {
-            sign * magnitude
+            Layers::Outer
+        }
+    }
+
+    /// Queues a twist made by clicking, dragging or the keyboard behind any
+    /// still to play, starting it straight away if nothing is animating.
+    /// Input made mid-animation is never dropped, only delayed.
+    fn queue_move(&self, state: &mut HypercubeShaderState, mv: Move) {
+        state.queued_moves.push_back(mv);
+        self.start_queued_move(state);
+    }
+
+    /// Records and starts the next of `queued_moves`, if there is one and
+    /// nothing else is animating. Returns whether it did.
+    fn start_queued_move(&self, state: &mut HypercubeShaderState) -> bool {
+        if !Self::can_start_move(state) {
+            return false;
+        }
+        let Some(mv) = state.queued_moves.pop_front() else {
+            return false;
         };
+        state.history.push(mv);
+        self.start_move(state, mv);
+        true
+    }
+
+    /// Whether twist input counts right now: not during a reset's or a
+    /// reveal's animation, which drop or lock out the puzzle's moves. Other
+    /// animations just delay a twist in `queued_moves`.
+    fn accepts_twists(state: &HypercubeShaderState) -> bool {
+        state.animating_reset.is_none() && state.animating_reveal.is_none()
+    }
 
+    /// Whether a new move may start right now: the same conditions a queued
+    /// twist waits for, so undo/redo never overlap another animation.
+    fn can_start_move(state: &HypercubeShaderState) -> bool {
+        state.animating_move.is_none()
+            && state.animating_focus.is_none()
+            && state.animating_reset.is_none()
+            && state.animating_reveal.is_none()
+    }
+
+    /// Commits `mv` to the puzzle and starts its turn animation. Shared by
+    /// turn-clicks and undo/redo, so an undone move visibly turns back the
+    /// same way a clicked one turns forward. Doesn't touch `history` -
+    /// callers record (or step through) it themselves. The animation is
+    /// shortened by `QUEUE_SPEEDUP` per move still waiting behind it.
+    fn start_move(&self, state: &mut HypercubeShaderState, mv: Move) {
         let pre_move_pieces = state.hypercube.pieces.clone();
-        state
-            .hypercube
-            .apply_move(facet.axis, facet.side_sign, facet.local_coords, angle);
+        state.hypercube.apply_move(mv);
 
+        let waiting = i32::try_from(state.queued_moves.len()).unwrap_or(i32::MAX);
+        let speedup = QUEUE_SPEEDUP.powi(waiting).max(MIN_QUEUED_DURATION);
         state.animating_move = Some(AnimatingMove {
-            side_axis: facet.axis,
-            side_sign: facet.side_sign,
-            local_coords: facet.local_coords,
-            angle,
+            mv,
             pre_move_pieces,
             elapsed: Duration::ZERO,
-            duration: Duration::from_millis(self.animation_duration_ms as u64),
+            duration: Duration::from_millis(self.animation_duration_ms as u64).mul_f32(speedup),
+            committed_at: Instant::now(),
         });
         state.last_redraw_instant = None;
-        state.hovered_sticker = None;
     }
 
     /// Advances the in-progress move animation (if any) by the time elapsed
@@ -1175,6 +1593,7 @@ impl HypercubeShaderProgram {
         &self,
         state: &mut HypercubeShaderState,
         keyboard_event: &iced::keyboard::Event,
+        over_puzzle: bool,
     ) -> event::Status {
         use iced::keyboard::Event;
         use iced::keyboard::{Key, key};
@@ -1193,6 +1612,60 @@ impl HypercubeShaderProgram {
                 state.shift_pressed = false;
                 return event::Status::Captured;
             }
+            Event::KeyPressed {
+                key: Key::Named(key::Named::Alt),
+                ..
+            } => {
+                state.alt_pressed = true;
+                return event::Status::Captured;
+            }
+            Event::KeyReleased {
+                key: Key::Named(key::Named::Alt),
+                ..
+            } => {
+                state.alt_pressed = false;
+                return event::Status::Captured;
+            }
+            Event::KeyPressed {
+                key: Key::Named(key::Named::Control),
+                ..
+            } => {
+                state.control_pressed = true;
+                return event::Status::Captured;
+            }
+            Event::KeyReleased {
+                key: Key::Named(key::Named::Control),
+                ..
+            } => {
+                state.control_pressed = false;
+                return event::Status::Captured;
+            }
+            // Twist keys only count with the cursor over the puzzle, so
+            // typing into the panel's text boxes doesn't twist it.
+            Event::KeyPressed {
+                key: Key::Character(key),
+                ..
+            } if over_puzzle
+                && state.paint_draft.is_none()
+                && state.twist_keys.press(&self.key_bindings, key) =>
+            {
+                return event::Status::Captured;
+            }
+            // Releases always count, so held keys are never left stuck.
+            Event::KeyReleased {
+                key: Key::Character(key),
+                ..
+            } => {
+                let layers = Self::held_layers(state);
+                if let Some(mv) =
+                    state
+                        .twist_keys
+                        .release(&self.key_bindings, key, layers, state.shift_pressed)
+                {
+                    self.queue_move(state, mv);
+                    return event::Status::Captured;
+                }
+            }
             _ => {}
         }
 
@@ -1231,11 +1704,14 @@ impl Default for HypercubeShaderState {
             mouse_pressed: false,
             last_mouse_pos: None,
             shift_pressed: false,
+            alt_pressed: false,
+            control_pressed: false,
             cached_indices,
             indices_generation: 0,
             cached_sticker_instances,
             sticker_generation: 0,
             hovered_sticker: None,
+            hovered_offset: Vector3::zeros(),
             debug_instances: Vec::new(),
             hypercube,
             animating_move: None,
@@ -1245,12 +1721,25 @@ impl Default for HypercubeShaderState {
             reveal_scale_override: None,
             reveal_gap_override: None,
             rotate_press: None,
+            twist_press: None,
             pending_face_click: None,
             last_redraw_instant: None,
             reset_generation: 0,
             random_moves_generation: 0,
-            rng: fastrand::Rng::new(),
             reveal_generation: 0,
+            history: MoveHistory::default(),
+            undo_generation: 0,
+            redo_generation: 0,
+            apply_moves_generation: 0,
+            save_session_generation: 0,
+            load_session_generation: 0,
+            replay_generation: 0,
+            play_generation: 0,
+            queued_moves: VecDeque::new(),
+            paint_draft: None,
+            apply_paint_generation: 0,
+            twist_keys: TwistKeys::default(),
+            published_view: None,
         }
     }
 }
@@ -1259,8 +1748,46 @@ impl Default for HypercubeShaderState {
 mod tests {
     use super::*;
     use crate::geometry::FACE_CENTERS;
+    use crate::piece::{face_id_for, side_color};
+    use crate::solver;
     use iced::widget::shader::Program;
 
+    /// A program with nothing requested, for tests to override the fields
+    /// they exercise.
+    fn test_program() -> HypercubeShaderProgram {
+        HypercubeShaderProgram {
+            sticker_scale: 0.9,
+            face_gap: 0.0,
+            render_mode: RenderMode::Standard,
+            aabb_mode: AABBMode::None,
+            rotate_button: RotateButton::default(),
+            animation_duration_ms: 250,
+            reset_generation: 0,
+            random_moves_generation: 0,
+            random_move_count: 0,
+            random_seed: 0,
+            reveal_generation: 0,
+            revealed_target: false,
+            undo_generation: 0,
+            redo_generation: 0,
+            apply_moves_generation: 0,
+            pending_moves: Arc::from([]),
+            save_session_generation: 0,
+            load_session_generation: 0,
+            pending_session: None,
+            replay_generation: 0,
+            pending_replay: None,
+            play_generation: 0,
+            pending_play: Arc::from([]),
+            highlighted_slots: [0; 4],
+            hint: None,
+            paint_brush: None,
+            apply_paint_generation: 0,
+            key_bindings: Arc::new(KeyBindings::default()),
+            record_view: false,
+        }
+    }
+
     fn round_key(v: [f32; 4]) -> [i32; 4] {
         v.map(|x| (x * 1000.0).round() as i32)
     }
@@ -1310,20 +1837,17 @@ mod tests {
         for side_axis in 0..4usize {
             for side_sign in [-1i8, 1] {
                 for local_coords in [[1i8, 0, 0], [1, 1, 0], [1, 1, 1]] {
-                    let nonzero = local_coords.iter().filter(|c| **c != 0).count();
-                    let angle = base_angle(nonzero);
+                    let mv = Move::new(side_axis, side_sign, local_coords, 1);
 
                     let pre_move = Hypercube::solved();
                     let state = HypercubeShaderState {
                         hypercube: pre_move.clone(),
                         animating_move: Some(AnimatingMove {
-                            side_axis,
-                            side_sign,
-                            local_coords,
-                            angle,
+                            mv,
                             pre_move_pieces: pre_move.pieces.clone(),
                             elapsed: Duration::ZERO,
                             duration: Duration::from_millis(250),
+                            committed_at: Instant::now(),
                         }),
                         ..Default::default()
                     };
@@ -1351,20 +1875,17 @@ mod tests {
         for side_axis in 0..4usize {
             for side_sign in [-1i8, 1] {
                 for local_coords in [[1i8, 0, 0], [1, 1, 0], [1, 1, 1]] {
-                    let nonzero = local_coords.iter().filter(|c| **c != 0).count();
-                    let angle = base_angle(nonzero);
+                    let mv = Move::new(side_axis, side_sign, local_coords, 1);
 
                     let pre_move = Hypercube::solved();
                     let state = HypercubeShaderState {
                         hypercube: pre_move.clone(),
                         animating_move: Some(AnimatingMove {
-                            side_axis,
-                            side_sign,
-                            local_coords,
-                            angle,
+                            mv,
                             pre_move_pieces: pre_move.pieces.clone(),
                             elapsed: Duration::ZERO,
                             duration: Duration::from_millis(250),
+                            committed_at: Instant::now(),
                         }),
                         ..Default::default()
                     };
@@ -1400,10 +1921,9 @@ mod tests {
         for side_axis in 0..4usize {
             for side_sign in [-1i8, 1] {
                 for local_coords in [[1i8, 0, 0], [1, 1, 0], [1, 1, 1]] {
-                    let nonzero = local_coords.iter().filter(|c| **c != 0).count();
-                    let angle = base_angle(nonzero);
+                    let mv = Move::new(side_axis, side_sign, local_coords, 1);
                     let axes = free_axes(side_axis);
-                    let (perm, sign) = discrete_rotation(local_coords, angle);
+                    let (perm, sign) = discrete_rotation(mv);
                     let mut inv_perm = [0usize; 3];
                     for slot in 0..3 {
                         inv_perm[perm[slot]] = slot;
@@ -1413,13 +1933,11 @@ mod tests {
                     let state = HypercubeShaderState {
                         hypercube: pre_move.clone(),
                         animating_move: Some(AnimatingMove {
-                            side_axis,
-                            side_sign,
-                            local_coords,
-                            angle,
+                            mv,
                             pre_move_pieces: pre_move.pieces.clone(),
                             elapsed: Duration::from_millis(250),
                             duration: Duration::from_millis(250),
+                            committed_at: Instant::now(),
                         }),
                         ..Default::default()
                     };
@@ -1476,24 +1994,25 @@ mod tests {
                     [0, 1, 1],
                     [1, 1, 1],
                 ] {
-                    for direction in [1i8, -1] {
-                        let nonzero = local_coords.iter().filter(|c| **c != 0).count();
-                        let angle = base_angle(nonzero) * direction as f32;
+                    for (direction, layers) in [1i8, -1].into_iter().flat_map(|direction| {
+                        [Layers::Outer, Layers::Middle, Layers::Wide, Layers::All]
+                            .map(|layers| (direction, layers))
+                    }) {
+                        let mv = Move::new(side_axis, side_sign, local_coords, direction)
+                            .with_layers(layers);
 
                         let pre_move = Hypercube::solved();
                         let mut post_move = pre_move.clone();
-                        post_move.apply_move(side_axis, side_sign, local_coords, angle);
+                        post_move.apply_move(mv);
 
                         let state = HypercubeShaderState {
                             hypercube: pre_move.clone(),
                             animating_move: Some(AnimatingMove {
-                                side_axis,
-                                side_sign,
-                                local_coords,
-                                angle,
+                                mv,
                                 pre_move_pieces: pre_move.pieces.clone(),
                                 elapsed: Duration::from_millis(250),
                                 duration: Duration::from_millis(250),
+                                committed_at: Instant::now(),
                             }),
                             ..Default::default()
                         };
@@ -1527,7 +2046,8 @@ mod tests {
                         assert_eq!(
                             animated_end, static_post,
                             "mismatch for side_axis={side_axis} side_sign={side_sign} \
-                             local_coords={local_coords:?} direction={direction}"
+                             local_coords={local_coords:?} direction={direction} \
+                             layers={layers:?}"
                         );
                     }
                 }
@@ -1559,10 +2079,9 @@ mod tests {
                     [1, 1, 1],
                 ] {
                     for direction in [1i8, -1] {
-                        let nonzero = local_coords.iter().filter(|c| **c != 0).count();
-                        let angle = base_angle(nonzero) * direction as f32;
+                        let mv = Move::new(side_axis, side_sign, local_coords, direction);
                         let axes = free_axes(side_axis);
-                        let (perm, _sign) = discrete_rotation(local_coords, angle);
+                        let (perm, _sign) = discrete_rotation(mv);
                         let mut inv_perm = [0usize; 3];
                         for slot in 0..3 {
                             inv_perm[perm[slot]] = slot;
@@ -1572,13 +2091,11 @@ mod tests {
                         let state = HypercubeShaderState {
                             hypercube: pre_move.clone(),
                             animating_move: Some(AnimatingMove {
-                                side_axis,
-                                side_sign,
-                                local_coords,
-                                angle,
+                                mv,
                                 pre_move_pieces: pre_move.pieces.clone(),
                                 elapsed: Duration::from_millis(250),
                                 duration: Duration::from_millis(250),
+                                committed_at: Instant::now(),
                             }),
                             ..Default::default()
                         };
@@ -1637,36 +2154,24 @@ mod tests {
             .iter()
             .find(|f| f.is_actionable)
             .expect("at least one actionable facet exists");
-        let nonzero = facet.local_coords.iter().filter(|c| **c != 0).count();
-        let angle = base_angle(nonzero);
+        let mv = Move::new(facet.axis, facet.side_sign, facet.local_coords, 1);
         let pre_move_pieces = state.hypercube.pieces.clone();
-        state
-            .hypercube
-            .apply_move(facet.axis, facet.side_sign, facet.local_coords, angle);
+        state.hypercube.apply_move(mv);
         assert!(!state.hypercube.is_solved());
         state.animating_move = Some(AnimatingMove {
-            side_axis: facet.axis,
-            side_sign: facet.side_sign,
-            local_coords: facet.local_coords,
-            angle,
+            mv,
             pre_move_pieces,
             elapsed: Duration::ZERO,
             duration: Duration::from_millis(250),
+            committed_at: Instant::now(),
         });
 
-        let program = HypercubeShaderProgram::new(
-            0.5,
-            2.0,
-            RenderMode::Standard,
-            AABBMode::None,
-            RotateButton::default(),
-            250,
-            1,
-            0,
-            0,
-            0,
-            false,
-        );
+        let program = HypercubeShaderProgram {
+            sticker_scale: 0.5,
+            face_gap: 2.0,
+            reset_generation: 1,
+            ..test_program()
+        };
 
         let bounds = Rectangle::new(Point::ORIGIN, iced::Size::new(800.0, 600.0));
         let sticker_generation_before = state.sticker_generation;
@@ -1711,28 +2216,20 @@ mod tests {
 
     #[test]
     fn random_moves_generation_mismatch_applies_moves_and_cancels_animation() {
-        let mut state = HypercubeShaderState {
-            rng: fastrand::Rng::with_seed(1),
-            ..Default::default()
-        };
+        let mut state = HypercubeShaderState::default();
         assert_eq!(state.random_moves_generation, 0);
 
         let mut expected = Hypercube::solved();
         expected.apply_random_moves(3, &mut fastrand::Rng::with_seed(1));
 
-        let program = HypercubeShaderProgram::new(
-            0.5,
-            2.0,
-            RenderMode::Standard,
-            AABBMode::None,
-            RotateButton::default(),
-            250,
-            0,
-            1,
-            3,
-            0,
-            false,
-        );
+        let program = HypercubeShaderProgram {
+            sticker_scale: 0.5,
+            face_gap: 2.0,
+            random_moves_generation: 1,
+            random_move_count: 3,
+            random_seed: 1,
+            ..test_program()
+        };
 
         let bounds = Rectangle::new(Point::ORIGIN, iced::Size::new(800.0, 600.0));
         let sticker_generation_before = state.sticker_generation;
@@ -1760,19 +2257,12 @@ mod tests {
         let mut state = HypercubeShaderState::default();
         assert!(state.hypercube.is_solved());
 
-        let program = HypercubeShaderProgram::new(
-            0.5,
-            2.0,
-            RenderMode::Standard,
-            AABBMode::None,
-            RotateButton::default(),
-            250,
-            0,
-            1,
-            0,
-            0,
-            false,
-        );
+        let program = HypercubeShaderProgram {
+            sticker_scale: 0.5,
+            face_gap: 2.0,
+            random_moves_generation: 1,
+            ..test_program()
+        };
 
         let bounds = Rectangle::new(Point::ORIGIN, iced::Size::new(800.0, 600.0));
         let action = program.update(
@@ -1794,19 +2284,12 @@ mod tests {
     #[test]
     fn idle_redraw_does_not_bump_generations() {
         let mut state = HypercubeShaderState::default();
-        let program = HypercubeShaderProgram::new(
-            0.9,
-            0.0,
-            RenderMode::Standard,
-            AABBMode::None,
-            RotateButton::default(),
-            250,
-            state.reset_generation,
-            state.random_moves_generation,
-            0,
-            state.reveal_generation,
-            false,
-        );
+        let program = HypercubeShaderProgram {
+            reset_generation: state.reset_generation,
+            random_moves_generation: state.random_moves_generation,
+            reveal_generation: state.reveal_generation,
+            ..test_program()
+        };
         let bounds = Rectangle::new(Point::ORIGIN, iced::Size::new(800.0, 600.0));
 
         program.update(
@@ -1834,19 +2317,13 @@ mod tests {
         let sticker_generation_before = state.sticker_generation;
 
         let rotate_button = RotateButton::default();
-        let program = HypercubeShaderProgram::new(
-            0.9,
-            0.0,
-            RenderMode::Standard,
-            AABBMode::None,
+        let program = HypercubeShaderProgram {
             rotate_button,
-            250,
-            state.reset_generation,
-            state.random_moves_generation,
-            0,
-            state.reveal_generation,
-            false,
-        );
+            reset_generation: state.reset_generation,
+            random_moves_generation: state.random_moves_generation,
+            reveal_generation: state.reveal_generation,
+            ..test_program()
+        };
         let bounds = Rectangle::new(Point::ORIGIN, iced::Size::new(800.0, 600.0));
         let cursor = mouse::Cursor::Available(Point::new(10.0, 10.0));
 
@@ -1856,11 +2333,486 @@ mod tests {
             bounds,
             cursor,
         );
+        assert!(
+            state.animating_move.is_none(),
+            "a press could still become a drag"
+        );
+        program.update(
+            &mut state,
+            &Event::Mouse(mouse::Event::ButtonReleased(rotate_button.click_button())),
+            bounds,
+            cursor,
+        );
 
         assert!(state.animating_move.is_some(), "click must start a move");
         assert_eq!(state.sticker_generation, sticker_generation_before + 1);
     }
 
+    /// Clicks landing while a move is still turning aren't dropped: they
+    /// queue behind it and play in order, faster while more are waiting.
+    #[test]
+    fn clicks_during_a_move_animation_queue_up_and_speed_up() {
+        // A face sticker: a quarter turn, so three clicks don't solve it.
+        let mut state = HypercubeShaderState {
+            hovered_sticker: FACET_TABLE.iter().position(|f| {
+                f.is_actionable && f.local_coords.iter().filter(|&&c| c != 0).count() == 1
+            }),
+            ..Default::default()
+        };
+        let rotate_button = RotateButton::default();
+        let program = HypercubeShaderProgram {
+            rotate_button,
+            ..test_program()
+        };
+        let bounds = Rectangle::new(Point::ORIGIN, iced::Size::new(800.0, 600.0));
+        let cursor = mouse::Cursor::Available(Point::new(10.0, 10.0));
+        for _ in 0..3 {
+            for event in [
+                mouse::Event::ButtonPressed(rotate_button.click_button()),
+                mouse::Event::ButtonReleased(rotate_button.click_button()),
+            ] {
+                program.update(&mut state, &Event::Mouse(event), bounds, cursor);
+            }
+        }
+        assert_eq!(state.history.applied().len(), 1, "the first click plays");
+        assert_eq!(state.queued_moves.len(), 2, "the rest wait");
+
+        let redraw = Event::Window(iced::window::Event::RedrawRequested(Instant::now()));
+        let full = Duration::from_millis(250);
+        let mut durations = Vec::new();
+        while let Some(animating) = state.animating_move.as_mut() {
+            durations.push(animating.duration);
+            animating.elapsed = animating.duration;
+            program.update(&mut state, &redraw, bounds, mouse::Cursor::Unavailable);
+        }
+        assert_eq!(state.history.applied().len(), 3);
+        assert!(state.queued_moves.is_empty());
+        assert_eq!(durations[0], full);
+        assert!(durations[1] < full, "one more was waiting behind it");
+        assert_eq!(durations[2], full);
+    }
+
+    /// Dragging across a sticker twists its cell so the sticker follows the
+    /// drag, and the release after it doesn't click as well.
+    #[test]
+    fn dragging_a_sticker_twists_its_cell_along_the_drag() {
+        // The front (w = -1) cell's sticker right of its center.
+        let from = [1, 0, 0, -1];
+        let mut state = HypercubeShaderState::default();
+        let slot = crate::piece::index_of(from);
+        state.hovered_sticker = FACET_TABLE
+            .iter()
+            .position(|f| f.piece_slot == slot && f.axis == 3);
+
+        let rotate_button = RotateButton::default();
+        let program = HypercubeShaderProgram {
+            rotate_button,
+            ..test_program()
+        };
+        let bounds = Rectangle::new(Point::ORIGIN, iced::Size::new(800.0, 600.0));
+        let press = Point::new(500.0, 300.0);
+        program.update(
+            &mut state,
+            &Event::Mouse(mouse::Event::ButtonPressed(rotate_button.click_button())),
+            bounds,
+            mouse::Cursor::Available(press),
+        );
+        state.last_mouse_pos = Some(press);
+        let dragged = Point::new(500.0, 280.0);
+        program.update(
+            &mut state,
+            &Event::Mouse(mouse::Event::CursorMoved { position: dragged }),
+            bounds,
+            mouse::Cursor::Available(dragged),
+        );
+        program.update(
+            &mut state,
+            &Event::Mouse(mouse::Event::ButtonReleased(rotate_button.click_button())),
+            bounds,
+            mouse::Cursor::Available(dragged),
+        );
+
+        assert!(state.animating_move.is_some(), "the drag must start a move");
+        assert_eq!(state.history.applied().len(), 1, "and only one");
+        let piece = state
+            .hypercube
+            .pieces
+            .iter()
+            .find(|piece| piece.home() == from)
+            .unwrap();
+        assert_eq!(piece.position, [0, 1, 0, -1], "dragged up");
+    }
+
+    /// Undo must turn the last clicked move back through the move animation
+    /// (not snap), and redo must replay it - each handled press advancing
+    /// its generation by exactly one step.
+    #[test]
+    fn undo_and_redo_generations_replay_history_through_move_animation() {
+        let mut state = HypercubeShaderState::default();
+        let sticker_index = FACET_TABLE
+            .iter()
+            .position(|f| f.is_actionable)
+            .expect("at least one actionable facet exists");
+        let program_at = |undo_generation, redo_generation| HypercubeShaderProgram {
+            undo_generation,
+            redo_generation,
+            ..test_program()
+        };
+        let bounds = Rectangle::new(Point::ORIGIN, iced::Size::new(800.0, 600.0));
+        let redraw = Event::Window(iced::window::Event::RedrawRequested(Instant::now()));
+
+        program_at(0, 0).handle_facet_click(&mut state, sticker_index);
+        let clicked = state.hypercube.clone();
+        assert!(state.history.can_undo());
+        state.animating_move = None;
+
+        let action =
+            program_at(1, 0).update(&mut state, &redraw, bounds, mouse::Cursor::Unavailable);
+        assert!(action.is_some(), "undo must publish the new history state");
+        assert!(state.hypercube.is_solved());
+        assert!(state.animating_move.is_some(), "undo must animate");
+        assert_eq!(state.undo_generation, 1);
+        assert!(!state.history.can_undo());
+        assert!(state.history.can_redo());
+        state.animating_move = None;
+
+        program_at(1, 1).update(&mut state, &redraw, bounds, mouse::Cursor::Unavailable);
+        assert_eq!(state.hypercube, clicked);
+        assert!(state.animating_move.is_some(), "redo must animate");
+        assert_eq!(state.redo_generation, 1);
+        assert!(state.history.can_undo());
+        assert!(!state.history.can_redo());
+    }
+
+    /// Pasted notation must land on the puzzle in one step (no animation)
+    /// and be recorded in history, so it can be undone move by move.
+    #[test]
+    fn apply_moves_generation_applies_and_records_pending_moves() {
+        let mut state = HypercubeShaderState::default();
+        let moves: Arc<[Move]> = crate::moves::parse_moves("RU OBR' IUFR2")
+            .expect("valid notation")
+            .into();
+        let program = HypercubeShaderProgram {
+            apply_moves_generation: 1,
+            pending_moves: moves.clone(),
+            ..test_program()
+        };
+        let bounds = Rectangle::new(Point::ORIGIN, iced::Size::new(800.0, 600.0));
+        let redraw = Event::Window(iced::window::Event::RedrawRequested(Instant::now()));
+
+        let action = program.update(&mut state, &redraw, bounds, mouse::Cursor::Unavailable);
+        assert!(action.is_some(), "must publish the new history state");
+        assert_eq!(state.apply_moves_generation, 1);
+        assert!(state.animating_move.is_none());
+        assert_eq!(state.history.applied(), &moves[..]);
+
+        let mut expected = Hypercube::solved();
+        for &mv in moves.iter() {
+            expected.apply_move(mv);
+        }
+        assert_eq!(state.hypercube, expected);
+    }
+
+    /// A played algorithm turns one animated move after another, each
+    /// recorded as it starts, with no pause between them.
+    #[test]
+    fn play_generation_animates_pending_moves_one_after_another() {
+        let mut state = HypercubeShaderState::default();
+        let moves: Arc<[Move]> = crate::algorithm::parse_alg("[RU, LB]")
+            .expect("valid expression")
+            .expand()
+            .into();
+        let program = HypercubeShaderProgram {
+            play_generation: 1,
+            pending_play: moves.clone(),
+            ..test_program()
+        };
+        let bounds = Rectangle::new(Point::ORIGIN, iced::Size::new(800.0, 600.0));
+        let redraw = Event::Window(iced::window::Event::RedrawRequested(Instant::now()));
+
+        let action = program.update(&mut state, &redraw, bounds, mouse::Cursor::Unavailable);
+        assert!(action.is_some(), "must publish the new history state");
+        assert_eq!(state.play_generation, 1);
+        for played in 1..=moves.len() {
+            assert_eq!(state.history.applied(), &moves[..played]);
+            let animating = state.animating_move.as_mut().expect("each move animates");
+            assert_eq!(animating.mv, moves[played - 1]);
+            animating.elapsed = animating.duration;
+            program.update(&mut state, &redraw, bounds, mouse::Cursor::Unavailable);
+        }
+        assert!(state.animating_move.is_none());
+        assert!(state.queued_moves.is_empty());
+        assert!(state.hypercube.is_solved());
+    }
+
+    /// The app works the solver's solution out on its own thread and hands
+    /// it over as a played move list, like an algorithm.
+    #[test]
+    fn play_generation_plays_the_solvers_solution() {
+        let mut state = HypercubeShaderState::default();
+        for mv in crate::moves::parse_moves("RU {2}OBR'").expect("valid notation") {
+            state.hypercube.apply_move(mv);
+        }
+        let solution = solver::solve(&state.hypercube).expect("reached by moves");
+        let program = HypercubeShaderProgram {
+            play_generation: 1,
+            pending_play: Arc::from(solution.clone()),
+            ..test_program()
+        };
+        let bounds = Rectangle::new(Point::ORIGIN, iced::Size::new(800.0, 600.0));
+        let redraw = Event::Window(iced::window::Event::RedrawRequested(Instant::now()));
+
+        program.update(&mut state, &redraw, bounds, mouse::Cursor::Unavailable);
+        assert_eq!(state.play_generation, 1);
+        while let Some(animating) = state.animating_move.as_mut() {
+            animating.elapsed = animating.duration;
+            program.update(&mut state, &redraw, bounds, mouse::Cursor::Unavailable);
+        }
+        assert_eq!(state.history.applied(), &solution[..]);
+        assert_eq!(state.hypercube, Hypercube::solved());
+    }
+
+    /// Painting mode paints a copy of the puzzle, shown in its place; Apply
+    /// publishes what's wrong with the draft, or moves reaching it.
+    #[test]
+    fn painted_draft_is_validated_when_applied() {
+        let program =
+            |paint_brush: Option<Brush>, apply_paint_generation: u64| HypercubeShaderProgram {
+                paint_brush,
+                apply_paint_generation,
+                ..test_program()
+            };
+        let bounds = Rectangle::new(Point::ORIGIN, iced::Size::new(800.0, 600.0));
+        let redraw = Event::Window(iced::window::Event::RedrawRequested(Instant::now()));
+        let published = |state: &mut HypercubeShaderState, program: &HypercubeShaderProgram| {
+            program
+                .update(state, &redraw, bounds, mouse::Cursor::Unavailable)
+                .and_then(|action| action.into_inner().0)
+        };
+
+        let mut state = HypercubeShaderState::default();
+        state
+            .hypercube
+            .apply_move(crate::moves::parse_move("RU", 0).expect("valid notation"));
+        let painting = program(Some(Brush::Cycle), 0);
+        assert!(published(&mut state, &painting).is_none());
+        let draft = state.paint_draft.as_mut().expect("painting starts a draft");
+        assert_eq!(*draft, state.hypercube);
+
+        // One sticker stepped on to the next color can't be right.
+        painting::paint(draft, 0, Brush::Cycle);
+        let painted = generate_sticker_instances(draft);
+        assert_eq!(
+            sticker_instances_for_render(&state)[0].color,
+            painted[0].color
+        );
+        assert!(matches!(
+            published(&mut state, &program(Some(Brush::Cycle), 1)),
+            Some(Message::Painted(Err(_)))
+        ));
+
+        // Stepped all the way round, it's the turned puzzle again.
+        let draft = state.paint_draft.as_mut().expect("still painting");
+        for _ in 1..crate::piece::COLORS.len() {
+            painting::paint(draft, 0, Brush::Cycle);
+        }
+        let Some(Message::Painted(Ok(painted))) =
+            published(&mut state, &program(Some(Brush::Cycle), 2))
+        else {
+            panic!("a reachable draft must be published");
+        };
+        assert_eq!(painted, state.hypercube);
+
+        published(&mut state, &program(None, 2));
+        assert!(state.paint_draft.is_none());
+    }
+
+    /// Opening a session must put back everything `snapshot` captured, not
+    /// just the pieces, and rebuild the rotation-dependent index buffer.
+    #[test]
+    fn load_session_generation_restores_a_captured_snapshot() {
+        let mut saved = HypercubeShaderState::default();
+        for mv in crate::moves::parse_moves("RU OBR' IUFR2").expect("valid notation") {
+            saved.hypercube.apply_move(mv);
+            saved.history.push(mv);
+        }
+        saved.history.undo();
+        saved.rotation_4d = create_4d_plane_rotation(Vector4::x(), Vector4::w(), 0.7);
+        saved.camera_controller.yaw = 40.0;
+        saved.camera_controller.pitch = -12.0;
+        saved.camera_controller.distance = 11.0;
+        let snapshot = saved.snapshot();
+
+        let mut state = HypercubeShaderState::default();
+        let indices_generation_before = state.indices_generation;
+        let program = HypercubeShaderProgram {
+            load_session_generation: 1,
+            pending_session: Some(Arc::new(snapshot.clone())),
+            ..test_program()
+        };
+        let bounds = Rectangle::new(Point::ORIGIN, iced::Size::new(800.0, 600.0));
+        let redraw = Event::Window(iced::window::Event::RedrawRequested(Instant::now()));
+
+        let action = program.update(&mut state, &redraw, bounds, mouse::Cursor::Unavailable);
+        assert!(action.is_some(), "must publish the restored history");
+        assert_eq!(state.load_session_generation, 1);
+        assert_eq!(state.snapshot(), snapshot);
+        assert!(state.indices_generation > indices_generation_before);
+        assert_eq!(
+            &state.cached_indices[..],
+            &HypercubeShaderProgram::calculate_indices(&state.rotation_4d)[..]
+        );
+    }
+
+    /// With `record_view` on, the view is published once when it first
+    /// can be and again only after it moves.
+    #[test]
+    fn view_changes_are_published_while_recording_views() {
+        let mut state = HypercubeShaderState::default();
+        let program = HypercubeShaderProgram {
+            sticker_scale: 0.5,
+            face_gap: 2.0,
+            record_view: true,
+            ..test_program()
+        };
+        let bounds = Rectangle::new(Point::ORIGIN, iced::Size::new(800.0, 600.0));
+        let published_view = |state: &mut HypercubeShaderState| {
+            let redraw = Event::Window(iced::window::Event::RedrawRequested(Instant::now()));
+            let action = program.update(state, &redraw, bounds, mouse::Cursor::Unavailable)?;
+            match action.into_inner().0? {
+                Message::ViewChanged(view) => Some(view),
+                other => panic!("expected ViewChanged, got {other:?}"),
+            }
+        };
+
+        assert_eq!(published_view(&mut state), Some(state.view_pose()));
+        assert_eq!(published_view(&mut state), None, "unchanged");
+        state.camera_controller.yaw += 10.0;
+        let view = published_view(&mut state).expect("the camera moved");
+        assert_eq!(view.camera.yaw, state.camera_controller.yaw);
+    }
+
+    /// A replay must rebuild the puzzle from its start (solved if it has
+    /// none) - whatever it showed before - through the applied part of its
+    /// history only, keeping the rest to redo.
+    #[test]
+    fn replay_generation_rebuilds_puzzle_from_log() {
+        let mut state = HypercubeShaderState::default();
+        state
+            .hypercube
+            .apply_random_moves(5, &mut fastrand::Rng::with_seed(2));
+        let log = crate::history::MoveLog {
+            start: None,
+            scramble: crate::moves::parse_moves("UF DB2").expect("valid notation"),
+            moves: crate::moves::parse_moves("RU OBR'").expect("valid notation"),
+        };
+        let program_at = |replay_generation, replay| HypercubeShaderProgram {
+            replay_generation,
+            pending_replay: Some(Arc::new(replay)),
+            ..test_program()
+        };
+        let bounds = Rectangle::new(Point::ORIGIN, iced::Size::new(800.0, 600.0));
+        let redraw = Event::Window(iced::window::Event::RedrawRequested(Instant::now()));
+
+        let action = program_at(1, Replay::applied(log.clone())).update(
+            &mut state,
+            &redraw,
+            bounds,
+            mouse::Cursor::Unavailable,
+        );
+        assert!(action.is_some(), "must publish the replayed history");
+        assert_eq!(state.replay_generation, 1);
+        assert_eq!(state.history.applied(), &log.moves[..]);
+
+        let mut expected = Hypercube::solved();
+        for &mv in log.scramble.iter().chain(&log.moves) {
+            expected.apply_move(mv);
+        }
+        assert_eq!(state.hypercube, expected);
+
+        let start = Hypercube::random_state(&mut fastrand::Rng::with_seed(3));
+        let scrambled_log = crate::history::MoveLog {
+            start: Some(start.clone()),
+            scramble: log.scramble.clone(),
+            moves: Vec::new(),
+        };
+        let replay = Replay::continuing(scrambled_log, &log.moves);
+        program_at(2, replay).update(&mut state, &redraw, bounds, mouse::Cursor::Unavailable);
+        let mut scrambled = start;
+        for &mv in &log.scramble {
+            scrambled.apply_move(mv);
+        }
+        assert_eq!(state.hypercube, scrambled);
+        assert!(state.history.applied().is_empty());
+        assert_eq!(state.history.redo(), Some(log.moves[0]));
+    }
+
+    /// Undo presses that arrive while a move is still animating must wait
+    /// for it to finish rather than being dropped or collapsed into one.
+    #[test]
+    fn undo_presses_during_a_move_animation_queue_up() {
+        let mut state = HypercubeShaderState::default();
+        let sticker_index = FACET_TABLE
+            .iter()
+            .position(|f| f.is_actionable)
+            .expect("at least one actionable facet exists");
+        let program_at = |undo_generation| HypercubeShaderProgram {
+            undo_generation,
+            ..test_program()
+        };
+        let bounds = Rectangle::new(Point::ORIGIN, iced::Size::new(800.0, 600.0));
+        let redraw = Event::Window(iced::window::Event::RedrawRequested(Instant::now()));
+
+        program_at(0).handle_facet_click(&mut state, sticker_index);
+        state.animating_move = None;
+        program_at(0).handle_facet_click(&mut state, sticker_index);
+
+        // Both presses land mid-animation: nothing is undone yet.
+        program_at(2).update(&mut state, &redraw, bounds, mouse::Cursor::Unavailable);
+        assert_eq!(state.undo_generation, 0);
+
+        state.animating_move = None;
+        program_at(2).update(&mut state, &redraw, bounds, mouse::Cursor::Unavailable);
+        assert_eq!(state.undo_generation, 1);
+        assert!(!state.hypercube.is_solved());
+
+        state.animating_move = None;
+        program_at(2).update(&mut state, &redraw, bounds, mouse::Cursor::Unavailable);
+        assert_eq!(state.undo_generation, 2);
+        assert!(state.hypercube.is_solved());
+    }
+
+    /// Double-clicking a cell that isn't in front records a whole-puzzle
+    /// rotation carrying it there; double-clicking the front cell only
+    /// recenters the view.
+    #[test]
+    fn focus_face_reorients_the_puzzle_unless_the_cell_is_already_in_front() {
+        let program = test_program();
+        let mut state = HypercubeShaderState::default();
+
+        program.focus_face(&mut state, face_id_for(0, 1));
+        let recorded = state.history.applied();
+        assert_eq!(recorded.len(), 1);
+        assert!(recorded[0].is_rotation());
+        assert!(state.animating_move.is_some());
+        assert!(state.animating_focus.is_none());
+        assert!(state.hypercube.is_solved());
+        let front = FACE_AXIS_SIGN[0];
+        assert!(
+            state
+                .hypercube
+                .pieces
+                .iter()
+                .filter(|p| p.position[front.0] == front.1)
+                .all(|p| p.colors[front.0] == Some(side_color(0, 1)))
+        );
+
+        state.animating_move = None;
+        program.focus_face(&mut state, 0);
+        assert_eq!(state.history.applied().len(), 1);
+        assert!(state.animating_focus.is_some());
+    }
+
     /// A "center this face" animation tick rotates `rotation_4d` every frame
     /// but never touches `Hypercube` state or `animating_move` - it must bump
     /// `indices_generation` (the winding-corrected index buffer depends on
@@ -1883,19 +2835,12 @@ mod tests {
         let indices_generation_before = state.indices_generation;
         let sticker_generation_before = state.sticker_generation;
 
-        let program = HypercubeShaderProgram::new(
-            0.9,
-            0.0,
-            RenderMode::Standard,
-            AABBMode::None,
-            RotateButton::default(),
-            250,
-            state.reset_generation,
-            state.random_moves_generation,
-            0,
-            state.reveal_generation,
-            false,
-        );
+        let program = HypercubeShaderProgram {
+            reset_generation: state.reset_generation,
+            random_moves_generation: state.random_moves_generation,
+            reveal_generation: state.reveal_generation,
+            ..test_program()
+        };
         let bounds = Rectangle::new(Point::ORIGIN, iced::Size::new(800.0, 600.0));
 
         program.update(
@@ -1990,17 +2935,14 @@ mod tests {
             .iter()
             .find(|f| f.is_actionable)
             .expect("at least one actionable facet exists");
-        let nonzero = facet.local_coords.iter().filter(|c| **c != 0).count();
-        let angle = base_angle(nonzero);
+        let mv = Move::new(facet.axis, facet.side_sign, facet.local_coords, 1);
         let pre_move_pieces = state.hypercube.pieces.clone();
         state.animating_move = Some(AnimatingMove {
-            side_axis: facet.axis,
-            side_sign: facet.side_sign,
-            local_coords: facet.local_coords,
-            angle,
+            mv,
             pre_move_pieces,
             elapsed: Duration::ZERO,
             duration: Duration::from_millis(250),
+            committed_at: Instant::now(),
         });
         state.animating_focus = Some(AnimatingFocus {
             start_rotation: Matrix4::identity(),
@@ -2014,19 +2956,11 @@ mod tests {
         });
         state.camera_controller.yaw = 42.0;
 
-        let program = HypercubeShaderProgram::new(
-            0.9,
-            0.0,
-            RenderMode::Standard,
-            AABBMode::None,
-            RotateButton::default(),
-            250,
-            0,
-            0,
-            0,
-            1,
-            true,
-        );
+        let program = HypercubeShaderProgram {
+            reveal_generation: 1,
+            revealed_target: true,
+            ..test_program()
+        };
 
         let bounds = Rectangle::new(Point::ORIGIN, iced::Size::new(800.0, 600.0));
         let action = program.update(
@@ -2057,19 +2991,12 @@ mod tests {
     #[test]
     fn reveal_generation_mismatch_targets_primary_defaults_when_hiding() {
         let mut state = HypercubeShaderState::default();
-        let program = HypercubeShaderProgram::new(
-            1.0 - SECONDARY_STICKER_SCALE,
-            SECONDARY_FACE_GAP,
-            RenderMode::Standard,
-            AABBMode::None,
-            RotateButton::default(),
-            250,
-            0,
-            0,
-            0,
-            1,
-            false,
-        );
+        let program = HypercubeShaderProgram {
+            sticker_scale: 1.0 - SECONDARY_STICKER_SCALE,
+            face_gap: SECONDARY_FACE_GAP,
+            reveal_generation: 1,
+            ..test_program()
+        };
         let bounds = Rectangle::new(Point::ORIGIN, iced::Size::new(800.0, 600.0));
         program.update(
             &mut state,
@@ -2099,19 +3026,10 @@ mod tests {
         };
         let bounds = Rectangle::new(Point::ORIGIN, iced::Size::new(800.0, 600.0));
 
-        let stale_program = HypercubeShaderProgram::new(
-            0.5,
-            0.0,
-            RenderMode::Standard,
-            AABBMode::None,
-            RotateButton::default(),
-            250,
-            0,
-            0,
-            0,
-            0,
-            false,
-        );
+        let stale_program = HypercubeShaderProgram {
+            sticker_scale: 0.5,
+            ..test_program()
+        };
         stale_program.update(
             &mut state,
             &Event::Window(iced::window::Event::RedrawRequested(Instant::now())),
@@ -2121,19 +3039,10 @@ mod tests {
         assert_eq!(state.reveal_scale_override, Some(0.9));
         assert_eq!(state.reveal_gap_override, Some(1.0));
 
-        let caught_up_program = HypercubeShaderProgram::new(
-            0.9,
-            1.0,
-            RenderMode::Standard,
-            AABBMode::None,
-            RotateButton::default(),
-            250,
-            0,
-            0,
-            0,
-            0,
-            false,
-        );
+        let caught_up_program = HypercubeShaderProgram {
+            face_gap: 1.0,
+            ..test_program()
+        };
         caught_up_program.update(
             &mut state,
             &Event::Window(iced::window::Event::RedrawRequested(Instant::now())),
@@ -2164,19 +3073,12 @@ mod tests {
             }),
             ..Default::default()
         };
-        let program = HypercubeShaderProgram::new(
-            1.0 - PRIMARY_STICKER_SCALE,
-            PRIMARY_FACE_GAP,
-            RenderMode::Standard,
-            AABBMode::None,
-            RotateButton::default(),
-            250,
-            0,
-            0,
-            0,
-            0,
-            true,
-        );
+        let program = HypercubeShaderProgram {
+            sticker_scale: 1.0 - PRIMARY_STICKER_SCALE,
+            face_gap: PRIMARY_FACE_GAP,
+            revealed_target: true,
+            ..test_program()
+        };
         let bounds = Rectangle::new(Point::ORIGIN, iced::Size::new(800.0, 600.0));
         let action = program.update(
             &mut state,
@@ -2200,6 +3102,42 @@ mod tests {
         }
     }
 
+    /// A move animation finishing on a solved puzzle must publish
+    /// `Message::Solved`; one finishing anywhere else must not.
+    #[test]
+    fn move_completing_into_solved_state_publishes_solved() {
+        let mv = Move::new(3, 1, [1, 0, 0], 1);
+        let finished_move = |state: &mut HypercubeShaderState, mv: Move| {
+            let pre_move_pieces = state.hypercube.pieces.clone();
+            state.hypercube.apply_move(mv);
+            state.animating_move = Some(AnimatingMove {
+                mv,
+                pre_move_pieces,
+                elapsed: Duration::from_millis(350),
+                duration: Duration::from_millis(250),
+                committed_at: Instant::now(),
+            });
+        };
+        let program = test_program();
+        let bounds = Rectangle::new(Point::ORIGIN, iced::Size::new(800.0, 600.0));
+        let redraw = Event::Window(iced::window::Event::RedrawRequested(Instant::now()));
+        let published = |state: &mut HypercubeShaderState| {
+            program
+                .update(state, &redraw, bounds, mouse::Cursor::Unavailable)
+                .and_then(|action| action.into_inner().0)
+        };
+
+        let mut state = HypercubeShaderState::default();
+        finished_move(&mut state, mv);
+        assert!(published(&mut state).is_none());
+
+        finished_move(&mut state, mv.inverse());
+        assert!(matches!(
+            published(&mut state),
+            Some(Message::Solved { .. })
+        ));
+    }
+
     /// Camera-drag orbit start and facet turn-clicks must both be ignored
     /// while a reveal/hide flourish is playing, per the "locked cutscene"
     /// requirement.
@@ -2219,19 +3157,11 @@ mod tests {
             ..Default::default()
         };
         let rotate_button = RotateButton::default();
-        let program = HypercubeShaderProgram::new(
-            0.9,
-            0.0,
-            RenderMode::Standard,
-            AABBMode::None,
+        let program = HypercubeShaderProgram {
             rotate_button,
-            250,
-            0,
-            0,
-            0,
-            0,
-            true,
-        );
+            revealed_target: true,
+            ..test_program()
+        };
         let bounds = Rectangle::new(Point::ORIGIN, iced::Size::new(800.0, 600.0));
         let cursor = mouse::Cursor::Available(Point::new(10.0, 10.0));
 
@@ -16,6 +16,13 @@ struct HighlightingUniform {
     _padding: vec2<u32>,
     highlight_color: vec4<f32>,       // rgb = color, a = intensity
     piece_highlight_color: vec4<f32>, // rgb = color, a = intensity
+    highlighted_slots: vec4<u32>,     // bitmask over piece slots
+    slot_highlight_color: vec4<f32>,  // rgb = color, a = intensity
+    hint_sticker_index: u32,
+    _padding2: u32,                   // scalars: a vec3 would align to 16
+    _padding3: u32,
+    _padding4: u32,
+    hint_color: vec4<f32>,            // rgb = color, a = intensity
 };
 
 @group(0) @binding(0)
@@ -161,11 +168,17 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     var final_color = ambient + diffuse + specular;
     
     // Apply highlighting: the exact hovered sticker gets its own color, the
-    // rest of the hovered piece's stickers get a dimmer shared highlight.
+    // hint sticker (the next click in hint mode) its direction's color, the
+    // rest of the hovered piece's stickers a dimmer shared highlight, and
+    // the stickers of marked slots a fourth.
     if (in.instance_index == highlighting.hovered_sticker_index) {
         final_color = mix(final_color, highlighting.highlight_color.rgb, highlighting.highlight_color.a);
+    } else if (in.instance_index == highlighting.hint_sticker_index) {
+        final_color = mix(final_color, highlighting.hint_color.rgb, highlighting.hint_color.a);
     } else if (in.piece_slot == highlighting.hovered_piece_slot) {
         final_color = mix(final_color, highlighting.piece_highlight_color.rgb, highlighting.piece_highlight_color.a);
+    } else if ((highlighting.highlighted_slots[in.piece_slot / 32u] & (1u << (in.piece_slot % 32u))) != 0u) {
+        final_color = mix(final_color, highlighting.slot_highlight_color.rgb, highlighting.slot_highlight_color.a);
     }
     
     return vec4<f32>(final_color, in.color.a);
@@ -0,0 +1,331 @@
+//! Solve recordings and the viewer that replays them.
+//!
+//! While a timed solve runs, `HypercubeApp` keeps a `SolveRecording`: every
+//! change to the applied moves (an undo as the inverse move) and every view
+//! change the shader widget reports, each stamped with the time since the
+//! clock started. It's kept with the `SolveRecord`.
+//!
+//! `SolveViewer` shows a recording at any point on its timeline by
+//! rebuilding the puzzle from the scramble - its start state and moves, then
+//! every move stamped up to that point - with the view as it was, and
+//! handing it to the shader widget as a `PuzzleSnapshot`. Playing moves the
+//! point along with frame ticks, at the playback speed.
+
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::history::{MoveLog, Replay};
+use crate::moves::Move;
+use crate::session::{PuzzleSnapshot, ViewPose};
+use crate::stats::SolveRecord;
+
+/// Least time between recorded view changes: a drag reports one per frame,
+/// far more than a replay needs.
+const VIEW_SAMPLE_MS: u64 = 33;
+
+/// A move made `at_ms` into a solve.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct TimedMove {
+    pub(crate) at_ms: u64,
+    #[serde(rename = "move")]
+    pub(crate) mv: Move,
+}
+
+/// The view `at_ms` into a solve.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+struct TimedView {
+    at_ms: u64,
+    view: ViewPose,
+}
+
+/// Everything that happened during a timed solve, in order, each stamped
+/// with the time since the clock started.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct SolveRecording {
+    /// Applying these to the scrambled puzzle, in order, gives the puzzle as
+    /// it was at any point.
+    moves: Vec<TimedMove>,
+    /// Never empty: starts with the view when the clock started.
+    views: Vec<TimedView>,
+}
+
+impl SolveRecording {
+    /// A recording starting from `view`, with `applied` (moves made during
+    /// inspection, e.g. whole-puzzle rotations) already made.
+    pub(crate) fn new(view: ViewPose, applied: &[Move]) -> Self {
+        Self {
+            moves: applied
+                .iter()
+                .map(|&mv| TimedMove { at_ms: 0, mv })
+                .collect(),
+            views: vec![TimedView { at_ms: 0, view }],
+        }
+    }
+
+    pub(crate) fn moves(&self) -> &[TimedMove] {
+        &self.moves
+    }
+
+    /// Records the applied moves changing from `before` to `after` at
+    /// `at_ms`: the moves undone (as their inverses, latest first), then
+    /// the moves made.
+    pub(crate) fn record_history(&mut self, at_ms: u64, before: &[Move], after: &[Move]) {
+        let shared = before
+            .iter()
+            .zip(after)
+            .take_while(|(old, new)| old == new)
+            .count();
+        let undone = before[shared..].iter().rev().map(|mv| mv.inverse());
+        let made = after[shared..].iter().copied();
+        self.moves
+            .extend(undone.chain(made).map(|mv| TimedMove { at_ms, mv }));
+    }
+
+    /// Records the view changing to `view` at `at_ms`. Changes closer than
+    /// `VIEW_SAMPLE_MS` to the last kept one replace the latest instead, so
+    /// where a drag ended is always kept.
+    pub(crate) fn record_view(&mut self, at_ms: u64, view: ViewPose) {
+        let sample = TimedView { at_ms, view };
+        match self.views.len().checked_sub(2).map(|i| self.views[i].at_ms) {
+            Some(kept) if at_ms < kept + VIEW_SAMPLE_MS => *self.views.last_mut().unwrap() = sample,
+            _ => self.views.push(sample),
+        }
+    }
+
+    /// How many of the moves had been made `at_ms` in.
+    fn moves_at(&self, at_ms: u64) -> usize {
+        self.moves.partition_point(|timed| timed.at_ms <= at_ms)
+    }
+
+    /// Index of the view `at_ms` in.
+    fn view_index_at(&self, at_ms: u64) -> usize {
+        self.views
+            .partition_point(|timed| timed.at_ms <= at_ms)
+            .saturating_sub(1)
+    }
+}
+
+/// A recorded solve being watched: a point on its timeline, playing or
+/// paused.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SolveViewer {
+    /// Index of the solve in the `SolveDb`.
+    pub(crate) solve: usize,
+    /// The solve's log with none of its moves made.
+    scramble: MoveLog,
+    recording: SolveRecording,
+    duration_ms: u64,
+    at_ms: u64,
+    playing: bool,
+    /// Frame tick the timeline last moved on while playing.
+    last_tick: Option<Instant>,
+}
+
+impl SolveViewer {
+    /// A paused viewer at the start of solve `solve`, or `None` if it
+    /// wasn't recorded.
+    pub(crate) fn new(solve: usize, record: &SolveRecord) -> Option<Self> {
+        let recording = record.recording.clone()?;
+        let last_event = recording
+            .moves
+            .iter()
+            .map(|timed| timed.at_ms)
+            .chain(recording.views.iter().map(|timed| timed.at_ms))
+            .max()
+            .unwrap_or(0);
+        Some(Self {
+            solve,
+            scramble: MoveLog {
+                moves: Vec::new(),
+                ..record.log.clone()
+            },
+            recording,
+            duration_ms: record.time_ms.max(last_event),
+            at_ms: 0,
+            playing: false,
+            last_tick: None,
+        })
+    }
+
+    pub(crate) fn at_ms(&self) -> u64 {
+        self.at_ms
+    }
+
+    pub(crate) fn duration_ms(&self) -> u64 {
+        self.duration_ms
+    }
+
+    pub(crate) fn moves(&self) -> &[TimedMove] {
+        self.recording.moves()
+    }
+
+    /// How many of the moves have been made at the current point.
+    pub(crate) fn played(&self) -> usize {
+        self.recording.moves_at(self.at_ms)
+    }
+
+    /// The moves applied at the current point, in order.
+    pub(crate) fn applied(&self) -> impl Iterator<Item = Move> + '_ {
+        self.moves()[..self.played()].iter().map(|timed| timed.mv)
+    }
+
+    pub(crate) fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Plays or pauses; playing from the end starts over.
+    pub(crate) fn set_playing(&mut self, playing: bool) -> bool {
+        let frame = self.frame();
+        if playing && self.at_ms == self.duration_ms {
+            self.at_ms = 0;
+        }
+        self.playing = playing;
+        self.last_tick = None;
+        self.frame() != frame
+    }
+
+    /// Jumps to `at_ms` (clamped to the solve). Returns whether the puzzle
+    /// or view changed, i.e. needs showing again.
+    pub(crate) fn seek(&mut self, at_ms: u64) -> bool {
+        let frame = self.frame();
+        self.at_ms = at_ms.min(self.duration_ms);
+        self.frame() != frame
+    }
+
+    /// While playing, moves the timeline on to a frame tick at `now`, at
+    /// `speed` times real time, pausing at the end. Returns whether the
+    /// puzzle or view changed.
+    pub(crate) fn advance(&mut self, now: Instant, speed: f32) -> bool {
+        if !self.playing {
+            return false;
+        }
+        let Some(last) = self.last_tick.replace(now) else {
+            return false;
+        };
+        let elapsed = now.saturating_duration_since(last).mul_f32(speed);
+        let changed = self.seek(self.at_ms + elapsed.as_millis() as u64);
+        if self.at_ms == self.duration_ms {
+            self.playing = false;
+        }
+        changed
+    }
+
+    /// The puzzle and view at the current point, rebuilt from the scramble.
+    pub(crate) fn snapshot(&self) -> PuzzleSnapshot {
+        let replay = Replay::applied(MoveLog {
+            moves: self.applied().collect(),
+            ..self.scramble.clone()
+        });
+        let hypercube = replay.puzzle();
+        PuzzleSnapshot {
+            hypercube,
+            view: self.recording.views[self.recording.view_index_at(self.at_ms)].view,
+            history: replay.history,
+        }
+    }
+
+    /// What's shown at the current point: moves made and view index.
+    fn frame(&self) -> (usize, usize) {
+        (self.played(), self.recording.view_index_at(self.at_ms))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::Metrics;
+    use crate::moves::parse_moves;
+    use crate::piece::Hypercube;
+    use crate::session::CameraPose;
+
+    fn view(yaw: f32) -> ViewPose {
+        ViewPose {
+            rotation_4d: nalgebra::Matrix4::identity().into(),
+            camera: CameraPose {
+                yaw,
+                pitch: 0.0,
+                distance: 15.0,
+            },
+        }
+    }
+
+    #[test]
+    fn undos_are_recorded_as_inverse_moves() {
+        let moves = parse_moves("RU LB FUR").unwrap();
+        let mut recording = SolveRecording::new(view(0.0), &moves[..1]);
+        recording.record_history(100, &moves[..1], &moves[..2]);
+        recording.record_history(200, &moves[..2], &moves[..1]);
+        recording.record_history(300, &moves[..1], &[moves[0], moves[2]]);
+        let recorded: Vec<_> = recording.moves().iter().map(|t| (t.at_ms, t.mv)).collect();
+        assert_eq!(
+            recorded,
+            [
+                (0, moves[0]),
+                (100, moves[1]),
+                (200, moves[1].inverse()),
+                (300, moves[2]),
+            ]
+        );
+    }
+
+    #[test]
+    fn rapid_view_changes_are_thinned_but_keep_the_latest() {
+        let mut recording = SolveRecording::new(view(0.0), &[]);
+        for (i, at_ms) in [10, 20, 30, 40, 50].into_iter().enumerate() {
+            recording.record_view(at_ms, view(i as f32 + 1.0));
+        }
+        let kept: Vec<_> = recording
+            .views
+            .iter()
+            .map(|t| (t.at_ms, t.view.camera.yaw))
+            .collect();
+        assert_eq!(kept, [(0, 0.0), (30, 3.0), (50, 5.0)]);
+    }
+
+    #[test]
+    fn seeking_rebuilds_the_puzzle_as_it_was() {
+        let start = Hypercube::random_state(&mut fastrand::Rng::with_seed(4));
+        let scramble = parse_moves("OBR'").unwrap();
+        let moves = parse_moves("RU LB").unwrap();
+        let mut recording = SolveRecording::new(view(0.0), &[]);
+        recording.record_history(1000, &[], &moves[..1]);
+        recording.record_view(1500, view(1.0));
+        recording.record_history(2000, &moves[..1], &moves);
+        let record = SolveRecord {
+            seed: None,
+            time_ms: 2500,
+            date: 0,
+            metrics: Metrics::of(&moves),
+            log: MoveLog {
+                start: Some(start.clone()),
+                scramble: scramble.clone(),
+                moves: moves.clone(),
+            },
+            recording: Some(recording),
+        };
+        let mut viewer = SolveViewer::new(0, &record).unwrap();
+        assert_eq!(viewer.duration_ms(), 2500);
+
+        assert!(viewer.seek(1600));
+        let snapshot = viewer.snapshot();
+        let mut expected = start;
+        expected.apply_move(scramble[0]);
+        expected.apply_move(moves[0]);
+        assert_eq!(snapshot.hypercube, expected);
+        assert_eq!(snapshot.history.applied(), &moves[..1]);
+        assert_eq!(snapshot.view.camera.yaw, 1.0);
+        assert!(!viewer.seek(1700), "nothing happens in between");
+
+        // Playing runs to the end at the given speed, then pauses.
+        assert!(!viewer.set_playing(true));
+        let now = Instant::now();
+        assert!(!viewer.advance(now, 2.0));
+        assert!(viewer.advance(now + std::time::Duration::from_millis(200), 2.0));
+        assert_eq!(viewer.at_ms(), 2100);
+        assert_eq!(viewer.played(), 2);
+        viewer.advance(now + std::time::Duration::from_secs(1), 2.0);
+        assert_eq!(viewer.at_ms(), 2500);
+        assert!(!viewer.is_playing());
+    }
+}
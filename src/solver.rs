@@ -0,0 +1,591 @@
+//! Solves any legal `Hypercube` with conjugated commutators.
+//!
+//! Slice moves and whole-puzzle rotations move the cell centers, which
+//! twists never do, so those are first rotated home. Piece types are then
+//! solved one after another - faces, corners, then edges
+//! (2, 4 and 3 stickers). Each has one commutator that 3-cycles three
+//! pieces of that type and leaves the rest of the type, and every type
+//! solved before it, alone; what it does to types solved later doesn't
+//! matter. Conjugating it by a setup that carries any three slots of the
+//! type onto the commutator's own makes a 3-cycle of those three slots;
+//! setups come from a breadth-first search over ordered slot triples, run
+//! once per type. Slots are then solved one at a time: a small search over
+//! the 3-cycles among the slot, the slot its piece is in, and one other not
+//! yet solved finds the few that bring the right piece in turned the right
+//! way. The last three slots of each type are solved together from a table
+//! of every state of them those cycles can solve.
+//!
+//! Nowhere near the fewest moves - it's the method a person would use with
+//! a handful of algorithms, and about as long.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::sync::LazyLock;
+
+use crate::algorithm::parse_alg;
+use crate::moves::{Move, all_moves, all_rotations, invert_moves, simplify_moves};
+use crate::piece::{
+    Hypercube, IDENTITY_ORIENTATION, Orientation, compose_orientations, index_of,
+    permutation_is_odd, slots_with,
+};
+
+/// Per piece type in the order they're solved (by sticker count), a
+/// commutator 3-cycling three pieces of that type. Each leaves the types
+/// solved before it alone, but not always those after: the face one moves
+/// edges and corners too, and the corner one swaps two edges.
+const CYCLES: [(usize, &str); 3] = [
+    (2, "[IBUR', LB]"),
+    (4, "[IBUR', LBUI] [RUO: [LBUI, IBUR']]"),
+    (3, "([IB': LB2] FUR)2"),
+];
+
+static MOVES: LazyLock<Vec<Move>> = LazyLock::new(all_moves);
+
+/// Index in `MOVES` of each move's inverse.
+static INVERSES: LazyLock<Vec<usize>> = LazyLock::new(|| {
+    MOVES
+        .iter()
+        .map(|mv| MOVES.iter().position(|m| *m == mv.inverse()).unwrap())
+        .collect()
+});
+
+static PIECE_TYPES: LazyLock<[PieceType; 3]> =
+    LazyLock::new(|| CYCLES.map(|(facet_count, cycle)| PieceType::new(facet_count, cycle)));
+
+/// A sequence of `MOVES` indices.
+type Sequence = Vec<usize>;
+
+/// Pieces of one type in a few slots (by number within the type) and how
+/// each is turned, in the order of the slots being solved.
+type LocalState<const N: usize> = [(usize, Orientation); N];
+
+/// A 3-cycle among a few slots: its moves, and for each piece it moves,
+/// the slot it's in and the slot it goes to (as positions among the few)
+/// and how it's turned on the way.
+type LocalCycle = (Sequence, [(usize, usize, Orientation); 3]);
+
+/// How many slots of each type are solved together at the end: three
+/// slots' cycles don't reach every legal state of them, four do.
+const LAST: usize = 3;
+
+/// Everything the solver precomputes about one piece type. Pieces and
+/// slots of the type are numbered by position in `slots`.
+struct PieceType {
+    slots: Vec<usize>,
+    /// Per move, per slot number: the slot number the piece there moves to
+    /// and how the move turns it.
+    moved: Vec<Vec<(usize, Orientation)>>,
+    cycle: Sequence,
+    /// The slots `cycle` cycles, the first's piece to the second and so on.
+    cycle_slots: [usize; 3],
+    /// Per ordered slot triple (see `triple_index`): the first move of a
+    /// setup carrying those slots onto `cycle_slots`, and the triple that
+    /// move carries them to. `None` for `cycle_slots` itself.
+    setups: Vec<Option<(usize, usize)>>,
+    /// The cycles among the last `LAST` slots, which are solved together.
+    last_cycles: Vec<LocalCycle>,
+    /// Per solvable state of the last `LAST` slots: the cycle (in
+    /// `last_cycles`) that starts a shortest way to solving them, `None`
+    /// once solved.
+    last_steps: HashMap<LocalState<LAST>, Option<usize>>,
+}
+
+impl PieceType {
+    fn new(facet_count: usize, cycle: &str) -> Self {
+        let slots = slots_with(facet_count);
+        let number_of = |slot: usize| slots.iter().position(|&s| s == slot).unwrap();
+        let moved: Vec<Vec<(usize, Orientation)>> = MOVES
+            .iter()
+            .map(|&mv| {
+                let mut cube = Hypercube::solved();
+                cube.apply_move(mv);
+                let mut moved = vec![(0, IDENTITY_ORIENTATION); slots.len()];
+                for (number, &slot) in slots.iter().enumerate() {
+                    let piece = &cube.pieces[slot];
+                    moved[number_of(index_of(piece.home()))] = (number, piece.orientation());
+                }
+                moved
+            })
+            .collect();
+
+        let cycle: Sequence = parse_alg(cycle)
+            .expect("cycle commutators are valid notation")
+            .expand()
+            .into_iter()
+            .map(|mv| MOVES.iter().position(|m| *m == mv).unwrap())
+            .collect();
+        let mut piece_type = Self {
+            slots,
+            moved,
+            cycle,
+            cycle_slots: [0; 3],
+            setups: Vec::new(),
+            last_cycles: Vec::new(),
+            last_steps: HashMap::new(),
+        };
+        let first = (0..piece_type.slots.len())
+            .find(|&number| piece_type.track(&piece_type.cycle, number).0 != number)
+            .expect("cycle commutator moves a piece of its type");
+        let second = piece_type.track(&piece_type.cycle, first).0;
+        let third = piece_type.track(&piece_type.cycle, second).0;
+        piece_type.cycle_slots = [first, second, third];
+        piece_type.setups = piece_type.search_setups();
+        let last: [usize; LAST] = std::array::from_fn(|k| piece_type.slots.len() - LAST + k);
+        piece_type.last_cycles = piece_type.cycles_among(&last);
+        piece_type.last_steps = steps_to_solved(&last, &piece_type.last_cycles);
+        piece_type
+    }
+
+    /// Where the piece in slot `number` ends up after `sequence`, and how
+    /// it's turned on the way.
+    fn track(&self, sequence: &[usize], number: usize) -> (usize, Orientation) {
+        sequence.iter().fold(
+            (number, IDENTITY_ORIENTATION),
+            |(number, orientation), &mv| {
+                let (to, turn) = self.moved[mv][number];
+                (to, compose_orientations(orientation, turn))
+            },
+        )
+    }
+
+    fn triple_index(&self, [a, b, c]: [usize; 3]) -> usize {
+        let n = self.slots.len();
+        (a * n + b) * n + c
+    }
+
+    /// Breadth-first search backwards from `cycle_slots` over every ordered
+    /// triple of distinct slots, so each gets a shortest setup.
+    fn search_setups(&self) -> Vec<Option<(usize, usize)>> {
+        let n = self.slots.len();
+        let came_from: Vec<Vec<usize>> = self
+            .moved
+            .iter()
+            .map(|moved| {
+                let mut came_from = vec![0; n];
+                for (from, &(to, _)) in moved.iter().enumerate() {
+                    came_from[to] = from;
+                }
+                came_from
+            })
+            .collect();
+        let start = self.triple_index(self.cycle_slots);
+        let mut setups = vec![None; n * n * n];
+        let mut seen = vec![false; n * n * n];
+        seen[start] = true;
+        let mut queue = VecDeque::from([self.cycle_slots]);
+        while let Some(triple) = queue.pop_front() {
+            let index = self.triple_index(triple);
+            for (mv, came_from) in came_from.iter().enumerate() {
+                let before = triple.map(|number| came_from[number]);
+                let before_index = self.triple_index(before);
+                if !seen[before_index] {
+                    seen[before_index] = true;
+                    setups[before_index] = Some((mv, index));
+                    queue.push_back(before);
+                }
+            }
+        }
+        setups
+    }
+
+    /// Moves carrying slots `triple` onto `cycle_slots`, in order.
+    fn setup(&self, triple: [usize; 3]) -> Sequence {
+        let mut setup = Vec::new();
+        let mut index = self.triple_index(triple);
+        while let Some((mv, next)) = self.setups[index] {
+            setup.push(mv);
+            index = next;
+        }
+        setup
+    }
+
+    /// 3-cycles among `local` slots: for every three of them, set up from
+    /// each ordering and run both ways round - each turns the pieces
+    /// differently on the way.
+    fn cycles_among(&self, local: &[usize]) -> Vec<LocalCycle> {
+        let inverse_cycle: Sequence = self.cycle.iter().rev().map(|&mv| INVERSES[mv]).collect();
+        let mut cycles = Vec::new();
+        for a in 0..local.len() {
+            for b in a + 1..local.len() {
+                for c in b + 1..local.len() {
+                    for order in [
+                        [a, b, c],
+                        [b, c, a],
+                        [c, a, b],
+                        [a, c, b],
+                        [c, b, a],
+                        [b, a, c],
+                    ] {
+                        let setup = self.setup(order.map(|k| local[k]));
+                        let undo_setup: Sequence =
+                            setup.iter().rev().map(|&mv| INVERSES[mv]).collect();
+                        for cycle in [&self.cycle, &inverse_cycle] {
+                            let sequence: Sequence = [&setup[..], cycle, &undo_setup].concat();
+                            let effect = order.map(|k| {
+                                let (to, turn) = self.track(&sequence, local[k]);
+                                (k, local.iter().position(|&s| s == to).unwrap(), turn)
+                            });
+                            cycles.push((sequence, effect));
+                        }
+                    }
+                }
+            }
+        }
+        cycles
+    }
+
+    /// Solves every slot of this type, leaving the types before it alone.
+    /// `None` if the last few can't be solved, i.e. `cube` wasn't legal.
+    fn solve(&self, cube: &mut Hypercube, solution: &mut Vec<Move>) -> Option<()> {
+        let n = self.slots.len();
+        let number_of = |slot: usize| self.slots.iter().position(|&s| s == slot).unwrap();
+        let state_at = |cube: &Hypercube, number: usize| {
+            let piece = &cube.pieces[self.slots[number]];
+            (number_of(index_of(piece.home())), piece.orientation())
+        };
+        let mut run = |sequence: &Sequence, cube: &mut Hypercube| {
+            for &mv in sequence {
+                cube.apply_move(MOVES[mv]);
+                solution.push(MOVES[mv]);
+            }
+        };
+        // The last slots as they'd be with `local`'s pieces (one per slot
+        // of `local`) put in, if that's a state they can be solved from.
+        let last_solvable = |cube: &Hypercube, local: &[usize], state: &[(usize, Orientation)]| {
+            let mut last: LocalState<LAST> = std::array::from_fn(|k| state_at(cube, n - LAST + k));
+            for (&number, &piece) in local.iter().zip(state) {
+                if let Some(k) = number.checked_sub(n - LAST) {
+                    last[k] = piece;
+                }
+            }
+            self.last_steps.contains_key(&last)
+        };
+        for target in 0..n - LAST {
+            // Three slots' cycles don't reach every legal state of them, so
+            // the slot before the last few is solved such that they end up
+            // in one that they do reach.
+            let before_last = target == n - LAST - 1;
+            let done = |local: &[usize], state: &[(usize, Orientation)]| {
+                state[0] == (target, IDENTITY_ORIENTATION)
+                    && (!before_last || last_solvable(cube, local, state))
+            };
+            if done(&[target], &[state_at(cube, target)]) {
+                continue;
+            }
+            // Cycle through the slot the piece is in and any other not yet
+            // solved - but a given other doesn't always allow every turn of
+            // the piece coming in, so try them in turn.
+            let from = (target..n)
+                .find(|&number| state_at(cube, number).0 == target)
+                .expect("every piece is somewhere");
+            let others = (target + 1..n).rev().filter(|&number| number != from);
+            let locals: Vec<[usize; 3]> = if from == target {
+                others
+                    .clone()
+                    .flat_map(|a| {
+                        others
+                            .clone()
+                            .filter(move |&b| b != a)
+                            .map(move |b| [target, a, b])
+                    })
+                    .collect()
+            } else {
+                others.map(|other| [target, from, other]).collect()
+            };
+            let (cycles, path) = locals.into_iter().find_map(|local| {
+                let cycles = self.cycles_among(&local);
+                let start = local.map(|number| state_at(cube, number));
+                search_local(start, &cycles, |state| done(&local, state)).map(|path| (cycles, path))
+            })?;
+            for cycle in path {
+                run(&cycles[cycle].0, cube);
+            }
+        }
+
+        let mut state: LocalState<LAST> = std::array::from_fn(|k| state_at(cube, n - LAST + k));
+        while let Some(cycle) = *self.last_steps.get(&state)? {
+            let (sequence, effect) = &self.last_cycles[cycle];
+            run(sequence, cube);
+            state = apply_local(&state, effect);
+        }
+        Some(())
+    }
+}
+
+/// `state` after `effect` (see `LocalCycle`).
+fn apply_local<const N: usize>(
+    state: &LocalState<N>,
+    effect: &[(usize, usize, Orientation); 3],
+) -> LocalState<N> {
+    let mut next = *state;
+    for &(from, to, turn) in effect {
+        next[to] = (state[from].0, compose_orientations(state[from].1, turn));
+    }
+    next
+}
+
+/// Breadth-first search over a few slots' pieces for the fewest `cycles`
+/// (by index) reaching a state `solved` accepts.
+fn search_local<const N: usize>(
+    start: LocalState<N>,
+    cycles: &[LocalCycle],
+    solved: impl Fn(&LocalState<N>) -> bool,
+) -> Option<Vec<usize>> {
+    let mut came_from: HashMap<LocalState<N>, Option<(LocalState<N>, usize)>> =
+        HashMap::from([(start, None)]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(state) = queue.pop_front() {
+        if solved(&state) {
+            let mut path = Vec::new();
+            let mut at = state;
+            while let Some((before, cycle)) = came_from[&at] {
+                path.push(cycle);
+                at = before;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for (index, (_, effect)) in cycles.iter().enumerate() {
+            let next = apply_local(&state, effect);
+            if let Entry::Vacant(entry) = came_from.entry(next) {
+                entry.insert(Some((state, index)));
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Breadth-first search outwards from `local` slots solved, giving every
+/// state of them `cycles` can solve the first cycle of a shortest way
+/// back. `cycles` come in pairs, each followed by its inverse, so the way
+/// back from a state reached by one cycle starts with its partner.
+fn steps_to_solved<const N: usize>(
+    local: &[usize; N],
+    cycles: &[LocalCycle],
+) -> HashMap<LocalState<N>, Option<usize>> {
+    let solved = local.map(|number| (number, IDENTITY_ORIENTATION));
+    let mut steps = HashMap::from([(solved, None)]);
+    let mut queue = VecDeque::from([solved]);
+    while let Some(state) = queue.pop_front() {
+        for (index, (_, effect)) in cycles.iter().enumerate() {
+            let next = apply_local(&state, effect);
+            if let Entry::Vacant(entry) = steps.entry(next) {
+                entry.insert(Some(index ^ 1));
+                queue.push_back(next);
+            }
+        }
+    }
+    steps
+}
+
+/// Whole-puzzle rotations bringing every cell center home: a
+/// breadth-first search over where the eight centers are, which can only
+/// be one of the 192 arrangements the tesseract's rotations give them.
+/// `None` if the centers are in none of those.
+pub(crate) fn center_reorientation(cube: &Hypercube) -> Option<Vec<Move>> {
+    let centers = slots_with(1);
+    let number_of = |slot: usize| centers.iter().position(|&s| s == slot).unwrap();
+    let rotations = all_rotations();
+    // Per rotation, the center slot number each center slot's piece goes to.
+    let moved: Vec<Vec<usize>> = rotations
+        .iter()
+        .map(|&mv| {
+            let mut rotated = Hypercube::solved();
+            rotated.apply_move(mv);
+            let mut moved = vec![0; centers.len()];
+            for (number, &slot) in centers.iter().enumerate() {
+                moved[number_of(index_of(rotated.pieces[slot].home()))] = number;
+            }
+            moved
+        })
+        .collect();
+
+    // Which center (by home slot number) is in each center slot.
+    let start: Vec<usize> = centers
+        .iter()
+        .map(|&slot| number_of(index_of(cube.pieces[slot].home())))
+        .collect();
+    let home: Vec<usize> = (0..centers.len()).collect();
+    let mut came_from = HashMap::from([(start.clone(), None)]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(state) = queue.pop_front() {
+        if state == home {
+            let mut path = Vec::new();
+            let mut at = &state;
+            while let Some((before, rotation)) = &came_from[at] {
+                path.push(rotations[*rotation]);
+                at = before;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for (rotation, moved) in moved.iter().enumerate() {
+            let mut next = state.clone();
+            for (number, &center) in state.iter().enumerate() {
+                next[moved[number]] = center;
+            }
+            if let Entry::Vacant(entry) = came_from.entry(next.clone()) {
+                entry.insert(Some((state.clone(), rotation)));
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// A move sequence taking `cube` to `Hypercube::solved()`, or `None` if
+/// `cube` isn't a state moves can reach. Moves that cancel or merge where
+/// one commutator meets the next are simplified away. Deterministic: the
+/// same state always gets the same solution.
+pub(crate) fn solve(cube: &Hypercube) -> Option<Vec<Move>> {
+    let mut cube = cube.clone();
+    let mut solution = center_reorientation(&cube)?;
+    for &mv in &solution {
+        cube.apply_move(mv);
+    }
+
+    // Faces and edges are always permuted with the same parity, corners
+    // evenly; 3-cycles can't change parity, so fix it with a quarter turn.
+    let faces = &PIECE_TYPES[0];
+    let homes: Vec<usize> = faces
+        .slots
+        .iter()
+        .map(|&slot| {
+            let home = index_of(cube.pieces[slot].home());
+            faces.slots.iter().position(|&s| s == home).unwrap()
+        })
+        .collect();
+    if permutation_is_odd(&homes) {
+        let quarter_turn = MOVES
+            .iter()
+            .copied()
+            .find(|mv| mv.grip().iter().filter(|c| **c != 0).count() == 1)
+            .unwrap();
+        cube.apply_move(quarter_turn);
+        solution.push(quarter_turn);
+    }
+
+    for piece_type in PIECE_TYPES.iter() {
+        piece_type.solve(&mut cube, &mut solution)?;
+    }
+    (cube == Hypercube::solved()).then(|| simplify_moves(&solution))
+}
+
+/// A move sequence taking a solved puzzle to `target`: the inverse of its
+/// solution, for scrambling into a chosen (e.g. uniformly random) state.
+pub(crate) fn moves_reaching(target: &Hypercube) -> Option<Vec<Move>> {
+    solve(target).map(|solution| invert_moves(&solution))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::moves::{Layers, random_move};
+
+    #[test]
+    fn cycles_move_three_pieces_of_their_type_and_none_solved_before() {
+        for (index, (_, cycle)) in CYCLES.iter().enumerate() {
+            let mut cube = Hypercube::solved();
+            for mv in parse_alg(cycle).unwrap().expand() {
+                cube.apply_move(mv);
+            }
+            let moved = |piece_type: &PieceType| {
+                piece_type
+                    .slots
+                    .iter()
+                    .filter(|&&slot| cube.pieces[slot] != Hypercube::solved().pieces[slot])
+                    .count()
+            };
+            assert_eq!(moved(&PIECE_TYPES[index]), 3, "{cycle}");
+            for earlier in &PIECE_TYPES[..index] {
+                assert_eq!(moved(earlier), 0, "{cycle}");
+            }
+        }
+    }
+
+    #[test]
+    fn solves_random_move_scrambles() {
+        let mut rng = fastrand::Rng::with_seed(6);
+        for _ in 0..5 {
+            let mut cube = Hypercube::solved();
+            cube.apply_random_moves(30, &mut rng);
+            let solution = solve(&cube).expect("scramble is legal");
+            assert_eq!(simplify_moves(&solution), solution);
+            for mv in solution {
+                cube.apply_move(mv);
+            }
+            assert_eq!(cube, Hypercube::solved());
+        }
+    }
+
+    /// Solves a scramble of `count` random moves from each seed, checking
+    /// the solution is the same every time and solves it.
+    fn check_seeded_scrambles(seeds: std::ops::Range<u64>, count: u32) {
+        for seed in seeds {
+            let mut cube = Hypercube::solved();
+            cube.apply_random_moves(count, &mut fastrand::Rng::with_seed(seed));
+            let solution = solve(&cube).expect("scramble is legal");
+            assert_eq!(solve(&cube).as_ref(), Some(&solution), "seed {seed}");
+            for mv in solution {
+                cube.apply_move(mv);
+            }
+            assert_eq!(cube, Hypercube::solved(), "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn solves_seeded_scrambles_deterministically() {
+        check_seeded_scrambles(0..20, 40);
+    }
+
+    /// Too slow for a debug build: CI runs it with `cargo test --release --lib
+    /// -- --ignored`.
+    #[test]
+    #[ignore]
+    fn solves_thousands_of_seeded_scrambles() {
+        check_seeded_scrambles(0..3000, 40);
+    }
+
+    #[test]
+    fn solves_states_with_moved_cell_centers() {
+        let mut rng = fastrand::Rng::with_seed(17);
+        for text in ["{2}RU", "{1-3}RU", "{1-2}OBR' {2}FUR RU"] {
+            let mut cube = Hypercube::solved();
+            for mv in crate::moves::parse_moves(text).unwrap() {
+                cube.apply_move(mv);
+            }
+            for _ in 0..10 {
+                let layers = [Layers::Outer, Layers::Middle, Layers::Wide, Layers::All];
+                cube.apply_move(random_move(&mut rng).with_layers(layers[rng.usize(..4)]));
+            }
+            let solution = solve(&cube).expect("reached by moves");
+            for mv in solution {
+                cube.apply_move(mv);
+            }
+            assert_eq!(cube, Hypercube::solved(), "{text}");
+        }
+    }
+
+    #[test]
+    fn moves_reaching_random_states_reproduce_them() {
+        let mut rng = fastrand::Rng::with_seed(7);
+        for _ in 0..5 {
+            let target = Hypercube::random_state(&mut rng);
+            let mut cube = Hypercube::solved();
+            for mv in moves_reaching(&target).expect("random states are reachable") {
+                cube.apply_move(mv);
+            }
+            assert_eq!(cube, target);
+        }
+    }
+
+    #[test]
+    fn rejects_a_single_twisted_corner() {
+        let mut cube = Hypercube::solved();
+        let slot = index_of([1, 1, 1, 1]);
+        let piece = cube.pieces[slot];
+        cube.pieces[slot] = crate::piece::Piece::placed(piece.home(), piece.position, [1, 2, 0, 3]);
+        assert_eq!(solve(&cube), None);
+    }
+}
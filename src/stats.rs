@@ -0,0 +1,264 @@
+//! Persistent record of timed solves, and the usual speedsolving averages
+//! over it.
+//!
+//! Stored as JSON next to sessions in the `ProjectDirs` data dir, loaded
+//! once at startup and rewritten after every solve, the same way
+//! `settings.rs` handles its file.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::history::MoveLog;
+use crate::metrics::Metrics;
+use crate::solve_viewer::SolveRecording;
+
+/// One timed solve.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct SolveRecord {
+    /// Seed the scramble was generated from, if it was generated here.
+    pub(crate) seed: Option<u64>,
+    pub(crate) time_ms: u64,
+    /// When the solve finished, in seconds since the Unix epoch.
+    pub(crate) date: u64,
+    /// Length of the solve itself (not the scramble), after undos. Missing
+    /// from records saved before metrics were kept, so filled in from `log`
+    /// on load.
+    #[serde(default)]
+    pub(crate) metrics: Metrics,
+    pub(crate) log: MoveLog,
+    /// The solve as it happened, for the solve viewer. Missing from records
+    /// saved before solves were recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) recording: Option<SolveRecording>,
+}
+
+impl SolveRecord {
+    pub(crate) fn time(&self) -> Duration {
+        Duration::from_millis(self.time_ms)
+    }
+}
+
+/// Every recorded solve, oldest first.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct SolveDb {
+    pub(crate) solves: Vec<SolveRecord>,
+}
+
+fn db_path() -> Option<PathBuf> {
+    directories::ProjectDirs::from("", "", "hypercube")
+        .map(|dirs| dirs.data_dir().join("solves.json"))
+}
+
+/// Loads the solve record, starting empty if the file is missing or
+/// invalid.
+pub(crate) fn load() -> SolveDb {
+    let Some(path) = db_path() else {
+        log::warn!("Could not determine data directory; starting with no solves");
+        return SolveDb::default();
+    };
+
+    match std::fs::read_to_string(&path) {
+        Ok(contents) => match serde_json::from_str::<SolveDb>(&contents) {
+            Ok(mut db) => {
+                for record in &mut db.solves {
+                    if record.metrics == Metrics::default() {
+                        record.metrics = Metrics::of(&record.log.moves);
+                    }
+                }
+                db
+            }
+            Err(err) => {
+                log::warn!("Failed to parse solves at {path:?}: {err}; starting empty");
+                SolveDb::default()
+            }
+        },
+        Err(err) => {
+            log::warn!("Failed to read solves at {path:?}: {err}; starting empty");
+            SolveDb::default()
+        }
+    }
+}
+
+/// Persists the solve record, logging a warning on failure rather than
+/// propagating an error.
+pub(crate) fn save(db: &SolveDb) {
+    let Some(path) = db_path() else {
+        log::warn!("Could not determine data directory; solves not saved");
+        return;
+    };
+
+    if let Some(parent) = path.parent()
+        && let Err(err) = std::fs::create_dir_all(parent)
+    {
+        log::warn!("Failed to create data directory {parent:?}: {err}");
+        return;
+    }
+
+    let contents = match serde_json::to_string_pretty(db) {
+        Ok(contents) => contents,
+        Err(err) => {
+            log::warn!("Failed to serialize solves: {err}");
+            return;
+        }
+    };
+
+    if let Err(err) = std::fs::write(&path, contents) {
+        log::warn!("Failed to write solves to {path:?}: {err}");
+    }
+}
+
+/// One row of the statistics table: the latest value and the best ever.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Statistic {
+    pub(crate) label: &'static str,
+    pub(crate) current: Option<Duration>,
+    pub(crate) best: Option<Duration>,
+}
+
+/// Window sizes and labels of the statistics table. A window of 1 is the
+/// single; 3 is a plain mean; 5 and up are trimmed averages.
+const STATISTICS: [(usize, &str); 5] = [
+    (1, "single"),
+    (3, "mo3"),
+    (5, "ao5"),
+    (12, "ao12"),
+    (100, "ao100"),
+];
+
+/// WCA-style average of a window of times: the fastest and slowest 5%
+/// (rounded up, so one each for ao5/ao12 and five each for ao100) are
+/// dropped and the rest averaged. Windows under 5 (single, mo3) are a
+/// plain mean.
+fn average(window: &[Duration]) -> Duration {
+    let trim = if window.len() < 5 {
+        0
+    } else {
+        (window.len() * 5).div_ceil(100)
+    };
+    let mut sorted = window.to_vec();
+    sorted.sort_unstable();
+    let counted = &sorted[trim..sorted.len() - trim];
+    counted.iter().sum::<Duration>() / counted.len() as u32
+}
+
+/// The statistics table over `solves` (oldest first): for each window
+/// size, the average of the latest window and the best window ever (the
+/// personal best). `None` until there are enough solves.
+pub(crate) fn statistics(solves: &[SolveRecord]) -> Vec<Statistic> {
+    let times: Vec<Duration> = solves.iter().map(SolveRecord::time).collect();
+    STATISTICS
+        .iter()
+        .map(|&(size, label)| Statistic {
+            label,
+            current: times
+                .len()
+                .checked_sub(size)
+                .map(|start| average(&times[start..])),
+            best: times.windows(size).map(average).min(),
+        })
+        .collect()
+}
+
+/// `YYYY-MM-DD` (UTC) for a Unix timestamp in seconds.
+pub(crate) fn format_date(date: u64) -> String {
+    // Howard Hinnant's days-to-civil conversion, with eras of 400 years.
+    let days = (date / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solve_of(time_ms: u64) -> SolveRecord {
+        SolveRecord {
+            seed: Some(time_ms),
+            time_ms,
+            date: 0,
+            metrics: Metrics::default(),
+            log: MoveLog::default(),
+            recording: None,
+        }
+    }
+
+    fn secs(seconds: u64) -> Option<Duration> {
+        Some(Duration::from_secs(seconds))
+    }
+
+    #[test]
+    fn average_trims_fastest_and_slowest_five_percent() {
+        let window = |times: &[u64]| -> Vec<Duration> {
+            times.iter().map(|&s| Duration::from_secs(s)).collect()
+        };
+        // mo3: plain mean.
+        assert_eq!(average(&window(&[1, 2, 9])), Duration::from_secs(4));
+        // ao5: best and worst dropped.
+        assert_eq!(
+            average(&window(&[1, 10, 20, 30, 99])),
+            Duration::from_secs(20)
+        );
+        // ao100: five dropped from each end.
+        let mut times = vec![1; 5];
+        times.extend([50; 90]);
+        times.extend([1000; 5]);
+        assert_eq!(average(&window(&times)), Duration::from_secs(50));
+    }
+
+    #[test]
+    fn statistics_track_latest_window_and_personal_best() {
+        let solves: Vec<SolveRecord> = [40, 30, 20, 60, 50, 45]
+            .iter()
+            .map(|&s| solve_of(s * 1000))
+            .collect();
+        let table = statistics(&solves);
+        let row = |label| *table.iter().find(|s| s.label == label).unwrap();
+
+        assert_eq!(row("single").current, secs(45));
+        assert_eq!(row("single").best, secs(20));
+        // mo3 windows: [40,30,20]=30, [30,20,60]=36.67, [20,60,50]=43.33, [60,50,45]=51.67
+        assert_eq!(row("mo3").best, secs(30));
+        assert_eq!(row("mo3").current, Some(Duration::from_secs(155) / 3));
+        // ao5 windows: [40,30,20,60,50] -> 40, [30,20,60,50,45] -> 41.67
+        assert_eq!(row("ao5").best, secs(40));
+        assert_eq!(row("ao5").current, Some(Duration::from_secs(125) / 3));
+        assert_eq!(
+            row("ao12"),
+            Statistic {
+                label: "ao12",
+                current: None,
+                best: None,
+            }
+        );
+    }
+
+    #[test]
+    fn format_date_handles_epoch_and_leap_days() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+        assert_eq!(format_date(1_792_195_200), "2026-10-17");
+    }
+
+    #[test]
+    fn solve_db_round_trips_through_json() {
+        let db = SolveDb {
+            solves: vec![solve_of(12_340), solve_of(9_870)],
+        };
+        let json = serde_json::to_string(&db).unwrap();
+        assert_eq!(serde_json::from_str::<SolveDb>(&json).unwrap(), db);
+    }
+}
@@ -0,0 +1,183 @@
+//! Speedsolving timer: an inspection countdown after a scramble, then a
+//! running clock from the first twist until the puzzle is solved.
+//!
+//! Pure state machine over caller-supplied `Instant`s, so `HypercubeApp`
+//! drives it from messages and frame ticks and tests can drive it with
+//! made-up times.
+
+use std::time::{Duration, Instant};
+
+/// Inspection time after a scramble, as in WCA competition. Once it runs
+/// out the clock starts on its own, twist or not.
+pub(crate) const INSPECTION: Duration = Duration::from_secs(15);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum SolveTimer {
+    /// No timed solve in progress (never scrambled, or reset since).
+    #[default]
+    Idle,
+    /// Scrambled, counting down until the first twist.
+    Inspecting { started: Instant },
+    /// Solving since `started`.
+    Running { started: Instant },
+    /// Solved; the final time stays up until the next scramble or reset.
+    Finished { elapsed: Duration },
+}
+
+impl SolveTimer {
+    /// Whether the display changes over time, i.e. needs frame ticks.
+    pub(crate) fn is_live(self) -> bool {
+        matches!(
+            self,
+            SolveTimer::Inspecting { .. } | SolveTimer::Running { .. }
+        )
+    }
+
+    /// A scramble just finished: start inspecting.
+    pub(crate) fn scrambled(&mut self, now: Instant) {
+        *self = SolveTimer::Inspecting { started: now };
+    }
+
+    /// A twist was made. Only the first one after a scramble matters: it
+    /// ends inspection and starts the clock.
+    pub(crate) fn twisted(&mut self, now: Instant) {
+        if let SolveTimer::Inspecting { .. } = self {
+            *self = SolveTimer::Running { started: now };
+        }
+    }
+
+    /// The move committed at `at` solved the puzzle. Returns the solve time
+    /// if this stopped a running clock.
+    pub(crate) fn solved(&mut self, at: Instant) -> Option<Duration> {
+        let SolveTimer::Running { started } = *self else {
+            return None;
+        };
+        let elapsed = at.saturating_duration_since(started);
+        *self = SolveTimer::Finished { elapsed };
+        Some(elapsed)
+    }
+
+    /// How long the clock has been running at `now`, if it is.
+    pub(crate) fn running_for(self, now: Instant) -> Option<Duration> {
+        match self {
+            SolveTimer::Running { started } => Some(now.saturating_duration_since(started)),
+            _ => None,
+        }
+    }
+
+    /// Starts the clock once inspection has run out without a twist.
+    pub(crate) fn tick(&mut self, now: Instant) {
+        if let SolveTimer::Inspecting { started } = *self
+            && now.saturating_duration_since(started) >= INSPECTION
+        {
+            *self = SolveTimer::Running {
+                started: started + INSPECTION,
+            };
+        }
+    }
+
+    /// Text for the timer readout at `now`.
+    pub(crate) fn display(self, now: Instant) -> String {
+        match self {
+            SolveTimer::Idle => format_solve_time(Duration::ZERO),
+            SolveTimer::Inspecting { started } => {
+                let remaining = INSPECTION.saturating_sub(now.saturating_duration_since(started));
+                format!("Inspect {}", remaining.as_secs_f32().ceil() as u32)
+            }
+            SolveTimer::Running { started } => {
+                format_solve_time(now.saturating_duration_since(started))
+            }
+            SolveTimer::Finished { elapsed } => format_solve_time(elapsed),
+        }
+    }
+}
+
+/// Formats a solve time the way speedsolving timers do: `12.34`,
+/// `1:02.34`, `1:02:03.45` (hundredths, truncated).
+pub(crate) fn format_solve_time(time: Duration) -> String {
+    let hundredths = time.as_millis() / 10;
+    let (seconds, hundredths) = (hundredths / 100, hundredths % 100);
+    let (minutes, seconds) = (seconds / 60, seconds % 60);
+    let (hours, minutes) = (minutes / 60, minutes % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}.{hundredths:02}")
+    } else if minutes > 0 {
+        format!("{minutes}:{seconds:02}.{hundredths:02}")
+    } else {
+        format!("{seconds}.{hundredths:02}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_solve_time_switches_units_at_minutes_and_hours() {
+        assert_eq!(format_solve_time(Duration::ZERO), "0.00");
+        assert_eq!(format_solve_time(Duration::from_millis(12_345)), "12.34");
+        assert_eq!(format_solve_time(Duration::from_millis(62_349)), "1:02.34");
+        assert_eq!(
+            format_solve_time(Duration::from_millis(3_723_450)),
+            "1:02:03.45"
+        );
+    }
+
+    #[test]
+    fn first_twist_after_scramble_starts_clock_and_solve_stops_it() {
+        let t0 = Instant::now();
+        let mut timer = SolveTimer::default();
+        timer.twisted(t0);
+        assert_eq!(
+            timer,
+            SolveTimer::Idle,
+            "twists don't start an unscrambled timer"
+        );
+
+        timer.scrambled(t0);
+        assert_eq!(
+            timer.display(t0 + Duration::from_millis(2_500)),
+            "Inspect 13"
+        );
+        timer.twisted(t0 + Duration::from_secs(4));
+        timer.twisted(t0 + Duration::from_secs(5));
+        assert_eq!(
+            timer,
+            SolveTimer::Running {
+                started: t0 + Duration::from_secs(4)
+            }
+        );
+
+        let elapsed = timer.solved(t0 + Duration::from_secs(64));
+        assert_eq!(elapsed, Some(Duration::from_secs(60)));
+        assert_eq!(timer.display(t0 + Duration::from_secs(99)), "1:00.00");
+        assert_eq!(timer.solved(t0 + Duration::from_secs(70)), None);
+        assert!(!timer.is_live());
+    }
+
+    #[test]
+    fn clock_starts_on_its_own_when_inspection_runs_out() {
+        let t0 = Instant::now();
+        let mut timer = SolveTimer::default();
+        timer.scrambled(t0);
+        timer.tick(t0 + Duration::from_secs(14));
+        assert!(matches!(timer, SolveTimer::Inspecting { .. }));
+        timer.tick(t0 + Duration::from_secs(16));
+        assert_eq!(
+            timer,
+            SolveTimer::Running {
+                started: t0 + INSPECTION
+            }
+        );
+        assert_eq!(timer.display(t0 + Duration::from_secs(20)), "5.00");
+    }
+
+    #[test]
+    fn solve_without_running_clock_is_not_timed() {
+        let t0 = Instant::now();
+        let mut timer = SolveTimer::default();
+        assert_eq!(timer.solved(t0), None);
+        timer.scrambled(t0);
+        assert_eq!(timer.solved(t0), None);
+    }
+}